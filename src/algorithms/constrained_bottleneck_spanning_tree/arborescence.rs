@@ -0,0 +1,104 @@
+use log::{debug, trace};
+use crate::algorithms::constrained_bottleneck_spanning_tree::constraints::Constraints;
+use crate::algorithms::constrained_bottleneck_spanning_tree::solver::Solution;
+use crate::algorithms::min_sum_spanning_tree::edmonds::Edmonds;
+use crate::algorithms::util::Util;
+use crate::datastructures::graph::mutable_graph::MutableGraph;
+use crate::error::CbstuError;
+
+/// Directed CBSTU: the tree is an arborescence in which every node is reached from `root`. Directed links can only be
+/// used from their source to their target, undirected links either way.
+pub struct Arborescence {
+    root: usize,
+}
+
+impl Arborescence {
+    pub fn new(root: usize) -> Arborescence {
+        Arborescence { root }
+    }
+
+    /// Solves the preprocessed graph under the link constraints, see [`Arborescence::run`].
+    pub fn solve(&self, graph: &MutableGraph, budget: f64) -> Result<Solution, CbstuError> {
+        if self.root >= graph.nodes().len() {
+            return Err(CbstuError::MalformedInput(format!("root {} is not a node of the graph", self.root)));
+        }
+        Constraints::solve(graph, budget, |graph, budget| self.run(graph, budget))
+    }
+
+    /// Bisection over the thresholds like Berman's algorithm, with the min-cost arborescence of Chu-Liu and Edmonds
+    /// in place of the min-cost spanning tree. A threshold is feasible if that arborescence fits the budget and holds
+    /// every required link.
+    pub fn run(&self, graph: &MutableGraph, budget: f64) -> Result<(MutableGraph, f64, f64), CbstuError> {
+        trace!("Solving constrained bottleneck arborescence problem from root {}", self.root);
+        let unique_weights = Util::sorted_unique_weights(graph)?;
+        let required = Util::required_links(graph);
+        let mut min = 0;
+        let mut max = unique_weights.len();
+        let mut final_st = None;
+        while min < max {
+            let pivot = (min + max) / 2;
+            match Edmonds::run(&graph.smaller_or_eq_than(unique_weights[pivot]), self.root) {
+                Ok((tree, cost, bottleneck)) if cost <= budget
+                    && Util::required_in(&tree, tree.edges().iter().copied()) == required => {
+                    debug!("Feasible pivot [bottleneck: {}, cost: {}]", bottleneck, cost);
+                    final_st = Some((tree, cost, bottleneck));
+                    max = pivot;
+                }
+                Ok(_) | Err(CbstuError::DisconnectedGraph) | Err(CbstuError::EmptyEdgeSet) => {
+                    debug!("Infeasible pivot {}", unique_weights[pivot]);
+                    min = pivot + 1;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        final_st.ok_or_else(|| match Edmonds::run(graph, self.root) {
+            Ok(_) => CbstuError::InfeasibleBudget,
+            Err(err) => err,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithms::constrained_bottleneck_spanning_tree::arborescence::Arborescence;
+    use crate::algorithms::constrained_bottleneck_spanning_tree::solver::{solver_by_name, SolutionStatus};
+    use crate::algorithms::constrained_bottleneck_spanning_tree::test_graphs::{build, nodes, SQUARE};
+    use crate::datastructures::graph::mutable_graph::MutableGraph;
+    use crate::error::CbstuError;
+
+    fn square_graph(directed: bool) -> MutableGraph {
+        build(nodes(4), &SQUARE, |_, edge| edge.directed(directed))
+    }
+
+    #[test]
+    fn test_undirected_matches_solver() {
+        let graph = square_graph(false);
+        let berman = solver_by_name("berman").unwrap();
+        for budget in [0.0, 2.0, 5.0, 9.0, 13.0, 15.0, 30.0] {
+            for root in 0..4 {
+                let solution = Arborescence::new(root).solve(&graph, budget).unwrap();
+                let expected = berman.solve(&graph, budget).unwrap();
+                assert_eq!(solution.bottleneck(), expected.bottleneck(), "budget {} from root {}", budget, root);
+                assert!(solution.cost() <= budget);
+            }
+        }
+    }
+
+    #[test]
+    fn test_directed_cycle() {
+        // the ring 0 -> 1 -> 2 -> 3 -> 0 plus the chord 0 -> 2
+        let graph = square_graph(true);
+        // (budget, bottleneck, cost) from root 0: node 1 is only entered by (0,1) and node 3 by (2,3)
+        for (budget, bottleneck, cost) in [(0.0, -2.0, 0.0), (3.0, -5.0, 3.0), (8.0, -6.0, 7.0), (12.0, -8.0, 12.0), (30.0, -10.0, 15.0)] {
+            let solution = Arborescence::new(0).solve(&graph, budget).unwrap();
+            assert_eq!(solution.status(), SolutionStatus::Feasible);
+            assert_eq!((solution.bottleneck(), solution.cost()), (bottleneck, cost), "budget {}", budget);
+            assert_eq!(solution.tree().unwrap().edges().len(), 3);
+        }
+        // from node 1 every node is reached along the ring
+        let solution = Arborescence::new(1).solve(&graph, 0.0).unwrap();
+        assert_eq!(solution.bottleneck(), -2.0);
+        assert_eq!(Arborescence::new(1).solve(&graph, -1.0).unwrap().status(), SolutionStatus::Infeasible);
+        assert!(matches!(Arborescence::new(4).solve(&graph, 5.0), Err(CbstuError::MalformedInput(_))));
+    }
+}
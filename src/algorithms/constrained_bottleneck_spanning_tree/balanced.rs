@@ -0,0 +1,183 @@
+use log::{debug, trace};
+use crate::algorithms::constrained_bottleneck_spanning_tree::solver::CbstuSolver;
+use crate::algorithms::util::Util;
+use crate::datastructures::graph::mutable_graph::MutableGraph;
+use crate::error::CbstuError;
+
+/// Result of the balanced variant, in the (inverted) weights of the solved graph: `bottleneck` is the largest weight
+/// of the tree and `peak` the smallest, so the capacities of the tree lie between `-bottleneck` and `-peak`.
+#[derive(Debug, Clone)]
+pub struct BalancedSolution {
+    tree: MutableGraph,
+    cost: f64,
+    bottleneck: f64,
+    peak: f64,
+}
+
+impl BalancedSolution {
+    pub fn tree(&self) -> &MutableGraph {
+        &self.tree
+    }
+
+    pub fn cost(&self) -> f64 {
+        self.cost
+    }
+
+    pub fn bottleneck(&self) -> f64 {
+        self.bottleneck
+    }
+
+    pub fn peak(&self) -> f64 {
+        self.peak
+    }
+
+    /// Difference between the largest and the smallest capacity of the tree.
+    pub fn spread(&self) -> f64 {
+        self.bottleneck - self.peak
+    }
+}
+
+/// Balanced CBSTU: a spanning tree within budget whose capacities are as uniform as possible.
+pub struct Balanced {
+    solver: Box<dyn CbstuSolver>,
+}
+
+impl Balanced {
+    /// Uses `solver` for the bottleneck threshold search of every window.
+    pub fn new(solver: Box<dyn CbstuSolver>) -> Balanced {
+        Balanced { solver }
+    }
+
+    /// Sweeps the lower threshold over the weights of the preprocessed graph, from the largest capacity down. Each
+    /// window keeps the edges with a weight of at least the threshold, and the bottleneck solver finds the smallest
+    /// largest weight within budget. The optimal tree is found in the window of its smallest weight, so the best
+    /// spread over all windows is optimal. Windows up to the smallest weight of a tree already found give nothing
+    /// better and are skipped; the sweep stops once a window is infeasible, as all later windows are subsets of it, or
+    /// once the threshold passes every usable copy of a required link. Other errors of the solver are returned.
+    pub fn solve(&self, graph: &MutableGraph, budget: f64) -> Result<BalancedSolution, CbstuError> {
+        trace!("Solving balanced spanning tree problem with {}", self.solver.name());
+        let unique_weights = Util::sorted_unique_weights(graph)?;
+        let ceiling = Self::ceiling(graph);
+        let mut best: Option<BalancedSolution> = None;
+        let mut index = 0;
+        while index < unique_weights.len() && unique_weights[index] <= ceiling {
+            let lower = unique_weights[index];
+            let window = graph.filter(|edge| graph.weight(edge) >= lower);
+            let solution = match self.solver.solve(&window, budget) {
+                Ok(solution) => solution,
+                Err(err) if best.is_none() => return Err(err),
+                Err(CbstuError::DisconnectedGraph | CbstuError::EmptyEdgeSet | CbstuError::InfeasibleBudget | CbstuError::ConstraintConflict { .. }) => break,
+                Err(err) => return Err(err),
+            };
+            let tree = match solution.into_tree() {
+                Some(tree) => tree,
+                None if best.is_none() => return Err(CbstuError::InfeasibleBudget),
+                None => break,
+            };
+            let cost = tree.calculate_total_cost();
+            let bottleneck = tree.edges().iter().map(|&edge| tree.weight(edge)).fold(f64::NEG_INFINITY, f64::max);
+            let peak = tree.edges().iter().map(|&edge| tree.weight(edge)).fold(f64::INFINITY, f64::min);
+            debug!("Window from {} [bottleneck: {}, spread: {}]", lower, bottleneck, bottleneck - peak);
+            let candidate = BalancedSolution { tree, cost, bottleneck, peak };
+            if best.as_ref().map_or(true, |best| candidate.spread() < best.spread()) {
+                best = Some(candidate);
+            }
+            if best.as_ref().unwrap().spread() == 0.0 {
+                break;
+            }
+            index += unique_weights[index..].iter().take_while(|&&weight| weight <= peak).count().max(1);
+        }
+        Ok(best.unwrap())
+    }
+
+    /// Largest threshold whose window still holds a usable copy of every required link.
+    fn ceiling(graph: &MutableGraph) -> f64 {
+        let mut required: Vec<((usize, usize), f64)> = graph.edges().iter()
+            .filter(|&&edge| graph.is_required(edge) && !graph.is_forbidden(edge))
+            .map(|&edge| {
+                let (u, v) = graph.endpoints(edge);
+                ((u.min(v), u.max(v)), graph.weight(edge))
+            })
+            .collect();
+        // the heaviest copy of each link comes first and stays
+        required.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.total_cmp(&a.1)));
+        required.dedup_by_key(|(link, _)| *link);
+        required.iter().map(|&(_, weight)| weight).fold(f64::INFINITY, f64::min)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use crate::algorithms::constrained_bottleneck_spanning_tree::balanced::Balanced;
+    use crate::algorithms::constrained_bottleneck_spanning_tree::solver::{solver_by_name, CbstuSolver, Solution, SOLVER_NAMES};
+    use crate::algorithms::constrained_bottleneck_spanning_tree::test_graphs::{build, graph, nodes, Link};
+    use crate::datastructures::graph::mutable_graph::MutableGraph;
+    use crate::error::CbstuError;
+
+    /// Berman's algorithm for the first window, `error` for every later one.
+    struct FailingLater {
+        calls: AtomicUsize,
+        error: fn() -> CbstuError,
+    }
+
+    impl CbstuSolver for FailingLater {
+        fn name(&self) -> &'static str {
+            "failing_later"
+        }
+
+        fn solve(&self, graph: &MutableGraph, budget: f64) -> Result<Solution, CbstuError> {
+            match self.calls.fetch_add(1, Ordering::SeqCst) {
+                0 => solver_by_name("berman").unwrap().solve(graph, budget),
+                _ => Err((self.error)()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_spread() {
+        let graph = graph(4, &[(0, 1, 4.0, 9.0, 3.0), (1, 2, 10.0, 12.0, 2.0), (2, 3, 5.0, 11.0, 4.0), (3, 0, 1.0, 10.0, 5.0), (0, 2, 2.0, 6.0, 1.0)]);
+        for name in SOLVER_NAMES {
+            let balanced = Balanced::new(solver_by_name(name).unwrap());
+            // (0,1), (0,2) and (2,3) at 4, 2 and 5, then (0,2) upgraded to 6, then (1,2) at 10 with (3,0) and (0,1)
+            // upgraded to 10 and 9
+            for (budget, spread) in [(0.0, 3.0), (1.0, 2.0), (7.0, 2.0), (8.0, 1.0), (100.0, 1.0)] {
+                let solution = balanced.solve(&graph, budget).unwrap();
+                assert_eq!(solution.spread(), spread, "{} with budget {}", name, budget);
+                assert!(solution.cost() <= budget);
+                assert!(solution.tree().is_spanning_tree());
+            }
+            assert!(matches!(balanced.solve(&graph, -1.0), Err(CbstuError::InfeasibleBudget)));
+        }
+    }
+
+    #[test]
+    fn test_required_link() {
+        let links: [Link; 5] = [(0, 1, 4.0, 9.0, 3.0), (1, 2, 10.0, 12.0, 2.0), (2, 3, 5.0, 11.0, 4.0), (3, 0, 1.0, 10.0, 5.0), (0, 2, 2.0, 6.0, 1.0)];
+        let graph = build(nodes(4), &links, |i, edge| edge.required(i == 1));
+        for name in SOLVER_NAMES {
+            let balanced = Balanced::new(solver_by_name(name).unwrap());
+            // (1,2) at 10 with (2,3) and (0,1) at 5 and 4, then (0,2) upgraded to 6 instead of (0,1), then (2,3) and
+            // (0,1) upgraded to 11 and 9, then (3,0) upgraded to 10 instead of (2,3)
+            for (budget, spread) in [(0.0, 6.0), (1.0, 5.0), (7.0, 2.0), (8.0, 1.0), (100.0, 1.0)] {
+                let solution = balanced.solve(&graph, budget).unwrap();
+                assert_eq!(solution.spread(), spread, "{} with budget {}", name, budget);
+                assert!(solution.cost() <= budget);
+                assert!(solution.tree().edges().iter().any(|&edge| solution.tree().is_required(edge)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_solver_errors() {
+        let graph = graph(4, &[(0, 1, 4.0, 9.0, 3.0), (1, 2, 10.0, 12.0, 2.0), (2, 3, 5.0, 11.0, 4.0), (3, 0, 1.0, 10.0, 5.0), (0, 2, 2.0, 6.0, 1.0)]);
+        // an infeasible later window ends the sweep with the tree of the first one, any other error is returned
+        let disconnected = Balanced::new(Box::new(FailingLater { calls: AtomicUsize::new(0), error: || CbstuError::DisconnectedGraph }));
+        let first = solver_by_name("berman").unwrap().solve(&graph, 0.0).unwrap().into_tree().unwrap();
+        let weights: Vec<f64> = first.edges().iter().map(|&edge| first.weight(edge)).collect();
+        let spread = weights.iter().copied().fold(f64::NEG_INFINITY, f64::max) - weights.iter().copied().fold(f64::INFINITY, f64::min);
+        assert_eq!(disconnected.solve(&graph, 0.0).unwrap().spread(), spread);
+        let malformed = Balanced::new(Box::new(FailingLater { calls: AtomicUsize::new(0), error: || CbstuError::MalformedInput(String::from("failing")) }));
+        assert!(matches!(malformed.solve(&graph, 0.0), Err(CbstuError::MalformedInput(_))));
+    }
+}
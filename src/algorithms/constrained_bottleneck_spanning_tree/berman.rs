@@ -1,25 +1,26 @@
-use std::rc::Rc;
 use log::{debug, info, trace};
+use crate::algorithms::constrained_bottleneck_spanning_tree::constraints::Constraints;
+use crate::algorithms::constrained_bottleneck_spanning_tree::solver::{CbstuSolver, Solution};
 use crate::algorithms::min_sum_spanning_tree::kruskal::CalculationType;
 use crate::algorithms::util::{PivotResult, Util};
 use crate::datastructures::graph::mutable_graph::MutableGraph;
+use crate::error::CbstuError;
 
 // Algorithm based on the paper "The Constrained Bottleneck Problem in Networks" by Berman et al.
 pub struct Berman();
 
 impl Berman {
-    pub fn run(graph: &mut MutableGraph, budget: f64) -> (Option<MutableGraph>, f64, f64) {
+    pub fn run(graph: &MutableGraph, budget: f64) -> Result<(MutableGraph, f64, f64), CbstuError> {
         trace!("Solving Constrained bottleneck spanning tree problem with Berman's algorithm");
-        let mut unique_weights = Util::unique_weight_list(graph.edges(), f64::NEG_INFINITY, 0.0);
-        unique_weights.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let unique_weights = Util::sorted_unique_weights(graph)?;
         Self::bisection_search(graph, &unique_weights, budget)
     }
 
-    fn naive_search(graph: &mut MutableGraph, unique_weights: &Vec<f64>, budget: f64) -> (Option<MutableGraph>, f64, f64) {
+    fn naive_search(graph: &MutableGraph, unique_weights: &Vec<f64>, budget: f64) -> Result<(MutableGraph, f64, f64), CbstuError> {
         trace!("Naive search");
         let mut final_st = None;
         for pivot_weight in unique_weights {
-            if let PivotResult::Feasible(st) = Util::check_pivot(graph, *pivot_weight, budget) {
+            if let PivotResult::Feasible(st) = Util::check_pivot(graph, *pivot_weight, budget)? {
                 final_st = Some(st);
                 break;
             }
@@ -27,32 +28,31 @@ impl Berman {
         match final_st {
             Some(st) => {
                 trace!("Naive search finished [bottleneck {}, cost {}]", st.2, st.1);
-                (Some(st.0), st.1, st.2)
+                Ok(st)
             }
             None => {
                 info!("No valid solution found");
-                (None, 0.0, 0.0)
+                Err(Util::infeasibility_reason(graph))
             }
         }
     }
 
-    fn bisection_search(graph: &MutableGraph, unique_weights: &Vec<f64>, budget: f64) -> (Option<MutableGraph>, f64, f64) {
+    fn bisection_search(graph: &MutableGraph, unique_weights: &Vec<f64>, budget: f64) -> Result<(MutableGraph, f64, f64), CbstuError> {
         trace!("Bisection search");
         let mut max = unique_weights.len() - 1;
         let mut min = 0;
         let mut pivot;
         let mut final_st = None;
-        let mut cost = 0.0;
-        let mut bottleneck = 0.0;
         while min <= max {
             pivot = ((max as f64 + min as f64) / 2.0).floor() as usize;
-            match Util::check_pivot(graph, unique_weights[pivot], budget) {
+            match Util::check_pivot(graph, unique_weights[pivot], budget)? {
                 PivotResult::Feasible(st) => {
                     debug!("Feasible pivot [bottleneck: {}, cost: {}]", st.2, st.1);
+                    final_st = Some(st);
+                    if pivot == 0 {
+                        break;
+                    }
                     max = pivot - 1;
-                    final_st = Some(st.0);
-                    cost = st.1;
-                    bottleneck = st.2;
                 }
                 PivotResult::Infeasible => {
                     debug!("Infeasible pivot");
@@ -60,7 +60,7 @@ impl Berman {
                 }
             }
         }
-        (final_st, cost, bottleneck)
+        final_st.ok_or_else(|| Util::infeasibility_reason(graph))
     }
 
     // fn dual_bound_search(graph: &MutableGraph, unique_weights: &Vec<f64>, budget: f64) -> (Option<MutableGraph>, f64, f64) {
@@ -111,4 +111,14 @@ impl Berman {
     //     trace!("Dual bound search finished [bottleneck: {}, cost: {}, iterations: {}]", bottleneck, cost, iterations);
     //     (final_st, cost, bottleneck)
     // }
-}
\ No newline at end of file
+}
+
+impl CbstuSolver for Berman {
+    fn name(&self) -> &'static str {
+        "berman"
+    }
+
+    fn solve(&self, graph: &MutableGraph, budget: f64) -> Result<Solution, CbstuError> {
+        Constraints::solve(graph, budget, Self::run)
+    }
+}
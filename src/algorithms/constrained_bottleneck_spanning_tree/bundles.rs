@@ -0,0 +1,190 @@
+use log::trace;
+use crate::algorithms::constrained_bottleneck_spanning_tree::constraints::{ConstrainedGraph, Constraints};
+use crate::algorithms::min_sum_spanning_tree::kruskal::CalculationType;
+use crate::algorithms::util::{BoundedSolution, ThresholdCheck, Util};
+use crate::datastructures::graph::mutable_graph::MutableGraph;
+use crate::error::CbstuError;
+
+/// Above this number of bundles in reach of a threshold the bundles to pay for are chosen by local search.
+const EXACT_LIMIT: usize = 12;
+
+/// Tree of a threshold graph with the bundles it pays for and its cost including their fixed charges.
+struct Candidate {
+    tree: MutableGraph,
+    cost: f64,
+    bundles: Vec<usize>,
+}
+
+/// CBSTU where upgrades in a bundle share a fixed charge, `costs[b]` for bundle `b`, paid once when any of them is
+/// part of the tree on top of their own costs.
+pub struct Bundles {
+    costs: Vec<f64>,
+}
+
+impl Bundles {
+    pub fn new(costs: Vec<f64>) -> Bundles {
+        Bundles { costs }
+    }
+
+    /// Bisection over the thresholds of the preprocessed graph under its link constraints. Once the bundles to pay for
+    /// are fixed the cheapest tree is a min-cost spanning tree without the upgrades of the other bundles, so a threshold
+    /// is decided by trying every set of bundles when at most `EXACT_LIMIT` of them are in reach. Otherwise bundles are
+    /// opened and closed one at a time while that lowers the cost, and a threshold this local search cannot fit in the
+    /// budget stays undecided unless the tree with all bundles free already exceeds it. The cost of the solution holds
+    /// the fixed charges of the bundles it pays for.
+    pub fn solve(&self, graph: &MutableGraph, budget: f64) -> Result<BoundedSolution, CbstuError> {
+        trace!("Solving constrained bottleneck spanning tree problem with {} upgrade bundles", self.costs.len());
+        if let Some(&edge) = graph.edges().iter().find(|&&edge| graph.bundle(edge).map_or(false, |bundle| bundle >= self.costs.len())) {
+            return Err(CbstuError::MalformedInput(format!("edge {:?} belongs to an unknown bundle", graph.endpoints(edge))));
+        }
+        let unique_weights = Util::sorted_unique_weights(graph)?;
+        let reduced = Constraints::reduce(graph, Some(budget))?;
+        Constraints::check_budget(graph, budget)?;
+        let (found, bound) = Util::bisect_thresholds(&unique_weights, |threshold| {
+            self.check(&reduced, &reduced.graph().smaller_or_eq_than(threshold), reduced.budget(budget))
+        });
+        let candidate = match found {
+            Some(candidate) => candidate,
+            None => return Err(Util::infeasibility_reason(reduced.graph())),
+        };
+        let (tree, cost) = reduced.restore(graph, &candidate.tree);
+        let cost = cost + candidate.bundles.iter().map(|&bundle| self.costs[bundle]).sum::<f64>();
+        Ok(BoundedSolution::new(tree, vec![cost], bound))
+    }
+
+    /// Bundles whose fixed charge `tree` pays.
+    pub fn paid(tree: &MutableGraph) -> Vec<usize> {
+        let mut bundles: Vec<usize> = tree.edges().iter().filter_map(|&edge| tree.bundle(edge)).collect();
+        bundles.sort();
+        bundles.dedup();
+        bundles
+    }
+
+    fn check(&self, reduced: &ConstrainedGraph, graph: &MutableGraph, budget: f64) -> ThresholdCheck<Candidate> {
+        let mut open = vec![false; self.costs.len()];
+        graph.edges().iter().filter_map(|&edge| graph.bundle(edge)).for_each(|bundle| open[bundle] = true);
+        let in_reach: Vec<usize> = (0..self.costs.len()).filter(|&bundle| open[bundle]).collect();
+        // without fixed charges the tree is as cheap as it gets
+        let free = match self.cheapest_tree(reduced, graph, &open) {
+            Some(candidate) => candidate,
+            None => return ThresholdCheck::Infeasible,
+        };
+        if free.cost - free.bundles.iter().map(|&bundle| self.costs[bundle]).sum::<f64>() > budget {
+            return ThresholdCheck::Infeasible;
+        }
+        let best = match in_reach.len() <= EXACT_LIMIT {
+            true => (0..1usize << in_reach.len())
+                .filter_map(|set| {
+                    in_reach.iter().enumerate().for_each(|(i, &bundle)| open[bundle] = set >> i & 1 == 1);
+                    self.cheapest_tree(reduced, graph, &open)
+                })
+                .min_by(|a, b| a.cost.total_cmp(&b.cost)),
+            false => Some(self.local_search(reduced, graph, &in_reach, free)),
+        };
+        match best {
+            Some(candidate) if candidate.cost <= budget => ThresholdCheck::Feasible(candidate),
+            _ if in_reach.len() <= EXACT_LIMIT => ThresholdCheck::Infeasible,
+            _ => ThresholdCheck::Unknown,
+        }
+    }
+
+    /// Opens or closes one bundle of `in_reach` at a time, starting from the bundles used by `start`, as long as the
+    /// cost drops.
+    fn local_search(&self, reduced: &ConstrainedGraph, graph: &MutableGraph, in_reach: &[usize], start: Candidate) -> Candidate {
+        let mut open = vec![false; self.costs.len()];
+        start.bundles.iter().for_each(|&bundle| open[bundle] = true);
+        let mut best = start;
+        let mut improved = true;
+        while improved {
+            improved = false;
+            for &bundle in in_reach {
+                open[bundle] = !open[bundle];
+                match self.cheapest_tree(reduced, graph, &open) {
+                    Some(candidate) if candidate.cost < best.cost => {
+                        trace!("Local search step [bundle: {}, cost: {}]", bundle, candidate.cost);
+                        open.iter_mut().for_each(|open| *open = false);
+                        candidate.bundles.iter().for_each(|&bundle| open[bundle] = true);
+                        best = candidate;
+                        improved = true;
+                    }
+                    _ => open[bundle] = !open[bundle],
+                }
+            }
+        }
+        best
+    }
+
+    /// Min-cost spanning tree holding every required link without the upgrades of closed bundles, with its cost
+    /// including the fixed charges of the bundles it uses.
+    fn cheapest_tree(&self, reduced: &ConstrainedGraph, graph: &MutableGraph, open: &[bool]) -> Option<Candidate> {
+        let (tree, cost, _) = graph.filter(|edge| graph.bundle(edge).map_or(true, |bundle| open[bundle]))
+            .mst(CalculationType::Cost).ok()?;
+        if !reduced.satisfies(&tree) {
+            return None;
+        }
+        let bundles = Self::paid(&tree);
+        let cost = cost + bundles.iter().map(|&bundle| self.costs[bundle]).sum::<f64>();
+        Some(Candidate { tree, cost, bundles })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithms::constrained_bottleneck_spanning_tree::bundles::{Bundles, EXACT_LIMIT};
+    use crate::algorithms::constrained_bottleneck_spanning_tree::test_graphs::{build, nodes, Link};
+    use crate::datastructures::graph::mutable_graph::MutableGraph;
+    use crate::error::CbstuError;
+
+    /// The square with (1,2) and (3,0) sharing a conduit: the bundle costs 4 and makes both upgrades free.
+    fn square_graph() -> MutableGraph {
+        let links = [(0, 1, 5.0, 10.0, 4.0), (1, 2, 2.0, 8.0, 0.0), (2, 3, 6.0, 12.0, 5.0), (3, 0, 3.0, 9.0, 0.0), (0, 2, 1.0, 20.0, 6.0)];
+        build(nodes(4), &links, |i, edge| edge.bundle(if i == 1 || i == 3 { Some(0) } else { None }))
+    }
+
+    #[test]
+    fn test_bundles() {
+        let graph = square_graph();
+        // (budget, bottleneck, cost, bundle paid)
+        for (budget, bottleneck, cost, paid) in [
+            (0.0, -3.0, 0.0, false),
+            (3.9, -3.0, 0.0, false),
+            (4.0, -6.0, 4.0, true),
+            (8.0, -8.0, 8.0, true),
+            (9.0, -8.0, 8.0, true),
+            (13.0, -9.0, 13.0, true),
+            // at bottleneck -10 neither bundled link is good enough
+            (20.0, -10.0, 15.0, false),
+        ] {
+            let solution = Bundles::new(vec![4.0]).solve(&graph, budget).unwrap();
+            assert_eq!((solution.bottleneck(), solution.cost()), (bottleneck, cost), "budget {}", budget);
+            assert!(solution.is_optimal());
+            assert_eq!(!Bundles::paid(solution.tree()).is_empty(), paid);
+            assert!(solution.tree().is_spanning_tree());
+        }
+        assert!(matches!(Bundles::new(vec![]).solve(&graph, 5.0), Err(CbstuError::MalformedInput(_))));
+    }
+
+    #[test]
+    fn test_local_search() {
+        // the triangle 0-1-2 whose upgrades cost 1, 1 and 2, with (0,1) and (0,2) sharing bundle 0 and (1,2) in bundle
+        // 1, both charged 3, under pendant links whose own bundles bring more than EXACT_LIMIT bundles in reach
+        let mut links: Vec<Link> = vec![(0, 1, 5.0, 10.0, 1.0), (1, 2, 5.0, 10.0, 1.0), (0, 2, 5.0, 10.0, 2.0)];
+        let mut bundles = vec![0, 1, 0];
+        let mut costs = vec![3.0, 3.0];
+        for p in 0..=EXACT_LIMIT {
+            links.push((0, 3 + p, 100.0, 101.0, 1.0));
+            bundles.push(2 + p);
+            costs.push(1.0);
+        }
+        let graph = build(nodes(4 + EXACT_LIMIT), &links, |i, edge| edge.bundle(Some(bundles[i])));
+        // the cheapest upgrades pay both charges for 8, closing bundle 1 leaves the upgrades of bundle 0 for 6
+        let solution = Bundles::new(costs.clone()).solve(&graph, 6.0).unwrap();
+        assert_eq!((solution.bottleneck(), solution.cost()), (-10.0, 6.0));
+        assert_eq!(Bundles::paid(solution.tree()), vec![0]);
+        assert!(solution.is_optimal());
+        // the search cannot prove that no pair of upgrades fits a budget of 5
+        let solution = Bundles::new(costs).solve(&graph, 5.0).unwrap();
+        assert_eq!((solution.bottleneck(), solution.cost()), (-5.0, 0.0));
+        assert!(!solution.is_optimal() && solution.bound() <= -10.0);
+    }
+}
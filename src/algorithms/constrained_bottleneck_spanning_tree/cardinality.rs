@@ -0,0 +1,174 @@
+use log::{debug, trace};
+use crate::algorithms::constrained_bottleneck_spanning_tree::constraints::Constraints;
+use crate::algorithms::constrained_bottleneck_spanning_tree::solver::Solution;
+use crate::algorithms::util::Util;
+use crate::datastructures::graph::edge_store::EdgeId;
+use crate::datastructures::graph::mutable_graph::MutableGraph;
+use crate::error::CbstuError;
+
+/// CBSTU with at most `max_upgrades` upgraded links in the tree, on its own (infinite budget) or next to the budget.
+/// Only upgrades of existing links count, unless built candidate links are counted as well.
+pub struct CardinalityLimit {
+    max_upgrades: usize,
+    count_builds: bool,
+}
+
+impl CardinalityLimit {
+    pub fn new(max_upgrades: usize) -> CardinalityLimit {
+        CardinalityLimit { max_upgrades, count_builds: false }
+    }
+
+    /// Counts built candidate links towards the limit too.
+    pub fn count_builds(mut self, count_builds: bool) -> CardinalityLimit {
+        self.count_builds = count_builds;
+        self
+    }
+
+    /// Solves the preprocessed graph under the link constraints, see [`CardinalityLimit::run`].
+    pub fn solve(&self, graph: &MutableGraph, budget: f64) -> Result<Solution, CbstuError> {
+        Constraints::solve(graph, budget, |graph, budget| self.run(graph, budget))
+    }
+
+    /// Bisection over the thresholds like Berman's algorithm. Without a budget a threshold is feasible if the Kruskal
+    /// variant that takes cost-0 base edges before upgrades needs at most `max_upgrades` of them. With a budget the
+    /// min-cost spanning tree is brought down to `max_upgrades` upgrades by cheapest swaps: the optimal trees for one
+    /// upgrade less differ by a single swap of an upgraded edge for a non-upgraded one.
+    pub fn run(&self, graph: &MutableGraph, budget: f64) -> Result<(MutableGraph, f64, f64), CbstuError> {
+        trace!("Solving constrained bottleneck spanning tree problem with at most {} upgrades", self.max_upgrades);
+        let unique_weights = Util::sorted_unique_weights(graph)?;
+        // costs do not keep the required edges in the tree without a budget, so they are counted
+        let required = Util::required_links(graph);
+        let mut min = 0;
+        let mut max = unique_weights.len();
+        let mut final_st = None;
+        while min < max {
+            let pivot = (min + max) / 2;
+            let graph_below_pivot = graph.smaller_or_eq_than(unique_weights[pivot]);
+            let tree = match budget.is_finite() {
+                true => self.cheapest_tree(&graph_below_pivot, budget),
+                false => self.fewest_upgrades_tree(&graph_below_pivot),
+            };
+            match tree.filter(|tree| Util::required_in(graph, tree.iter().copied()) == required) {
+                Some(tree) => {
+                    let (cost, bottleneck) = Self::cost_and_bottleneck(graph, &tree);
+                    debug!("Feasible pivot [bottleneck: {}, cost: {}]", bottleneck, cost);
+                    final_st = Some((graph.subgraph(tree), cost, bottleneck));
+                    max = pivot;
+                }
+                None => {
+                    debug!("Infeasible pivot");
+                    min = pivot + 1;
+                }
+            }
+        }
+        final_st.ok_or_else(|| Util::infeasibility_reason(graph))
+    }
+
+    /// Kruskal that takes required edges first, then edges without upgrade and only then upgrades. The number of
+    /// upgrades of the result is the minimum over all spanning trees holding the required edges.
+    fn fewest_upgrades_tree(&self, graph: &MutableGraph) -> Option<Vec<EdgeId>> {
+        let mut order = graph.edges_copy();
+        order.sort_by(|&a, &b| {
+            (!graph.is_required(a), self.counts(graph, a)).cmp(&(!graph.is_required(b), self.counts(graph, b)))
+                .then(graph.cost(a).total_cmp(&graph.cost(b)))
+        });
+        let tree = Util::kruskal(graph, order, |_| true)?;
+        match self.upgrades(graph, &tree) <= self.max_upgrades {
+            true => Some(tree),
+            false => None,
+        }
+    }
+
+    /// Min-cost spanning tree with at most `max_upgrades` upgrades if it fits the budget.
+    fn cheapest_tree(&self, graph: &MutableGraph, budget: f64) -> Option<Vec<EdgeId>> {
+        let mut order = graph.edges_copy();
+        order.sort_by(|&a, &b| graph.cost(a).total_cmp(&graph.cost(b)).then(self.counts(graph, a).cmp(&self.counts(graph, b))));
+        let mut tree = Util::kruskal(graph, order, |_| true)?;
+        let mut cost: f64 = tree.iter().map(|&edge| graph.cost(edge)).sum();
+        let mut upgrades = self.upgrades(graph, &tree);
+        while upgrades > self.max_upgrades && cost <= budget {
+            let (delta, out, into) = self.best_swap(graph, &tree)?;
+            trace!("Swapping upgrade out [delta: {}, upgrades: {}]", delta, upgrades);
+            tree.retain(|&edge| edge != out);
+            tree.push(into);
+            cost += delta;
+            upgrades -= 1;
+        }
+        match cost <= budget {
+            true => Some(tree),
+            false => None,
+        }
+    }
+
+    /// Cheapest exchange of a tree upgrade that is not required for an edge without upgrade closing a cycle with it,
+    /// as (cost increase, edge out, edge in).
+    fn best_swap(&self, graph: &MutableGraph, tree: &[EdgeId]) -> Option<(f64, EdgeId, EdgeId)> {
+        let mut best: Option<(f64, EdgeId, EdgeId)> = None;
+        for &into in graph.edges() {
+            if self.counts(graph, into) || tree.contains(&into) {
+                continue;
+            }
+            let (u, v) = graph.endpoints(into);
+            for out in Util::tree_path(graph, tree, u, v).into_iter().map(|position| tree[position]) {
+                if !self.counts(graph, out) || graph.is_required(out) {
+                    continue;
+                }
+                let delta = graph.cost(into) - graph.cost(out);
+                if best.map_or(true, |(best_delta, _, _)| delta < best_delta) {
+                    best = Some((delta, out, into));
+                }
+            }
+        }
+        best
+    }
+
+    /// Whether the edge is an upgrade that counts towards the limit.
+    fn counts(&self, graph: &MutableGraph, edge: EdgeId) -> bool {
+        graph.is_upgraded(edge) && (self.count_builds || !graph.is_candidate(edge))
+    }
+
+    fn upgrades(&self, graph: &MutableGraph, tree: &[EdgeId]) -> usize {
+        tree.iter().filter(|&&edge| self.counts(graph, edge)).count()
+    }
+
+    fn cost_and_bottleneck(graph: &MutableGraph, tree: &[EdgeId]) -> (f64, f64) {
+        let cost = tree.iter().map(|&edge| graph.cost(edge)).sum();
+        let bottleneck = tree.iter().map(|&edge| graph.weight(edge)).fold(f64::NEG_INFINITY, f64::max);
+        (cost, bottleneck)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithms::constrained_bottleneck_spanning_tree::cardinality::CardinalityLimit;
+    use crate::algorithms::constrained_bottleneck_spanning_tree::solver::SolutionStatus;
+    use crate::algorithms::constrained_bottleneck_spanning_tree::test_graphs::square;
+
+    #[test]
+    fn test_upgrade_limit() {
+        let graph = square();
+        // (max upgrades, budget, bottleneck, cost)
+        for (max_upgrades, budget, bottleneck, cost) in [
+            (0, f64::INFINITY, -3.0, 0.0),
+            (1, f64::INFINITY, -5.0, 2.0),
+            (2, f64::INFINITY, -6.0, 5.0),
+            (3, f64::INFINITY, -10.0, 15.0),
+            (3, 5.0, -6.0, 5.0),
+            (1, 5.0, -5.0, 2.0),
+            // the budget pays for bottleneck -8, but only with three upgrades
+            (2, 9.0, -6.0, 5.0),
+            (1, 100.0, -5.0, 2.0),
+        ] {
+            let solution = CardinalityLimit::new(max_upgrades).solve(&graph, budget).unwrap();
+            assert_eq!(solution.bottleneck(), bottleneck, "{} upgrades within {}", max_upgrades, budget);
+            assert!(solution.upgraded_edges().len() <= max_upgrades);
+            assert!(solution.cost() <= budget);
+            assert!(solution.tree().unwrap().is_spanning_tree());
+            if budget.is_finite() {
+                assert_eq!(solution.cost(), cost);
+            }
+        }
+        let solution = CardinalityLimit::new(0).solve(&graph, -1.0).unwrap();
+        assert_eq!(solution.status(), SolutionStatus::Infeasible);
+    }
+}
@@ -0,0 +1,284 @@
+use std::collections::{BTreeMap, HashSet};
+use log::{debug, trace};
+use crate::algorithms::constrained_bottleneck_spanning_tree::solver::Solution;
+use crate::algorithms::util::Util;
+use crate::datastructures::graph::edge_store::EdgeId;
+use crate::datastructures::graph::mutable_graph::MutableGraph;
+use crate::datastructures::uf::union_find::UF;
+use crate::error::CbstuError;
+
+/// Preprocessed graph with the link constraints folded in: forbidden copies are left out and the cost of every required
+/// copy is lowered by `shift`, so a min-cost spanning tree takes all required links before any other. Costs and budgets
+/// of the reduced graph are offset by `shift` times the number of required links.
+#[derive(Debug, Clone)]
+pub struct ConstrainedGraph {
+    graph: MutableGraph,
+    original: Vec<EdgeId>,
+    shift: f64,
+    required_links: usize,
+}
+
+impl ConstrainedGraph {
+    pub fn graph(&self) -> &MutableGraph {
+        &self.graph
+    }
+
+    /// Budget of the reduced graph for a budget of the original one.
+    pub fn budget(&self, budget: f64) -> f64 {
+        budget - self.offset()
+    }
+
+    /// Difference between a cost of the original graph and the cost of the same tree in the reduced graph.
+    pub fn offset(&self) -> f64 {
+        self.shift * self.required_links as f64
+    }
+
+    /// Whether a spanning tree of the reduced graph holds every required link.
+    pub fn satisfies(&self, tree: &MutableGraph) -> bool {
+        tree.edges().iter().filter(|&&edge| tree.is_required(edge)).count() == self.required_links
+    }
+
+    /// Maps a tree of the reduced graph back onto the edges of `graph`, the graph that was reduced, and returns it with
+    /// its actual cost.
+    pub fn restore(&self, graph: &MutableGraph, tree: &MutableGraph) -> (MutableGraph, f64) {
+        let restored = graph.subgraph(tree.edges().iter().map(|&edge| self.original[edge as usize]).collect());
+        let cost = restored.calculate_total_cost();
+        (restored, cost)
+    }
+}
+
+pub struct Constraints();
+
+impl Constraints {
+    /// Whether any edge of the graph is required or forbidden.
+    pub fn has_constraints(graph: &MutableGraph) -> bool {
+        graph.edges().iter().any(|&edge| graph.is_required(edge) || graph.is_forbidden(edge))
+    }
+
+    /// Runs a CBSTU algorithm on the preprocessed graph under its link constraints. Without constraints the algorithm
+    /// gets the graph as is, otherwise it solves the reduced graph and the tree is mapped back. An exceeded budget gives
+    /// an infeasible solution, a budget that cannot even pay for the required links a conflict.
+    pub fn solve(graph: &MutableGraph, budget: f64, run: impl FnOnce(&MutableGraph, f64) -> Result<(MutableGraph, f64, f64), CbstuError>) -> Result<Solution, CbstuError> {
+        if !Self::has_constraints(graph) {
+            return Solution::from_result(run(graph, budget));
+        }
+        trace!("Solving under link constraints");
+        let reduced = Self::reduce(graph, Some(budget))?;
+        Self::check_budget(graph, budget)?;
+        match run(reduced.graph(), reduced.budget(budget)) {
+            Ok((tree, _, bottleneck)) => {
+                debug_assert!(reduced.satisfies(&tree));
+                let (tree, cost) = reduced.restore(graph, &tree);
+                Ok(Solution::feasible(tree, cost, bottleneck))
+            }
+            Err(CbstuError::InfeasibleBudget) => Ok(Solution::infeasible()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Folds the link constraints of the preprocessed graph into its costs, see [`ConstrainedGraph`]. The shift exceeds
+    /// a finite `budget` and the absolute costs combined, so leaving out a required link never pays off. Upgraded copies of links
+    /// whose base copy is forbidden get an original weight of 0, the edge elimination then never drops them in favour
+    /// of the base copy.
+    pub fn reduce(graph: &MutableGraph, budget: Option<f64>) -> Result<ConstrainedGraph, CbstuError> {
+        let allowed = Self::allowed_edges(graph)?;
+        let shift = budget.filter(|budget| budget.is_finite()).unwrap_or(0.0).max(0.0) + allowed.iter().map(|&edge| graph.cost(edge).abs()).sum::<f64>() + 1.0;
+        let with_base: HashSet<(usize, usize)> = allowed.iter()
+            .filter(|&&edge| !graph.is_upgraded(edge))
+            .map(|&edge| Self::link(graph, edge))
+            .collect();
+        let edges = allowed.iter().map(|&edge| {
+            let mut reduced = graph.edge(edge);
+            if graph.is_required(edge) {
+                reduced = reduced.cost(graph.cost(edge) - shift);
+            }
+            if graph.is_upgraded(edge) && !with_base.contains(&Self::link(graph, edge)) {
+                reduced = reduced.or_weight(0.0);
+            }
+            reduced
+        }).collect();
+        let required_links = Self::required_links(graph).len();
+        debug!("Reduced graph [edges: {}, required links: {}, shift: {}]", allowed.len(), required_links, shift);
+        Ok(ConstrainedGraph { graph: MutableGraph::new(graph.nodes_copy(), edges), original: allowed, shift, required_links })
+    }
+
+    /// Edges that are not forbidden, after checking that the constraints leave a spanning tree: every required link
+    /// keeps a usable edge, the required links hold no cycle and the usable edges span the graph. Works on the
+    /// preprocessed graph as well as on the links themselves.
+    pub fn allowed_edges(graph: &MutableGraph) -> Result<Vec<EdgeId>, CbstuError> {
+        let allowed: Vec<EdgeId> = graph.edges().iter().copied().filter(|&edge| !graph.is_forbidden(edge)).collect();
+        let usable: HashSet<(usize, usize)> = allowed.iter().map(|&edge| Self::link(graph, edge)).collect();
+        let required = Self::required_links(graph);
+        let blocked: Vec<(usize, usize)> = required.iter().copied().filter(|link| !usable.contains(link)).collect();
+        if !blocked.is_empty() {
+            return Err(CbstuError::ConstraintConflict {
+                reason: String::from("required link cannot be used as it or its upgrade is forbidden"),
+                links: blocked,
+            });
+        }
+        Self::check_cycles(graph)?;
+        let mut uf = UF::new(graph.nodes().len() as i32);
+        allowed.iter().for_each(|&edge| {
+            let (u, v) = graph.endpoints(edge);
+            uf.union(u, v);
+        });
+        if uf.count() > 1 {
+            let mut cut: Vec<(usize, usize)> = graph.edges().iter()
+                .filter(|&&edge| graph.is_forbidden(edge))
+                .filter(|&&edge| {
+                    let (u, v) = graph.endpoints(edge);
+                    !uf.connected(u, v)
+                })
+                .map(|&edge| Self::link(graph, edge))
+                .collect();
+            cut.sort();
+            cut.dedup();
+            return match cut.is_empty() {
+                true => Err(CbstuError::DisconnectedGraph),
+                false => Err(CbstuError::ConstraintConflict { reason: String::from("forbidden links disconnect the graph"), links: cut }),
+            };
+        }
+        Ok(allowed)
+    }
+
+    /// Checks that the budget covers the cheapest usable edge of every required link.
+    pub fn check_budget(graph: &MutableGraph, budget: f64) -> Result<(), CbstuError> {
+        let mut cheapest: BTreeMap<(usize, usize), f64> = BTreeMap::new();
+        for &edge in graph.edges() {
+            if graph.is_required(edge) && !graph.is_forbidden(edge) {
+                let cost = cheapest.entry(Self::link(graph, edge)).or_insert(f64::INFINITY);
+                *cost = cost.min(graph.cost(edge));
+            }
+        }
+        let total: f64 = cheapest.values().sum();
+        if total > budget {
+            return Err(CbstuError::ConstraintConflict {
+                reason: format!("required links cost at least {} which exceeds the budget", total),
+                links: cheapest.into_iter().filter(|&(_, cost)| cost > 0.0).map(|(link, _)| link).collect(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Distinct links with a required edge, as (smallest, largest) endpoint.
+    pub fn required_links(graph: &MutableGraph) -> Vec<(usize, usize)> {
+        Self::required_edges(graph).into_iter().map(|edge| Self::link(graph, edge)).collect()
+    }
+
+    /// One required edge per required link, in the order of the links.
+    fn required_edges(graph: &MutableGraph) -> Vec<EdgeId> {
+        let mut required: Vec<EdgeId> = graph.edges().iter().copied().filter(|&edge| graph.is_required(edge)).collect();
+        required.sort_by_key(|&edge| Self::link(graph, edge));
+        required.dedup_by_key(|edge| Self::link(graph, *edge));
+        required
+    }
+
+    /// Reports the first cycle closed by the required links.
+    fn check_cycles(graph: &MutableGraph) -> Result<(), CbstuError> {
+        let mut uf = UF::new(graph.nodes().len() as i32);
+        let mut forest = Vec::new();
+        for edge in Self::required_edges(graph) {
+            let (u, v) = graph.endpoints(edge);
+            if uf.connected(u, v) {
+                let mut cycle: Vec<(usize, usize)> = Util::tree_path(graph, &forest, u, v).into_iter().map(|position| Self::link(graph, forest[position])).collect();
+                cycle.push(Self::link(graph, edge));
+                return Err(CbstuError::ConstraintConflict { reason: String::from("required links form a cycle"), links: cycle });
+            }
+            uf.union(u, v);
+            forest.push(edge);
+        }
+        Ok(())
+    }
+
+    fn link(graph: &MutableGraph, edge: EdgeId) -> (usize, usize) {
+        let (u, v) = graph.endpoints(edge);
+        (u.min(v), u.max(v))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithms::constrained_bottleneck_spanning_tree::constraints::Constraints;
+    use crate::algorithms::constrained_bottleneck_spanning_tree::min_budget::min_budget_for_bottleneck;
+    use crate::algorithms::constrained_bottleneck_spanning_tree::pareto::ParetoFrontier;
+    use crate::algorithms::constrained_bottleneck_spanning_tree::solver::{solver_by_name, SOLVER_NAMES};
+    use crate::algorithms::constrained_bottleneck_spanning_tree::verify::Verifier;
+    use crate::algorithms::constrained_bottleneck_spanning_tree::test_graphs::{build, nodes, SQUARE};
+    use crate::datastructures::graph::edge::Edge;
+    use crate::datastructures::graph::mutable_graph::MutableGraph;
+    use crate::error::CbstuError;
+
+    fn square_graph(flag: impl Fn(usize, Edge) -> Edge) -> MutableGraph {
+        build(nodes(4), &SQUARE, flag)
+    }
+
+    fn links(graph: &MutableGraph) -> Vec<((usize, usize), bool)> {
+        let mut links: Vec<((usize, usize), bool)> = graph.edges().iter().map(|&edge| (graph.endpoints(edge), graph.is_upgraded(edge))).collect();
+        links.sort();
+        links
+    }
+
+    #[test]
+    fn test_constrained_solvers() {
+        // unconstrained, budget 5 upgrades (1, 2) and (3, 0) for bottleneck -6
+        let cases: Vec<(Box<dyn Fn(usize, Edge) -> Edge>, f64, f64)> = vec![
+            // the weak link (0, 2) has to stay
+            (Box::new(|i, edge| edge.required(i == 4)), 5.0, -1.0),
+            // upgrading (0, 2) to 20 is required and costs 6, leaving 3 to upgrade (1, 2)
+            (Box::new(|i, edge| edge.upgrade_required(i == 4)), 9.0, -6.0),
+            // without (2, 3) node 3 hangs on (3, 0)
+            (Box::new(|i, edge| edge.forbidden(i == 2)), 5.0, -5.0),
+            // (3, 0) stays at 3
+            (Box::new(|i, edge| edge.upgrade_forbidden(i == 3)), 5.0, -5.0),
+        ];
+        for (flag, budget, bottleneck) in cases {
+            let graph = square_graph(flag);
+            for name in SOLVER_NAMES {
+                let solution = solver_by_name(name).unwrap().solve(&graph, budget).unwrap();
+                assert_eq!(solution.bottleneck(), bottleneck, "{} with budget {}", name, budget);
+                assert!(solution.cost() <= budget);
+                assert!(solution.tree().unwrap().is_spanning_tree());
+                assert!(Verifier::verify(&graph, budget, &solution).unwrap().is_ok(), "{}", name);
+            }
+        }
+        let graph = square_graph(|i, edge| edge.upgrade_required(i == 4));
+        let solution = solver_by_name("berman").unwrap().solve(&graph, 9.0).unwrap();
+        assert!(links(solution.tree().unwrap()).contains(&((0, 2), true)));
+        assert_eq!(solution.cost(), 6.0 + 3.0);
+    }
+
+    #[test]
+    fn test_constrained_budget_queries() {
+        let graph = square_graph(|i, edge| edge.required(i == 1));
+        let frontier = ParetoFrontier::run(&graph).unwrap();
+        let points: Vec<(f64, f64)> = frontier.iter().map(|point| (point.bottleneck(), point.cost())).collect();
+        assert_eq!(points, vec![(-8.0, 9.0), (-6.0, 5.0), (-5.0, 3.0), (-2.0, 0.0)]);
+        for point in &frontier {
+            assert!(links(point.tree()).iter().any(|&(link, _)| link == (1, 2)));
+            let solution = min_budget_for_bottleneck(&graph, point.bottleneck()).unwrap();
+            assert_eq!((solution.cost(), solution.bottleneck()), (point.cost(), point.bottleneck()));
+        }
+        assert!(matches!(min_budget_for_bottleneck(&graph, -9.0), Err(CbstuError::UnreachableBottleneck)));
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let conflict = |graph: &MutableGraph, budget: f64| match solver_by_name("berman").unwrap().solve(graph, budget) {
+            Err(CbstuError::ConstraintConflict { mut links, .. }) => {
+                links.sort();
+                links
+            }
+            _ => panic!("expected a constraint conflict"),
+        };
+        let cycle = square_graph(|i, edge| edge.required(i != 2 && i != 3));
+        assert_eq!(conflict(&cycle, 100.0), vec![(0, 1), (0, 2), (1, 2)]);
+        let blocked = square_graph(|i, edge| edge.upgrade_required(i == 0).upgrade_forbidden(i == 0));
+        assert_eq!(conflict(&blocked, 100.0), vec![(0, 1)]);
+        let cut = square_graph(|i, edge| edge.forbidden(i == 2 || i == 3));
+        assert_eq!(conflict(&cut, 100.0), vec![(0, 3), (2, 3)]);
+        let expensive = square_graph(|i, edge| edge.upgrade_required(i == 0 || i == 3).required(i == 1));
+        assert_eq!(conflict(&expensive, 5.0), vec![(0, 1), (0, 3)]);
+        assert!(solver_by_name("berman").unwrap().solve(&expensive, 6.0).unwrap().is_feasible());
+        assert!(Constraints::allowed_edges(&square_graph(|_, edge| edge)).is_ok());
+    }
+}
@@ -0,0 +1,265 @@
+use log::trace;
+use crate::algorithms::constrained_bottleneck_spanning_tree::constraints::Constraints;
+use crate::algorithms::util::{BoundedSolution, ThresholdCheck, Util};
+use crate::datastructures::graph::edge_store::EdgeId;
+use crate::datastructures::graph::mutable_graph::MutableGraph;
+use crate::error::CbstuError;
+
+/// Above this number of usable edges a threshold the Lagrangian relaxation cannot decide is left undecided.
+const EXACT_LIMIT: usize = 30;
+/// Subgradient steps per threshold.
+const ITERATIONS: usize = 60;
+
+/// CBSTU in which no node carries more tree links than its maximum degree.
+pub struct DegreeConstrained();
+
+impl DegreeConstrained {
+    /// Bisection over the thresholds of the preprocessed graph under its link constraints. A threshold is decided by
+    /// Lagrangian relaxation of the degree bounds: the min-cost spanning tree on costs raised by a penalty per tree
+    /// link at each endpoint bounds the cost of every tree within the degrees, and the same costs guide a Kruskal that
+    /// skips edges at full nodes to the heuristic solutions. What it leaves open is settled by branch and bound when the
+    /// threshold has at most `EXACT_LIMIT` usable edges and left undecided otherwise, then the solution comes with a
+    /// bound.
+    pub fn solve(graph: &MutableGraph, budget: f64) -> Result<BoundedSolution, CbstuError> {
+        trace!("Solving degree-constrained bottleneck spanning tree problem");
+        let unique_weights = Util::sorted_unique_weights(graph)?;
+        let allowed = graph.subgraph(Constraints::allowed_edges(graph)?);
+        let graph = &allowed;
+        let bounds: Vec<usize> = graph.nodes().iter().map(|node| node.get_max_degree().unwrap_or(usize::MAX)).collect();
+        Self::check_degrees(graph, &bounds)?;
+        let required = Util::required_links(graph);
+        let (found, bound) = Util::bisect_thresholds(&unique_weights, |threshold| {
+            Self::check(&graph.smaller_or_eq_than(threshold), &bounds, budget, required)
+        });
+        let tree = match found {
+            Some(tree) => graph.subgraph(tree),
+            None => return Err(match Util::infeasibility_reason(graph) {
+                CbstuError::InfeasibleBudget => match Self::check(graph, &bounds, f64::INFINITY, required) {
+                    ThresholdCheck::Infeasible => CbstuError::InfeasibleDegrees,
+                    _ => CbstuError::InfeasibleBudget,
+                },
+                err => err,
+            }),
+        };
+        let cost = tree.calculate_total_cost();
+        Ok(BoundedSolution::new(tree, vec![cost], bound))
+    }
+
+    /// Quick proof that the degree bounds alone rule out every spanning tree: each node needs a tree link, and the
+    /// degrees of a tree sum up to twice its number of links.
+    fn check_degrees(graph: &MutableGraph, bounds: &[usize]) -> Result<(), CbstuError> {
+        let n = graph.nodes().len();
+        let mut neighbours = vec![Vec::new(); n];
+        for &edge in graph.edges() {
+            let (u, v) = graph.endpoints(edge);
+            neighbours[u].push(v);
+            neighbours[v].push(u);
+        }
+        let reachable: Vec<usize> = neighbours.iter_mut().enumerate().map(|(v, list)| {
+            list.sort();
+            list.dedup();
+            list.len().min(bounds[v])
+        }).collect();
+        if n > 1 && (reachable.contains(&0) || reachable.iter().map(|&degree| degree.min(n)).sum::<usize>() < 2 * (n - 1)) {
+            return Err(CbstuError::InfeasibleDegrees);
+        }
+        Ok(())
+    }
+
+    /// Decides whether a spanning tree of `graph` within the degree bounds and holding every required link fits the
+    /// budget.
+    fn check(graph: &MutableGraph, bounds: &[usize], budget: f64, required: usize) -> ThresholdCheck<Vec<EdgeId>> {
+        let n = graph.nodes().len();
+        let scale = graph.edges().iter().map(|&edge| graph.cost(edge).abs()).fold(1.0, f64::max);
+        let fits = |tree: &[EdgeId]| tree.iter().map(|&edge| graph.cost(edge)).sum::<f64>() <= budget;
+        let mut penalties = vec![0.0; n];
+        for iteration in 0..ITERATIONS {
+            let penalized = |edge: EdgeId| {
+                let (u, v) = graph.endpoints(edge);
+                graph.cost(edge) + penalties[u] + penalties[v]
+            };
+            let mut order = graph.edges_copy();
+            order.sort_by(|&a, &b| graph.is_required(b).cmp(&graph.is_required(a)).then(penalized(a).total_cmp(&penalized(b))));
+            let tree = match Util::kruskal(graph, order.iter().copied(), |_| true) {
+                Some(tree) => tree,
+                None => return ThresholdCheck::Infeasible,
+            };
+            if Util::required_in(graph, tree.iter().copied()) < required {
+                trace!("Required link out of reach");
+                return ThresholdCheck::Infeasible;
+            }
+            let degrees = Self::degrees(graph, &tree);
+            if (0..n).all(|v| degrees[v] <= bounds[v]) && fits(&tree) {
+                return ThresholdCheck::Feasible(tree);
+            }
+            // the same order, skipping edges at nodes that reached their bound
+            let mut taken = vec![0; n];
+            let bounded = Util::kruskal(graph, order.iter().copied(), |edge| {
+                let (u, v) = graph.endpoints(edge);
+                let open = taken[u] < bounds[u] && taken[v] < bounds[v];
+                if open {
+                    taken[u] += 1;
+                    taken[v] += 1;
+                }
+                open
+            });
+            if let Some(tree) = bounded.filter(|tree| fits(tree) && Util::required_in(graph, tree.iter().copied()) == required) {
+                return ThresholdCheck::Feasible(tree);
+            }
+            let bound = tree.iter().map(|&edge| penalized(edge)).sum::<f64>()
+                - (0..n).filter(|&v| bounds[v] < n).map(|v| penalties[v] * bounds[v] as f64).sum::<f64>();
+            if bound > budget + 1e-9 {
+                trace!("Lagrangian bound {} proves the threshold infeasible", bound);
+                return ThresholdCheck::Infeasible;
+            }
+            let step = scale / (iteration + 1) as f64;
+            for v in (0..n).filter(|&v| bounds[v] < n) {
+                penalties[v] = (penalties[v] + step * (degrees[v] as f64 - bounds[v] as f64)).max(0.0);
+            }
+        }
+        if graph.edges().len() > EXACT_LIMIT {
+            return ThresholdCheck::Unknown;
+        }
+        let mut order = graph.edges_copy();
+        order.sort_by(|&a, &b| graph.is_required(b).cmp(&graph.is_required(a)).then(graph.cost(a).total_cmp(&graph.cost(b))));
+        // only edges between nodes below their bound may join the chosen ones
+        let open = |degrees: &[usize], edge: EdgeId| {
+            let (u, v) = graph.endpoints(edge);
+            degrees[u] < bounds[u] && degrees[v] < bounds[v]
+        };
+        let admits = |chosen: &[EdgeId], edge: EdgeId| open(&Self::degrees(graph, chosen), edge);
+        // the cheapest completion by the undecided edges that may still join the chosen ones has to fit
+        let promising = |chosen: &[EdgeId], undecided: &[EdgeId]| {
+            let degrees = Self::degrees(graph, chosen);
+            let spent: f64 = chosen.iter().map(|&edge| graph.cost(edge)).sum();
+            Util::completion(graph, chosen, undecided.iter().copied().filter(|&edge| open(&degrees, edge)), |edge| graph.cost(edge))
+                .map_or(false, |completion| spent + completion <= budget)
+        };
+        let mut found = None;
+        Util::branch_and_bound(graph, &order, required, admits, promising, |tree| {
+            let fitting = fits(tree);
+            if fitting {
+                found = Some(tree.to_vec());
+            }
+            fitting
+        });
+        match found {
+            Some(tree) => ThresholdCheck::Feasible(tree),
+            None => ThresholdCheck::Infeasible,
+        }
+    }
+
+    fn degrees(graph: &MutableGraph, tree: &[EdgeId]) -> Vec<usize> {
+        let mut degrees = vec![0; graph.nodes().len()];
+        for &edge in tree {
+            let (u, v) = graph.endpoints(edge);
+            degrees[u] += 1;
+            degrees[v] += 1;
+        }
+        degrees
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+    use crate::algorithms::constrained_bottleneck_spanning_tree::degree::DegreeConstrained;
+    use crate::algorithms::constrained_bottleneck_spanning_tree::solver::solver_by_name;
+    use crate::algorithms::constrained_bottleneck_spanning_tree::test_graphs::{brute_force, build, Link, SQUARE};
+    use crate::datastructures::graph::edge_store::EdgeId;
+    use crate::datastructures::graph::mutable_graph::MutableGraph;
+    use crate::datastructures::graph::node::Node;
+    use crate::error::CbstuError;
+
+    fn square_graph(max_degrees: &[Option<usize>]) -> MutableGraph {
+        let nodes = max_degrees.iter().enumerate()
+            .map(|(id, max_degree)| match max_degree {
+                Some(max_degree) => Node::default(id).max_degree(*max_degree),
+                None => Node::default(id),
+            })
+            .collect();
+        build(nodes, &SQUARE, |_, edge| edge)
+    }
+
+    /// Preprocessed graph of `links` whose nodes carry the maximum degrees `bounds`.
+    fn bounded_graph(bounds: &[usize], links: &[Link]) -> MutableGraph {
+        build(bounds.iter().enumerate().map(|(id, &bound)| Node::default(id).max_degree(bound)).collect(), links, |_, edge| edge)
+    }
+
+    #[test]
+    fn test_unbounded_matches_solver() {
+        let graph = square_graph(&[None; 4]);
+        for budget in [0.0, 3.0, 5.0, 9.0, 15.0, 100.0] {
+            let solution = DegreeConstrained::solve(&graph, budget).unwrap();
+            let expected = solver_by_name("berman").unwrap().solve(&graph, budget).unwrap();
+            assert_eq!(solution.bottleneck(), expected.bottleneck(), "budget {}", budget);
+            assert!(solution.is_optimal());
+        }
+    }
+
+    #[test]
+    fn test_degree_bounds() {
+        // node 0 may carry a single link, so the tree holds (1,2), (2,3) and one link at node 0
+        let graph = square_graph(&[Some(1), None, None, None]);
+        // (budget, bottleneck, cost)
+        for (budget, bottleneck, cost) in [(0.0, -2.0, 0.0), (3.0, -5.0, 3.0), (5.0, -6.0, 5.0), (9.0, -6.0, 5.0), (100.0, -8.0, 10.0)] {
+            let solution = DegreeConstrained::solve(&graph, budget).unwrap();
+            assert_eq!((solution.bottleneck(), solution.cost()), (bottleneck, cost), "budget {}", budget);
+            assert!(solution.is_optimal());
+            assert!(solution.tree().is_spanning_tree());
+            assert_eq!(solution.tree().edges().iter().filter(|&&edge| solution.tree().endpoints(edge).0 == 0 || solution.tree().endpoints(edge).1 == 0).count(), 1);
+        }
+        assert!(matches!(DegreeConstrained::solve(&graph, -1.0), Err(CbstuError::InfeasibleBudget)));
+        // with nodes 0 and 2 as leaves nothing joins nodes 1 and 3
+        assert!(matches!(DegreeConstrained::solve(&square_graph(&[Some(1), None, Some(1), None]), 5.0), Err(CbstuError::InfeasibleDegrees)));
+        assert!(matches!(DegreeConstrained::solve(&square_graph(&[Some(0), None, None, None]), 5.0), Err(CbstuError::InfeasibleDegrees)));
+    }
+
+    #[test]
+    fn test_random_degree_bounds() {
+        // random graphs on 5 nodes with random degree bounds, against the best tree within the bounds and the budget
+        let mut binding = 0;
+        for seed in 0..100 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut links: Vec<Link> = Vec::new();
+            for v in 1..5 {
+                links.push((rng.gen_range(0..v), v, rng.gen_range(1..10) as f64, rng.gen_range(10..20) as f64, rng.gen_range(0..10) as f64));
+            }
+            for _ in 0..4 {
+                let (u, v) = (rng.gen_range(0..5), rng.gen_range(0..5));
+                if u != v {
+                    links.push((u, v, rng.gen_range(1..10) as f64, rng.gen_range(10..20) as f64, rng.gen_range(0..10) as f64));
+                }
+            }
+            let bounds: Vec<usize> = (0..5).map(|_| rng.gen_range(1..4)).collect();
+            let graph = bounded_graph(&bounds, &links);
+            let budget = rng.gen_range(0..20) as f64;
+            let degrees = |tree: &[EdgeId]| {
+                let mut degrees = [0; 5];
+                tree.iter().map(|&edge| graph.endpoints(edge)).for_each(|(u, v)| {
+                    degrees[u] += 1;
+                    degrees[v] += 1;
+                });
+                degrees
+            };
+            let optimum = brute_force(&graph, 5, |tree| {
+                let degrees = degrees(tree);
+                tree.iter().map(|&edge| graph.cost(edge)).sum::<f64>() <= budget && (0..5).all(|v| degrees[v] <= bounds[v])
+            });
+            match DegreeConstrained::solve(&graph, budget) {
+                Ok(solution) => {
+                    assert!(solution.is_optimal(), "seed {}", seed);
+                    assert_eq!(solution.bottleneck(), optimum, "seed {}", seed);
+                    assert!(solution.cost() <= budget, "seed {}", seed);
+                    let degrees = degrees(solution.tree().edges());
+                    assert!((0..5).all(|v| degrees[v] <= bounds[v]), "seed {}", seed);
+                    binding += (solution.bottleneck() > brute_force(&graph, 5, |tree| tree.iter().map(|&edge| graph.cost(edge)).sum::<f64>() <= budget)) as usize;
+                }
+                Err(err) => assert!(matches!(err, CbstuError::InfeasibleBudget | CbstuError::InfeasibleDegrees) && optimum.is_infinite(), "seed {}", seed),
+            }
+        }
+        // the bounds cost bottleneck in some of the graphs
+        assert!(binding > 0);
+    }
+}
@@ -0,0 +1,164 @@
+use log::trace;
+use crate::algorithms::constrained_bottleneck_spanning_tree::cardinality::CardinalityLimit;
+use crate::algorithms::constrained_bottleneck_spanning_tree::solver::{CbstuSolver, Solution};
+use crate::datastructures::graph::mutable_graph::MutableGraph;
+use crate::error::CbstuError;
+
+/// What the volume discount is applied to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiscountBasis {
+    /// The sum of the individual upgrade and build costs.
+    Sum,
+    /// The number of upgrades and builds.
+    Count,
+}
+
+/// Concave total cost of the upgrades of a tree as a function of their combined `total`, the sum or the number of
+/// individual costs. The function is piecewise linear through `points`, starting at `(0, 0)` and continuing along its
+/// last piece beyond the last point.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscountCurve {
+    basis: DiscountBasis,
+    points: Vec<(f64, f64)>,
+}
+
+impl DiscountCurve {
+    /// Curve through the given `(total, cost)` points, linear in between. Totals must increase, costs must not
+    /// decrease and the slopes must not increase. Without points upgrades are free.
+    pub fn piecewise(basis: DiscountBasis, points: Vec<(f64, f64)>) -> Result<DiscountCurve, CbstuError> {
+        let mut curve = vec![(0.0, 0.0)];
+        let mut last_slope = f64::INFINITY;
+        for (total, cost) in points {
+            let &(last_total, last_cost) = curve.last().unwrap();
+            if !(total > last_total) || !(cost >= last_cost) {
+                return Err(CbstuError::MalformedInput(format!("discount point ({}, {}) does not follow ({}, {})", total, cost, last_total, last_cost)));
+            }
+            let slope = (cost - last_cost) / (total - last_total);
+            if slope > last_slope {
+                return Err(CbstuError::MalformedInput(format!("discount curve is not concave at ({}, {})", last_total, last_cost)));
+            }
+            last_slope = slope;
+            curve.push((total, cost));
+        }
+        Ok(DiscountCurve { basis, points: curve })
+    }
+
+    pub fn basis(&self) -> DiscountBasis {
+        self.basis
+    }
+
+    /// Points of the curve after `(0, 0)`.
+    pub fn points(&self) -> &[(f64, f64)] {
+        &self.points[1..]
+    }
+
+    /// Discounted cost of the given total.
+    pub fn cost(&self, total: f64) -> f64 {
+        let end = self.points.iter().position(|&(x, _)| x >= total).unwrap_or(self.points.len() - 1).max(1);
+        match self.points.len() {
+            1 => 0.0,
+            _ => {
+                let (x0, y0) = self.points[end - 1];
+                let (x1, y1) = self.points[end];
+                y0 + (y1 - y0) / (x1 - x0) * (total - x0)
+            }
+        }
+    }
+
+    /// Largest total whose discounted cost fits the budget, negative if not even no upgrades do. The cost is
+    /// non-decreasing, so a tree fits the budget exactly when its total does not exceed this one.
+    pub fn max_total(&self, budget: f64) -> f64 {
+        if budget < 0.0 {
+            return -1.0;
+        }
+        for window in self.points.windows(2) {
+            let ((x0, y0), (x1, y1)) = (window[0], window[1]);
+            if y1 > budget {
+                return x0 + (budget - y0) * (x1 - x0) / (y1 - y0);
+            }
+        }
+        match self.points.len() {
+            1 => f64::INFINITY,
+            n => {
+                let ((x0, y0), (x1, y1)) = (self.points[n - 2], self.points[n - 1]);
+                match y1 > y0 {
+                    true => x1 + (budget - y1) * (x1 - x0) / (y1 - y0),
+                    false => f64::INFINITY,
+                }
+            }
+        }
+    }
+
+    /// Solves the preprocessed graph with the discounted cost as the budget check. On the sum of the costs the
+    /// threshold search of `solver` runs unchanged against the largest total that fits the budget; on the number of
+    /// upgrades this is a cardinality limit, whose search is Berman's, so other solvers are rejected. The solution
+    /// reports the discounted cost.
+    pub fn solve(&self, solver: &dyn CbstuSolver, graph: &MutableGraph, budget: f64) -> Result<Solution, CbstuError> {
+        if self.basis == DiscountBasis::Count && solver.name() != "berman" {
+            return Err(CbstuError::MalformedInput(format!("discounts on the number of upgrades are only solved with berman, not {}", solver.name())));
+        }
+        let max_total = self.max_total(budget);
+        trace!("Solving with volume discounts [basis: {:?}, largest total: {}]", self.basis, max_total);
+        let solution = match self.basis {
+            DiscountBasis::Sum => solver.solve(graph, max_total)?,
+            DiscountBasis::Count if max_total < 0.0 => return Ok(Solution::infeasible()),
+            DiscountBasis::Count => {
+                let max_upgrades = max_total.min(graph.edges().len() as f64).floor() as usize;
+                CardinalityLimit::new(max_upgrades).count_builds(true).solve(graph, f64::INFINITY)?
+            }
+        };
+        let bottleneck = solution.bottleneck();
+        let total = match self.basis {
+            DiscountBasis::Sum => solution.cost(),
+            DiscountBasis::Count => (solution.upgraded_edges().len() + solution.built_edges().len()) as f64,
+        };
+        Ok(match solution.into_tree() {
+            Some(tree) => Solution::feasible(tree, self.cost(total), bottleneck),
+            None => Solution::infeasible(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithms::constrained_bottleneck_spanning_tree::discount::{DiscountBasis, DiscountCurve};
+    use crate::algorithms::constrained_bottleneck_spanning_tree::solver::{solver_by_name, SolutionStatus, SOLVER_NAMES};
+    use crate::algorithms::constrained_bottleneck_spanning_tree::test_graphs::square;
+    use crate::error::CbstuError;
+
+    #[test]
+    fn test_discount_curve() {
+        let curve = DiscountCurve::piecewise(DiscountBasis::Sum, vec![(4.0, 4.0), (10.0, 7.0)]).unwrap();
+        assert_eq!((curve.cost(0.0), curve.cost(2.0), curve.cost(6.0), curve.cost(14.0)), (0.0, 2.0, 5.0, 9.0));
+        assert_eq!((curve.max_total(-1.0), curve.max_total(3.0), curve.max_total(5.0), curve.max_total(9.0)), (-1.0, 3.0, 6.0, 14.0));
+        let flat = DiscountCurve::piecewise(DiscountBasis::Count, vec![(2.0, 6.0), (3.0, 6.0)]).unwrap();
+        assert_eq!((flat.max_total(5.0), flat.max_total(6.0)), (5.0 / 3.0, f64::INFINITY));
+        assert!(matches!(DiscountCurve::piecewise(DiscountBasis::Sum, vec![(4.0, 4.0), (10.0, 20.0)]), Err(CbstuError::MalformedInput(_))));
+        assert!(matches!(DiscountCurve::piecewise(DiscountBasis::Sum, vec![(4.0, 4.0), (2.0, 5.0)]), Err(CbstuError::MalformedInput(_))));
+    }
+
+    #[test]
+    fn test_discounted_solvers() {
+        let graph = square();
+        // full price up to 4, half price beyond
+        let sum = DiscountCurve::piecewise(DiscountBasis::Sum, vec![(4.0, 4.0), (10.0, 7.0)]).unwrap();
+        // (budget, bottleneck, discounted cost): -8 costs 9 and -10 costs 15 at full price
+        for (budget, bottleneck, cost) in [(1.0, -3.0, 0.0), (5.0, -6.0, 4.5), (6.5, -8.0, 6.5), (9.5, -10.0, 9.5), (-1.0, 0.0, 0.0)] {
+            for name in SOLVER_NAMES {
+                let solution = sum.solve(solver_by_name(name).unwrap().as_ref(), &graph, budget).unwrap();
+                assert_eq!(solution.status() == SolutionStatus::Feasible, budget >= 0.0, "{} with {}", name, budget);
+                assert_eq!((solution.bottleneck(), solution.cost()), (bottleneck, cost), "{} with {}", name, budget);
+            }
+        }
+        // first upgrade costs 3, each further one 1
+        let count = DiscountCurve::piecewise(DiscountBasis::Count, vec![(1.0, 3.0), (2.0, 4.0)]).unwrap();
+        let berman = solver_by_name("berman").unwrap();
+        for (budget, bottleneck, cost) in [(2.0, -3.0, 0.0), (3.0, -5.0, 3.0), (4.0, -6.0, 4.0), (5.0, -10.0, 5.0)] {
+            let solution = count.solve(berman.as_ref(), &graph, budget).unwrap();
+            assert_eq!((solution.bottleneck(), solution.cost()), (bottleneck, cost), "count with {}", budget);
+        }
+        for name in SOLVER_NAMES.iter().filter(|&&name| name != "berman") {
+            assert!(matches!(count.solve(solver_by_name(name).unwrap().as_ref(), &graph, 5.0), Err(CbstuError::MalformedInput(_))), "count with {}", name);
+        }
+    }
+}
@@ -1,34 +1,34 @@
-use std::cell::RefCell;
-use std::collections::HashSet;
-use std::rc::Rc;
 use log::{trace, warn};
+use crate::algorithms::constrained_bottleneck_spanning_tree::constraints::Constraints;
+use crate::algorithms::constrained_bottleneck_spanning_tree::solver::{CbstuSolver, Solution};
 use crate::algorithms::min_sum_spanning_tree::kruskal::CalculationType;
 use crate::algorithms::util;
 use crate::algorithms::util::{PivotResult, Util};
-use crate::datastructures::garbage::Garbage;
-use crate::datastructures::graph::edge::Edge;
 use crate::datastructures::graph::mutable_graph::MutableGraph;
+use crate::error::CbstuError;
 use crate::print_edges;
 
 pub struct EdgeEliminationOld();
 
 impl EdgeEliminationOld {
-    pub fn run(graph: &mut MutableGraph, budget: f64) -> (Option<MutableGraph>, f64, f64, Garbage) {
+    pub fn run(graph: &MutableGraph, budget: f64) -> Result<(MutableGraph, f64, f64), CbstuError> {
         trace!("Solving Constrained bottleneck spanning tree problem with Edge Elimination algorithm");
-        let (op_bst, _, bottleneck_mbst) = graph.mst(CalculationType::Weight);
-        let total_cost = graph.calculate_total_cost();
+        // edges get eliminated during the search, so work on a copy of the edge list
+        let mut working_graph = graph.clone();
+        let (op_bst, _, bottleneck_mbst) = graph.mst(CalculationType::Weight)?;
+        let total_cost = op_bst.calculate_total_cost();
         if total_cost <= budget {
             trace!("MBST is valid solution [bottleneck: {}, cost: {}]", bottleneck_mbst, total_cost);
-            return (op_bst, total_cost, bottleneck_mbst, Garbage::default());
+            return Ok((op_bst, total_cost, bottleneck_mbst));
         }
         trace!("MBST is not valid solution [bottleneck: {}, cost: {}]", bottleneck_mbst, total_cost);
-        Self::eliminate_upgraded_edges_above_bottleneck(graph, bottleneck_mbst);
-        let mut relevant_edges = Util::unique_weight_list_above_or_eq(graph.edges(), bottleneck_mbst);
+        Self::eliminate_upgraded_edges_above_bottleneck(&mut working_graph, bottleneck_mbst);
+        let mut relevant_edges = Util::unique_weight_list_above_or_eq(&working_graph, bottleneck_mbst);
         relevant_edges.sort_by(|a, b| a.partial_cmp(b).unwrap());
-        Self::dual_bound_search(graph, relevant_edges, budget)
+        Self::dual_bound_search(&mut working_graph, relevant_edges, budget)
     }
 
-    fn dual_bound_search(graph: &mut MutableGraph, relevant_edges: Vec<f64>, budget: f64) -> (Option<MutableGraph>, f64, f64, Garbage) {
+    fn dual_bound_search(graph: &mut MutableGraph, relevant_edges: Vec<f64>, budget: f64) -> Result<(MutableGraph, f64, f64), CbstuError> {
         trace!("Dual bound search");
         let mut max = relevant_edges.len();
         let mut min = 0_usize;
@@ -47,20 +47,20 @@ impl EdgeEliminationOld {
             pivot_b = max - 1;
             pivot_a_weight = relevant_edges[pivot_a];
             pivot_b_weight = relevant_edges[pivot_b];
-            checked_a = Util::check_pivot(graph, pivot_a_weight, budget);
+            checked_a = Util::check_pivot(graph, pivot_a_weight, budget)?;
             match checked_a {
                 PivotResult::Feasible(st) => {
                     trace!("Found feasible solution pivot_a [bottleneck {}, cost {}]", st.2, st.1);
-                    graph.edges_mut().retain(|e| e.borrow().get_weight() <= st.2);
+                    *graph = graph.smaller_or_eq_than(st.2);
                     final_st = Some(st);
                     max = pivot_a;
                 }
                 PivotResult::Infeasible => {
-                    checked_b = Util::check_pivot(graph, pivot_b_weight, budget);
+                    checked_b = Util::check_pivot(graph, pivot_b_weight, budget)?;
                     match checked_b {
                         PivotResult::Feasible(st) => {
                             trace!("Found feasible solution pivot_b [bottleneck {}, cost {}]", st.2, st.1);
-                            graph.edges_mut().retain(|e| e.borrow().get_weight() <= st.2);
+                            *graph = graph.smaller_or_eq_than(st.2);
                             final_st = Some(st);
                             min = pivot_a + 1;
                             max = pivot_b;
@@ -76,23 +76,34 @@ impl EdgeEliminationOld {
         match final_st {
             Some(st) => {
                 trace!("Dual bound search finished [bottleneck {}, cost {}, iterations {}]", st.2, st.1, iterations);
-                (Some(st.0), st.1, st.2, Garbage::default())
+                Ok(st)
             }
             None => {
                 warn!("No feasible solution found");
-                (None, 0.0, 0.0, Garbage::default())
+                Err(Util::infeasibility_reason(graph))
             }
         }
     }
 
     fn eliminate_upgraded_edges_above_bottleneck(graph: &mut MutableGraph, bottleneck: f64) {
-        graph.edges_mut().retain(|e| {
-            if e.borrow().is_upgraded() {
-                e.borrow().get_or_weight() >= bottleneck
+        let remaining = graph.filter(|edge| {
+            if graph.is_upgraded(edge) {
+                graph.or_weight(edge) >= bottleneck
             } else {
                 true
             }
         });
+        *graph = remaining;
     }
 
-}
\ No newline at end of file
+}
+
+impl CbstuSolver for EdgeEliminationOld {
+    fn name(&self) -> &'static str {
+        "edge_elimination_old"
+    }
+
+    fn solve(&self, graph: &MutableGraph, budget: f64) -> Result<Solution, CbstuError> {
+        Constraints::solve(graph, budget, Self::run)
+    }
+}
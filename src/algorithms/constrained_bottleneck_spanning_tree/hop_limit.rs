@@ -0,0 +1,274 @@
+use log::trace;
+use crate::algorithms::constrained_bottleneck_spanning_tree::constraints::Constraints;
+use crate::algorithms::min_sum_spanning_tree::kruskal::CalculationType;
+use crate::algorithms::util::{BoundedSolution, ThresholdCheck, Util};
+use crate::datastructures::graph::edge_store::EdgeId;
+use crate::datastructures::graph::mutable_graph::MutableGraph;
+use crate::error::CbstuError;
+
+/// Above this number of usable edges a threshold the heuristic cannot fit in the budget is left undecided.
+const EXACT_LIMIT: usize = 30;
+
+/// Hop limit on the tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HopBound {
+    /// Every node within `hops` links of `root`.
+    Depth { root: usize, hops: usize },
+    /// No path of the tree longer than the given number of links.
+    Diameter(usize),
+}
+
+impl HopBound {
+    /// Depth below the root or diameter of a spanning tree, whichever the bound limits.
+    pub fn measure(&self, tree: &MutableGraph) -> Option<usize> {
+        match *self {
+            HopBound::Depth { root, .. } => tree.depth(root),
+            HopBound::Diameter(_) => tree.diameter(),
+        }
+    }
+
+    pub fn limit(&self) -> usize {
+        match *self {
+            HopBound::Depth { hops, .. } => hops,
+            HopBound::Diameter(hops) => hops,
+        }
+    }
+}
+
+/// Center a tree within the bound grows from: its nodes, joined by `edge` if there are two, and the number of links
+/// every other node may be away from the nearest of them.
+struct Center {
+    nodes: Vec<usize>,
+    edge: Option<EdgeId>,
+    radius: usize,
+}
+
+/// CBSTU with a limit on the depth of the tree below a root or on its diameter.
+pub struct HopLimited {
+    bound: HopBound,
+}
+
+impl HopLimited {
+    pub fn new(bound: HopBound) -> HopLimited {
+        HopLimited { bound }
+    }
+
+    /// Bisection over the thresholds of the preprocessed graph under its link constraints. A tree within the bound
+    /// hangs below a center: the root, or for the diameter a node (even limit) or an edge (odd limit) from which no
+    /// node is more than half the diameter away. A threshold without such a center, or whose min-cost spanning tree
+    /// already exceeds the budget, is infeasible. Otherwise a Prim that only attaches nodes within the radius of a
+    /// center gives a tree per center. When none fits the budget, branch and bound decides thresholds with at most
+    /// `EXACT_LIMIT` usable edges and the others stay undecided, then the solution comes with a bound.
+    pub fn solve(&self, graph: &MutableGraph, budget: f64) -> Result<BoundedSolution, CbstuError> {
+        trace!("Solving hop-limited bottleneck spanning tree problem [{:?}]", self.bound);
+        if let HopBound::Depth { root, .. } = self.bound {
+            if root >= graph.nodes().len() {
+                return Err(CbstuError::MalformedInput(format!("root {} is not a node of the graph", root)));
+            }
+        }
+        let unique_weights = Util::sorted_unique_weights(graph)?;
+        let allowed = graph.subgraph(Constraints::allowed_edges(graph)?);
+        let graph = &allowed;
+        let required = Util::required_links(graph);
+        let (found, bound) = Util::bisect_thresholds(&unique_weights, |threshold| self.check(&graph.smaller_or_eq_than(threshold), budget, required));
+        let tree = match found {
+            Some(tree) => graph.subgraph(tree),
+            None => return Err(match Util::infeasibility_reason(graph) {
+                CbstuError::InfeasibleBudget if self.centers(graph).is_empty() => CbstuError::InfeasibleHops,
+                err => err,
+            }),
+        };
+        let cost = tree.calculate_total_cost();
+        Ok(BoundedSolution::new(tree, vec![cost], bound))
+    }
+
+    /// Decides whether a spanning tree of `graph` within the bound and holding every required link fits the budget.
+    fn check(&self, graph: &MutableGraph, budget: f64, required: usize) -> ThresholdCheck<Vec<EdgeId>> {
+        let centers = self.centers(graph);
+        if centers.is_empty() {
+            trace!("No center reaches every node");
+            return ThresholdCheck::Infeasible;
+        }
+        match graph.mst(CalculationType::Cost) {
+            Ok((_, cost, _)) if cost <= budget => {}
+            _ => return ThresholdCheck::Infeasible,
+        }
+        let cost = |tree: &[EdgeId]| tree.iter().map(|&edge| graph.cost(edge)).sum::<f64>();
+        let best = centers.iter()
+            .filter_map(|center| Self::prim(graph, center))
+            .filter(|tree| Util::required_in(graph, tree.iter().copied()) == required)
+            .min_by(|a, b| cost(a).total_cmp(&cost(b)));
+        if let Some(tree) = best.filter(|tree| cost(tree) <= budget) {
+            return ThresholdCheck::Feasible(tree);
+        }
+        if graph.edges().len() > EXACT_LIMIT {
+            return ThresholdCheck::Unknown;
+        }
+        let mut order = graph.edges_copy();
+        order.sort_by(|&a, &b| graph.is_required(b).cmp(&graph.is_required(a)).then(graph.cost(a).total_cmp(&graph.cost(b))));
+        // the chosen edges may not break the bound, and their cheapest completion has to fit
+        let admits = |chosen: &[EdgeId], edge: EdgeId| self.forest_fits(&graph.subgraph(chosen.iter().copied().chain([edge]).collect()));
+        let promising = |chosen: &[EdgeId], undecided: &[EdgeId]| {
+            Util::completion(graph, chosen, undecided.iter().copied(), |edge| graph.cost(edge))
+                .map_or(false, |completion| cost(chosen) + completion <= budget)
+        };
+        let mut found = None;
+        Util::branch_and_bound(graph, &order, required, admits, promising, |tree| {
+            let fits = cost(tree) <= budget;
+            if fits {
+                found = Some(tree.to_vec());
+            }
+            fits
+        });
+        match found {
+            Some(tree) => ThresholdCheck::Feasible(tree),
+            None => ThresholdCheck::Infeasible,
+        }
+    }
+
+    /// Centers of `graph` that reach every node within their radius.
+    fn centers(&self, graph: &MutableGraph) -> Vec<Center> {
+        let n = graph.nodes().len();
+        let candidates: Vec<Center> = match self.bound {
+            HopBound::Depth { root, hops } => vec![Center { nodes: vec![root], edge: None, radius: hops }],
+            HopBound::Diameter(hops) if hops % 2 == 0 => (0..n).map(|v| Center { nodes: vec![v], edge: None, radius: hops / 2 }).collect(),
+            HopBound::Diameter(hops) => graph.edges().iter().map(|&edge| {
+                let (u, v) = graph.endpoints(edge);
+                Center { nodes: vec![u, v], edge: Some(edge), radius: hops / 2 }
+            }).collect(),
+        };
+        let mut distances: Vec<Option<Vec<usize>>> = vec![None; n];
+        candidates.into_iter().filter(|center| {
+            center.nodes.iter().for_each(|&v| {
+                if distances[v].is_none() {
+                    distances[v] = Some(graph.hops_from(v));
+                }
+            });
+            (0..n).all(|w| center.nodes.iter().any(|&v| distances[v].as_ref().unwrap()[w] <= center.radius))
+        }).collect()
+    }
+
+    /// Prim from a center that only attaches nodes to tree nodes less than the radius away from it, taking the
+    /// cheapest such edge and among those the one closest to the center. `None` if some node cannot be attached.
+    fn prim(graph: &MutableGraph, center: &Center) -> Option<Vec<EdgeId>> {
+        let n = graph.nodes().len();
+        let mut depth = vec![usize::MAX; n];
+        center.nodes.iter().for_each(|&v| depth[v] = 0);
+        let mut tree: Vec<EdgeId> = center.edge.into_iter().collect();
+        while tree.len() + 1 < n {
+            let (edge, from, to) = graph.edges().iter()
+                .filter_map(|&edge| {
+                    let (u, v) = graph.endpoints(edge);
+                    match (depth[u], depth[v]) {
+                        (du, usize::MAX) if du < center.radius => Some((edge, u, v)),
+                        (usize::MAX, dv) if dv < center.radius => Some((edge, v, u)),
+                        _ => None,
+                    }
+                })
+                .min_by(|&(a, u, _), &(b, v, _)| graph.cost(a).total_cmp(&graph.cost(b)).then(depth[u].cmp(&depth[v])))?;
+            depth[to] = depth[from] + 1;
+            tree.push(edge);
+        }
+        Some(tree)
+    }
+
+    /// Whether a forest can still grow into a tree within the bound. Paths of the forest stay in the tree, so none may
+    /// be longer than the diameter, or twice the depth, and nodes joined to the root must be within the depth.
+    fn forest_fits(&self, forest: &MutableGraph) -> bool {
+        let longest = match self.bound {
+            HopBound::Depth { hops, .. } => 2 * hops,
+            HopBound::Diameter(hops) => hops,
+        };
+        if let HopBound::Depth { root, hops } = self.bound {
+            if forest.hops_from(root).iter().any(|&depth| depth != usize::MAX && depth > hops) {
+                return false;
+            }
+        }
+        (0..forest.nodes().len()).all(|v| forest.hops_from(v).iter().all(|&length| length == usize::MAX || length <= longest))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+    use crate::algorithms::constrained_bottleneck_spanning_tree::hop_limit::{HopBound, HopLimited};
+    use crate::algorithms::constrained_bottleneck_spanning_tree::solver::solver_by_name;
+    use crate::algorithms::constrained_bottleneck_spanning_tree::test_graphs::{brute_force, graph, square, Link};
+    use crate::datastructures::graph::edge_store::EdgeId;
+    use crate::error::CbstuError;
+
+    #[test]
+    fn test_loose_bound_matches_solver() {
+        let graph = square();
+        let berman = solver_by_name("berman").unwrap();
+        for budget in [0.0, 3.0, 5.0, 9.0, 15.0, 100.0] {
+            let expected = berman.solve(&graph, budget).unwrap();
+            for bound in [HopBound::Depth { root: 0, hops: 3 }, HopBound::Diameter(3)] {
+                let solution = HopLimited::new(bound).solve(&graph, budget).unwrap();
+                assert_eq!(solution.bottleneck(), expected.bottleneck(), "budget {} with {:?}", budget, bound);
+                assert!(solution.is_optimal());
+            }
+        }
+    }
+
+    #[test]
+    fn test_hop_bounds() {
+        let graph = square();
+        // within one hop of node 0 the tree is the star (0,1), (0,2), (3,0)
+        for (budget, bottleneck, cost) in [(0.0, -1.0, 0.0), (6.0, -3.0, 6.0), (8.0, -5.0, 8.0), (12.0, -9.0, 12.0)] {
+            let bound = HopBound::Depth { root: 0, hops: 1 };
+            let solution = HopLimited::new(bound).solve(&graph, budget).unwrap();
+            assert_eq!((solution.bottleneck(), solution.cost(), bound.measure(solution.tree())), (bottleneck, cost, Some(1)), "budget {}", budget);
+        }
+        // a diameter of two allows the stars at node 0 and node 2, the latter being (1,2), (2,3), (0,2)
+        for (budget, bottleneck, cost) in [(0.0, -1.0, 0.0), (6.0, -3.0, 6.0), (9.0, -6.0, 9.0), (12.0, -9.0, 12.0)] {
+            let solution = HopLimited::new(HopBound::Diameter(2)).solve(&graph, budget).unwrap();
+            assert_eq!((solution.bottleneck(), solution.cost(), HopBound::Diameter(2).measure(solution.tree())), (bottleneck, cost, Some(2)), "budget {}", budget);
+        }
+        assert!(matches!(HopLimited::new(HopBound::Depth { root: 1, hops: 1 }).solve(&graph, 5.0), Err(CbstuError::InfeasibleHops)));
+        assert!(matches!(HopLimited::new(HopBound::Diameter(1)).solve(&graph, 5.0), Err(CbstuError::InfeasibleHops)));
+        assert!(matches!(HopLimited::new(HopBound::Diameter(2)).solve(&graph, -1.0), Err(CbstuError::InfeasibleBudget)));
+        assert!(matches!(HopLimited::new(HopBound::Depth { root: 4, hops: 1 }).solve(&graph, 5.0), Err(CbstuError::MalformedInput(_))));
+    }
+
+    #[test]
+    fn test_random_hop_bounds() {
+        // random graphs on 6 nodes under a depth or diameter limit, against the best tree within the limit and the
+        // budget
+        let mut binding = 0;
+        for seed in 0..100 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut links: Vec<Link> = Vec::new();
+            for v in 1..6 {
+                links.push((rng.gen_range(0..v), v, rng.gen_range(1..10) as f64, rng.gen_range(10..20) as f64, rng.gen_range(0..10) as f64));
+            }
+            for _ in 0..4 {
+                let (u, v) = (rng.gen_range(0..6), rng.gen_range(0..6));
+                if u != v {
+                    links.push((u, v, rng.gen_range(1..10) as f64, rng.gen_range(10..20) as f64, rng.gen_range(0..10) as f64));
+                }
+            }
+            let graph = graph(6, &links);
+            let bound = match seed % 2 {
+                0 => HopBound::Depth { root: rng.gen_range(0..6), hops: rng.gen_range(1..4) },
+                _ => HopBound::Diameter(rng.gen_range(2..5)),
+            };
+            let budget = rng.gen_range(0..20) as f64;
+            let fits = |tree: &[EdgeId]| tree.iter().map(|&edge| graph.cost(edge)).sum::<f64>() <= budget;
+            let optimum = brute_force(&graph, 6, |tree| fits(tree) && bound.measure(&graph.subgraph(tree.to_vec())).unwrap() <= bound.limit());
+            match HopLimited::new(bound).solve(&graph, budget) {
+                Ok(solution) => {
+                    assert!(solution.is_optimal(), "seed {}", seed);
+                    assert_eq!(solution.bottleneck(), optimum, "seed {}", seed);
+                    assert!(solution.cost() <= budget, "seed {}", seed);
+                    assert!(bound.measure(solution.tree()).unwrap() <= bound.limit(), "seed {}", seed);
+                    binding += (solution.bottleneck() > brute_force(&graph, 6, fits)) as usize;
+                }
+                Err(err) => assert!(matches!(err, CbstuError::InfeasibleBudget | CbstuError::InfeasibleHops) && optimum.is_infinite(), "seed {}", seed),
+            }
+        }
+        // the limit costs bottleneck in some of the graphs
+        assert!(binding > 0);
+    }
+}
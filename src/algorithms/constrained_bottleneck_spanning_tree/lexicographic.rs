@@ -0,0 +1,340 @@
+use log::{debug, trace, warn};
+use crate::algorithms::constrained_bottleneck_spanning_tree::constraints::Constraints;
+use crate::algorithms::min_sum_spanning_tree::kruskal::CalculationType;
+use crate::algorithms::util::{ThresholdCheck, Util};
+use crate::datastructures::graph::edge_store::EdgeId;
+use crate::datastructures::graph::mutable_graph::MutableGraph;
+use crate::datastructures::uf::union_find::UF;
+use crate::error::CbstuError;
+
+/// Above this number of usable edges a threshold the Kruskal variants cannot decide is left undecided.
+const EXACT_LIMIT: usize = 200;
+/// Tolerance on path lengths in the exchange graph.
+const EPSILON: f64 = 1.0E-9;
+
+/// Result of the lexicographic variant, in the (inverted) weights of the solved graph. `ranks` are the weights of the
+/// tree from the worst down, so the first one is the bottleneck. The first `proven` ranks are proven optimal.
+#[derive(Debug, Clone)]
+pub struct LexicographicSolution {
+    tree: MutableGraph,
+    cost: f64,
+    ranks: Vec<f64>,
+    proven: usize,
+}
+
+impl LexicographicSolution {
+    pub fn tree(&self) -> &MutableGraph {
+        &self.tree
+    }
+
+    pub fn cost(&self) -> f64 {
+        self.cost
+    }
+
+    pub fn bottleneck(&self) -> f64 {
+        self.ranks[0]
+    }
+
+    pub fn ranks(&self) -> &Vec<f64> {
+        &self.ranks
+    }
+
+    pub fn proven(&self) -> usize {
+        self.proven
+    }
+
+    pub fn is_optimal(&self) -> bool {
+        self.proven == self.ranks.len()
+    }
+}
+
+/// Lexicographic CBSTU: maximizes the worst edge of the tree, then the second worst and so on, within the budget.
+pub struct Lexicographic();
+
+impl Lexicographic {
+    /// Threshold search rank by rank on the preprocessed graph under its link constraints. With the ranks before it
+    /// fixed at `b_1 >= ... >= b_(k-1)`, rank k reaches threshold `t` if some tree within budget has at most i - 1
+    /// edges heavier than `b_i` for every fixed rank and at most k - 1 heavier than `t`. The min-weight spanning tree
+    /// has the smallest weight at every rank, which bounds the search from below, and the tree of the previous rank
+    /// bounds it from above. Once a threshold is left undecided the ranks from there on are not proven optimal.
+    pub fn solve(graph: &MutableGraph, budget: f64) -> Result<LexicographicSolution, CbstuError> {
+        trace!("Solving lexicographic bottleneck spanning tree problem");
+        let allowed = graph.subgraph(Constraints::allowed_edges(graph)?);
+        let graph = &allowed;
+        let unique_weights = Util::sorted_unique_weights(graph)?;
+        let required = Util::required_links(graph);
+        let (lightest, _, _) = graph.mst(CalculationType::Weight)?;
+        let floor = Self::ranks(&lightest, lightest.edges());
+        let index = |weight: f64| unique_weights.partition_point(|&unique| unique < weight);
+        let mut limits: Vec<(f64, usize)> = Vec::with_capacity(floor.len());
+        let mut tree: Option<Vec<EdgeId>> = None;
+        let mut proven = floor.len();
+        for rank in 0..floor.len() {
+            let mut min = index(floor[rank]);
+            let mut max = match &tree {
+                Some(tree) => index(Self::ranks(graph, tree)[rank]),
+                None => unique_weights.len(),
+            };
+            while min < max {
+                let pivot = (min + max) / 2;
+                limits.push((unique_weights[pivot], rank));
+                match Self::check(graph, &limits, budget, required) {
+                    ThresholdCheck::Feasible(feasible) => {
+                        trace!("Feasible pivot {} for rank {}", unique_weights[pivot], rank + 1);
+                        tree = Some(feasible);
+                        max = pivot;
+                    }
+                    ThresholdCheck::Infeasible => min = pivot + 1,
+                    ThresholdCheck::Unknown => {
+                        trace!("Undecided pivot {} for rank {}", unique_weights[pivot], rank + 1);
+                        proven = proven.min(rank);
+                        min = pivot + 1;
+                    }
+                }
+                limits.pop();
+            }
+            if tree.is_none() {
+                return Err(Util::infeasibility_reason(graph));
+            }
+            debug!("Rank {} fixed at {}", rank + 1, unique_weights[min]);
+            limits.push((unique_weights[min], rank));
+        }
+        let tree = graph.subgraph(tree.unwrap());
+        let ranks = Self::ranks(&tree, tree.edges());
+        if proven < ranks.len() {
+            warn!("Solution not proven optimal from rank {} on [ranks: {}]", proven + 1, ranks.len());
+        }
+        Ok(LexicographicSolution { cost: tree.calculate_total_cost(), tree, ranks, proven })
+    }
+
+    /// Weights of the tree from the largest down.
+    pub fn ranks(graph: &MutableGraph, tree: &[EdgeId]) -> Vec<f64> {
+        let mut ranks: Vec<f64> = tree.iter().map(|&edge| graph.weight(edge)).collect();
+        ranks.sort_by(|a, b| b.total_cmp(a));
+        ranks
+    }
+
+    /// Decides whether a spanning tree within budget holds every required link and at most `count` edges heavier than
+    /// `threshold` for every limit. The min-weight spanning tree has the fewest edges above every threshold and the
+    /// cheapest tree with the required links the lowest cost, so either failing proves there is none, and the latter
+    /// decides when it keeps the limits. Otherwise Kruskal on weight or on cost that skips edges over a limit often
+    /// finds one, and up to `EXACT_LIMIT` usable edges the min-cost common base of the graphic matroid and the matroid
+    /// of the limits decides.
+    fn check(graph: &MutableGraph, limits: &[(f64, usize)], budget: f64, required: usize) -> ThresholdCheck<Vec<EdgeId>> {
+        let graph = &graph.smaller_or_eq_than(limits[0].0);
+        let fits = |tree: &[EdgeId]| tree.iter().map(|&edge| graph.cost(edge)).sum::<f64>() <= budget;
+        let holds = |tree: &[EdgeId]| Util::required_in(graph, tree.iter().copied()) == required;
+        match graph.mst(CalculationType::Weight) {
+            Ok((lightest, _, _)) if Self::within_limits(graph, limits, lightest.edges()) => {}
+            _ => return ThresholdCheck::Infeasible,
+        }
+        let cheapest = match Self::kruskal(graph, &[], |edge| (graph.cost(edge), graph.weight(edge))) {
+            Some(cheapest) if fits(&cheapest) && holds(&cheapest) => cheapest,
+            _ => return ThresholdCheck::Infeasible,
+        };
+        if Self::within_limits(graph, limits, &cheapest) {
+            return ThresholdCheck::Feasible(cheapest);
+        }
+        let lightest = Self::kruskal(graph, limits, |edge| (graph.weight(edge), graph.cost(edge)));
+        let cheapest = Self::kruskal(graph, limits, |edge| (graph.cost(edge), graph.weight(edge)));
+        if let Some(tree) = lightest.into_iter().chain(cheapest).find(|tree| fits(tree) && holds(tree)) {
+            return ThresholdCheck::Feasible(tree);
+        }
+        if graph.edges().len() > EXACT_LIMIT {
+            return ThresholdCheck::Unknown;
+        }
+        trace!("Matroid intersection for {} limits", limits.len());
+        match Self::intersection(graph, limits, required).filter(|tree| fits(tree) && holds(tree)) {
+            Some(tree) => ThresholdCheck::Feasible(tree),
+            None => ThresholdCheck::Infeasible,
+        }
+    }
+
+    /// Kruskal on `key` with the required edges first, skipping edges that would break a limit. `None` if the result
+    /// does not span the graph.
+    fn kruskal(graph: &MutableGraph, limits: &[(f64, usize)], key: impl Fn(EdgeId) -> (f64, f64)) -> Option<Vec<EdgeId>> {
+        let mut order = graph.edges_copy();
+        order.sort_by(|&a, &b| graph.is_required(b).cmp(&graph.is_required(a)).then_with(|| {
+            let (a, b) = (key(a), key(b));
+            a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1))
+        }));
+        let mut counts = vec![0; limits.len()];
+        Util::kruskal(graph, order, |edge| {
+            let above: Vec<usize> = (0..limits.len()).filter(|&i| graph.weight(edge) > limits[i].0).collect();
+            let admitted = above.iter().all(|&i| counts[i] < limits[i].1);
+            if admitted {
+                above.iter().for_each(|&i| counts[i] += 1);
+            }
+            admitted
+        })
+    }
+
+    fn within_limits(graph: &MutableGraph, limits: &[(f64, usize)], tree: &[EdgeId]) -> bool {
+        limits.iter().all(|&(threshold, count)| tree.iter().filter(|&&edge| graph.weight(edge) > threshold).count() <= count)
+    }
+
+    /// Min-cost common base of the graphic matroid and the matroid of the limits, whose sets of edges above the
+    /// thresholds are nested. Shortest augmenting paths in the exchange graph, with the fewest arcs among the
+    /// shortest, keep the set of every size a min-cost common independent set. Required edges get a discount larger
+    /// than all costs together, so a base with every required link is preferred to any without. `None` if no common
+    /// base spans the graph.
+    fn intersection(graph: &MutableGraph, limits: &[(f64, usize)], required: usize) -> Option<Vec<EdgeId>> {
+        let n = graph.nodes().len();
+        let edges = graph.edges();
+        let m = edges.len();
+        let discount = edges.iter().map(|&edge| graph.cost(edge).abs()).sum::<f64>() + 1.0;
+        let costs: Vec<f64> = edges.iter().map(|&edge| match graph.is_required(edge) && required > 0 {
+            true => graph.cost(edge) - discount,
+            false => graph.cost(edge),
+        }).collect();
+        // limits from the smallest set of edges above the threshold to the largest
+        let mut limits = limits.to_vec();
+        limits.sort_by(|a, b| b.0.total_cmp(&a.0));
+        let mut chosen = vec![false; m];
+        loop {
+            let set: Vec<usize> = (0..m).filter(|&i| chosen[i]).collect();
+            let forest: Vec<EdgeId> = set.iter().map(|&i| edges[i]).collect();
+            if set.len() + 1 == n {
+                return Some(forest);
+            }
+            let counts: Vec<usize> = limits.iter()
+                .map(|&(threshold, _)| set.iter().filter(|&&i| graph.weight(edges[i]) > threshold).count())
+                .collect();
+            let mut arcs = vec![Vec::new(); m];
+            let mut sources = vec![false; m];
+            let mut sinks = vec![false; m];
+            let mut uf = UF::new(n as i32);
+            set.iter().for_each(|&i| {
+                let (u, v) = graph.endpoints(edges[i]);
+                uf.union(u, v);
+            });
+            for x in (0..m).filter(|&x| !chosen[x]) {
+                let (u, v) = graph.endpoints(edges[x]);
+                // the graphic matroid takes x in exchange for an edge on the cycle it closes
+                match uf.connected(u, v) {
+                    true => Util::tree_path(graph, &forest, u, v).into_iter().for_each(|position| arcs[set[position]].push(x)),
+                    false => {
+                        sources[x] = true;
+                        set.iter().for_each(|&y| arcs[y].push(x));
+                    }
+                }
+                // the matroid of the limits takes x in exchange for an edge in the smallest full set holding x
+                let weight = graph.weight(edges[x]);
+                match (0..limits.len()).find(|&j| weight > limits[j].0 && counts[j] >= limits[j].1) {
+                    Some(j) => set.iter().filter(|&&y| graph.weight(edges[y]) > limits[j].0).for_each(|&y| arcs[x].push(y)),
+                    None => {
+                        sinks[x] = true;
+                        set.iter().for_each(|&y| arcs[x].push(y));
+                    }
+                }
+            }
+            let length = |i: usize| match chosen[i] {
+                true => -costs[i],
+                false => costs[i],
+            };
+            let shorter = |a: (f64, usize), b: (f64, usize)| a.0 < b.0 - EPSILON || (a.0 <= b.0 + EPSILON && a.1 < b.1);
+            let mut distances: Vec<Option<(f64, usize)>> = (0..m).map(|i| sources[i].then(|| (length(i), 0))).collect();
+            let mut predecessors: Vec<Option<usize>> = vec![None; m];
+            for _ in 0..m {
+                let mut changed = false;
+                for a in 0..m {
+                    let Some((distance, steps)) = distances[a] else { continue };
+                    for &b in &arcs[a] {
+                        let candidate = (distance + length(b), steps + 1);
+                        if distances[b].map_or(true, |current| shorter(candidate, current)) {
+                            distances[b] = Some(candidate);
+                            predecessors[b] = Some(a);
+                            changed = true;
+                        }
+                    }
+                }
+                if !changed {
+                    break;
+                }
+            }
+            let end = (0..m).filter(|&i| sinks[i] && distances[i].is_some())
+                .reduce(|a, b| match shorter(distances[b].unwrap(), distances[a].unwrap()) {
+                    true => b,
+                    false => a,
+                })?;
+            let mut path = vec![end];
+            while let Some(previous) = predecessors[*path.last().unwrap()] {
+                path.push(previous);
+            }
+            path.into_iter().for_each(|i| chosen[i] = !chosen[i]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+    use crate::algorithms::constrained_bottleneck_spanning_tree::lexicographic::Lexicographic;
+    use crate::algorithms::constrained_bottleneck_spanning_tree::solver::solver_by_name;
+    use crate::algorithms::constrained_bottleneck_spanning_tree::test_graphs::{brute_force_ranks, graph, square, Link};
+    use crate::error::CbstuError;
+
+    #[test]
+    fn test_bottleneck_matches_solver() {
+        let graph = square();
+        let berman = solver_by_name("berman").unwrap();
+        for budget in [0.0, 2.0, 3.0, 5.0, 8.0, 9.0, 12.0, 100.0] {
+            let solution = Lexicographic::solve(&graph, budget).unwrap();
+            assert_eq!(solution.bottleneck(), berman.solve(&graph, budget).unwrap().bottleneck(), "budget {}", budget);
+            assert!(solution.is_optimal());
+        }
+        assert!(matches!(Lexicographic::solve(&graph, -1.0), Err(CbstuError::InfeasibleBudget)));
+    }
+
+    #[test]
+    fn test_ranks() {
+        let graph = square();
+        // with a budget of 8 the bottleneck stays at 6, but (0,1) upgraded to 10 instead of (1,2) to 8 lifts the second rank
+        for (budget, ranks, cost) in [(0.0, vec![3.0, 5.0, 6.0], 0.0), (5.0, vec![6.0, 8.0, 9.0], 5.0), (8.0, vec![6.0, 9.0, 10.0], 6.0), (100.0, vec![10.0, 12.0, 20.0], 15.0)] {
+            let solution = Lexicographic::solve(&graph, budget).unwrap();
+            let capacities: Vec<f64> = solution.ranks().iter().map(|&weight| -weight).collect();
+            assert_eq!((capacities, solution.cost()), (ranks, cost), "budget {}", budget);
+            assert!(solution.tree().is_spanning_tree());
+        }
+    }
+
+    #[test]
+    fn test_random_ranks() {
+        // the tree of the bottleneck solver often settles for worse lower ranks than the lexicographic optimum, and
+        // negative budgets leave no tree at all
+        let berman = solver_by_name("berman").unwrap();
+        let (mut differs, mut infeasible) = (0, 0);
+        for seed in 0..100 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut links: Vec<Link> = Vec::new();
+            for v in 1..5 {
+                links.push((rng.gen_range(0..v), v, rng.gen_range(1..10) as f64, rng.gen_range(10..20) as f64, rng.gen_range(0..10) as f64));
+            }
+            for _ in 0..5 {
+                let (u, v) = (rng.gen_range(0..5), rng.gen_range(0..5));
+                if u != v {
+                    links.push((u, v, rng.gen_range(1..10) as f64, rng.gen_range(10..20) as f64, rng.gen_range(0..10) as f64));
+                }
+            }
+            let graph = graph(5, &links);
+            let budget = rng.gen_range(-2..20) as f64;
+            let optimum = brute_force_ranks(&graph, 5, &[0, 1, 2, 3, 4], |tree| tree.iter().map(|&edge| graph.cost(edge)).sum::<f64>() <= budget);
+            match (Lexicographic::solve(&graph, budget), optimum) {
+                (Ok(solution), Some(optimum)) => {
+                    assert!(solution.is_optimal());
+                    assert!(solution.cost() <= budget);
+                    assert_eq!(solution.ranks(), &optimum, "seed {}", seed);
+                    let tree = berman.solve(&graph, budget).unwrap().into_tree().unwrap();
+                    differs += (Lexicographic::ranks(&tree, tree.edges()) != optimum) as usize;
+                }
+                (Err(err), None) => {
+                    assert!(matches!(err, CbstuError::InfeasibleBudget), "seed {}", seed);
+                    infeasible += 1;
+                }
+                (result, optimum) => panic!("seed {}: {:?} against {:?}", seed, result.map(|solution| solution.ranks().clone()), optimum),
+            }
+        }
+        assert!(differs > 0 && infeasible > 0);
+    }
+}
@@ -0,0 +1,117 @@
+use log::{debug, trace};
+use crate::algorithms::constrained_bottleneck_spanning_tree::constraints::Constraints;
+use crate::algorithms::min_sum_spanning_tree::kruskal::CalculationType;
+use crate::datastructures::graph::edge::Edge;
+use crate::datastructures::graph::mutable_graph::MutableGraph;
+use crate::error::CbstuError;
+
+/// Cheapest way to reach a target bottleneck. `bottleneck` is the bottleneck the tree actually reaches, which can be
+/// better than the target. Like [`Solution`](super::solver::Solution) it is expressed in the (inverted) weights of
+/// the solved graph.
+#[derive(Debug, Clone)]
+pub struct BudgetSolution {
+    tree: MutableGraph,
+    cost: f64,
+    bottleneck: f64,
+    upgraded_edges: Vec<Edge>,
+    built_edges: Vec<Edge>,
+}
+
+impl BudgetSolution {
+    fn new(tree: MutableGraph, cost: f64, bottleneck: f64) -> BudgetSolution {
+        let (built_edges, upgraded_edges) = tree.edges().iter()
+            .filter(|&&edge| tree.is_upgraded(edge))
+            .map(|&edge| tree.edge(edge))
+            .partition(|edge| edge.is_candidate());
+        BudgetSolution { tree, cost, bottleneck, upgraded_edges, built_edges }
+    }
+
+    pub fn tree(&self) -> &MutableGraph {
+        &self.tree
+    }
+
+    pub fn cost(&self) -> f64 {
+        self.cost
+    }
+
+    pub fn bottleneck(&self) -> f64 {
+        self.bottleneck
+    }
+
+    pub fn upgraded_edges(&self) -> &Vec<Edge> {
+        &self.upgraded_edges
+    }
+
+    pub fn built_edges(&self) -> &Vec<Edge> {
+        &self.built_edges
+    }
+}
+
+pub struct MinBudget();
+
+impl MinBudget {
+    /// Minimum upgrade budget for a spanning tree with bottleneck `target` or better. The graph is expected to be
+    /// preprocessed like for the CBSTU solvers, so `target` is an inverted weight. Every edge copy below the target is
+    /// usable, the cheapest spanning tree among them (Kruskal on cost) gives the minimum budget. Link constraints are
+    /// respected through the reduced graph of [`Constraints::reduce`].
+    pub fn run(graph: &MutableGraph, target: f64) -> Result<BudgetSolution, CbstuError> {
+        trace!("Computing minimum budget for bottleneck {}", target);
+        let reduced = Constraints::reduce(graph, None)?;
+        match reduced.graph().smaller_or_eq_than(target).mst(CalculationType::Cost) {
+            Ok((tree, _, bottleneck)) if reduced.satisfies(&tree) => {
+                let (tree, cost) = reduced.restore(graph, &tree);
+                debug!("Minimum budget found [bottleneck: {}, cost: {}]", bottleneck, cost);
+                Ok(BudgetSolution::new(tree, cost, bottleneck))
+            }
+            Ok(_) => Err(CbstuError::UnreachableBottleneck),
+            Err(CbstuError::DisconnectedGraph) | Err(CbstuError::EmptyEdgeSet) => match reduced.graph().mst(CalculationType::Cost) {
+                Ok(_) => Err(CbstuError::UnreachableBottleneck),
+                Err(err) => Err(err),
+            },
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// See [`MinBudget::run`].
+pub fn min_budget_for_bottleneck(graph: &MutableGraph, target: f64) -> Result<BudgetSolution, CbstuError> {
+    MinBudget::run(graph, target)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithms::constrained_bottleneck_spanning_tree::min_budget::min_budget_for_bottleneck;
+    use crate::algorithms::constrained_bottleneck_spanning_tree::pareto::ParetoFrontier;
+    use crate::algorithms::constrained_bottleneck_spanning_tree::test_graphs::{graph, preprocess, square};
+    use crate::error::CbstuError;
+    use crate::io::generator::Generator;
+
+    #[test]
+    fn test_square_targets() {
+        let graph = square();
+        for (target, cost, bottleneck) in [(-10.0, 15.0, -10.0), (-7.0, 9.0, -8.0), (-6.0, 5.0, -6.0), (-1.0, 0.0, -3.0)] {
+            let solution = min_budget_for_bottleneck(&graph, target).unwrap();
+            assert_eq!((solution.cost(), solution.bottleneck()), (cost, bottleneck));
+            assert!(solution.tree().is_spanning_tree());
+            assert_eq!(solution.tree().calculate_total_cost(), cost);
+            assert_eq!(solution.upgraded_edges().len(), solution.tree().number_of_edges_upgraded());
+        }
+        assert!(matches!(min_budget_for_bottleneck(&graph, -11.0), Err(CbstuError::UnreachableBottleneck)));
+    }
+
+    #[test]
+    fn test_matches_frontier() {
+        let graph = preprocess(Generator::random_instance(15, 30, 5).unwrap());
+        for point in ParetoFrontier::run(&graph).unwrap() {
+            let solution = min_budget_for_bottleneck(&graph, point.bottleneck()).unwrap();
+            assert_eq!(solution.cost(), point.cost());
+            assert_eq!(solution.bottleneck(), point.bottleneck());
+        }
+    }
+
+    #[test]
+    fn test_disconnected() {
+        let graph = graph(3, &[(0, 1, 1.0, 2.0, 1.0)]);
+        assert!(matches!(min_budget_for_bottleneck(&graph, -2.0), Err(CbstuError::DisconnectedGraph)));
+    }
+}
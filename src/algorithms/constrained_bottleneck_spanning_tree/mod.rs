@@ -1,4 +1,23 @@
 pub mod berman;
 pub mod punnen;
 pub mod edge_elimination;
-pub mod fast_edge_elimination;
\ No newline at end of file
+pub mod fast_edge_elimination;
+pub mod solver;
+pub mod verify;
+pub mod pareto;
+pub mod min_budget;
+pub mod node_upgrade;
+pub mod constraints;
+pub mod cardinality;
+pub mod multi_resource;
+pub mod regional;
+pub mod bundles;
+pub mod discount;
+pub mod arborescence;
+pub mod steiner;
+pub mod degree;
+pub mod hop_limit;
+pub mod balanced;
+pub mod lexicographic;
+#[cfg(test)]
+pub(crate) mod test_graphs;
@@ -0,0 +1,208 @@
+use log::trace;
+use crate::algorithms::constrained_bottleneck_spanning_tree::constraints::Constraints;
+use crate::algorithms::min_sum_spanning_tree::kruskal::CalculationType;
+use crate::algorithms::util::{BoundedSolution, ThresholdCheck, Util};
+use crate::datastructures::graph::edge::Edge;
+use crate::datastructures::graph::edge_store::EdgeId;
+use crate::datastructures::graph::mutable_graph::MutableGraph;
+use crate::error::CbstuError;
+
+/// Above this number of usable edges a threshold the Lagrangian relaxation cannot decide is left undecided.
+const EXACT_LIMIT: usize = 40;
+/// Subgradient steps per threshold.
+const ITERATIONS: usize = 60;
+
+pub struct MultiResource();
+
+impl MultiResource {
+    /// Best bottleneck spanning tree of the preprocessed graph whose upgrades fit every entry of `budgets`, resource
+    /// `r` being entry `r` of the cost vectors of the edges. Bisection over the thresholds; a threshold is decided by
+    /// Lagrangian relaxation over the cost Kruskal, whose trees double as heuristic solutions and whose bound proves a
+    /// threshold out of reach. What it leaves open is settled by branch and bound when the threshold has at most
+    /// `EXACT_LIMIT` usable edges and left undecided otherwise, then the solution comes with a bound.
+    pub fn run(graph: &MutableGraph, budgets: &[f64]) -> Result<BoundedSolution, CbstuError> {
+        trace!("Solving constrained bottleneck spanning tree problem with {} budget resources", budgets.len());
+        if budgets.is_empty() {
+            return Err(CbstuError::MalformedInput(String::from("at least one budget is needed")));
+        }
+        if let Some(&edge) = graph.edges().iter().find(|&&edge| graph.costs(edge).len() > budgets.len()) {
+            return Err(CbstuError::MalformedInput(format!("edge {:?} has more costs than there are budgets", graph.endpoints(edge))));
+        }
+        let unique_weights = Util::sorted_unique_weights(graph)?;
+        let allowed = graph.subgraph(Constraints::allowed_edges(graph)?);
+        // edges that exceed a budget on their own can never be used
+        let graph = &allowed.filter(|edge| (0..budgets.len()).all(|r| graph.resource_cost(edge, r) <= budgets[r]));
+        let required = Util::required_links(graph);
+        let (found, bound) = Util::bisect_thresholds(&unique_weights, |threshold| Self::check(&graph.smaller_or_eq_than(threshold), budgets, required));
+        let tree = match found {
+            Some(tree) => graph.subgraph(tree),
+            None => return Err(Util::infeasibility_reason(&allowed)),
+        };
+        let costs = (0..budgets.len()).map(|r| tree.edges().iter().map(|&edge| tree.resource_cost(edge, r)).sum()).collect();
+        Ok(BoundedSolution::new(tree, costs, bound))
+    }
+
+    /// Decides whether a spanning tree of `graph` holding every required link fits the budgets.
+    fn check(graph: &MutableGraph, budgets: &[f64], required: usize) -> ThresholdCheck<Vec<EdgeId>> {
+        let edges = graph.edges();
+        // costs relative to the budgets, a resource without budget only admits free edges
+        let scaled: Vec<Vec<f64>> = edges.iter()
+            .map(|&edge| (0..budgets.len()).map(|r| match budgets[r] > 0.0 {
+                true => graph.resource_cost(edge, r) / budgets[r],
+                false => 0.0,
+            }).collect())
+            .collect();
+        let fits = |tree: &[usize]| (0..budgets.len()).all(|r| tree.iter().map(|&i| graph.resource_cost(edges[i], r)).sum::<f64>() <= budgets[r]);
+        let mut multipliers = vec![1.0; budgets.len()];
+        for iteration in 0..ITERATIONS {
+            let combined: Vec<f64> = scaled.iter().map(|costs| costs.iter().zip(multipliers.iter()).map(|(c, m)| c * m).sum()).collect();
+            let tree = match Self::kruskal(graph, &combined) {
+                Some(tree) => tree,
+                None => return ThresholdCheck::Infeasible,
+            };
+            if Util::required_in(graph, tree.iter().map(|&i| edges[i])) < required {
+                trace!("Required link out of reach");
+                return ThresholdCheck::Infeasible;
+            }
+            if fits(&tree) {
+                return ThresholdCheck::Feasible(tree.iter().map(|&i| edges[i]).collect());
+            }
+            let subgradient: Vec<f64> = (0..budgets.len()).map(|r| tree.iter().map(|&i| scaled[i][r]).sum::<f64>() - 1.0).collect();
+            let bound: f64 = subgradient.iter().zip(multipliers.iter()).map(|(g, m)| g * m).sum();
+            if bound > 1e-9 {
+                trace!("Lagrangian bound {} proves the threshold infeasible", bound);
+                return ThresholdCheck::Infeasible;
+            }
+            let step = 1.0 / (iteration + 1) as f64;
+            multipliers.iter_mut().zip(subgradient.iter()).for_each(|(m, g)| *m = (*m + step * g).max(0.0));
+        }
+        if let Some(tree) = Self::bottleneck_resource_tree(graph, &scaled).filter(|tree| fits(tree) && Util::required_in(graph, tree.iter().map(|&i| edges[i])) == required) {
+            return ThresholdCheck::Feasible(tree.iter().map(|&i| edges[i]).collect());
+        }
+        if edges.len() > EXACT_LIMIT {
+            return ThresholdCheck::Unknown;
+        }
+        let mut order: Vec<usize> = (0..edges.len()).collect();
+        order.sort_by(|&a, &b| graph.is_required(edges[b]).cmp(&graph.is_required(edges[a]))
+            .then(scaled[a].iter().sum::<f64>().total_cmp(&scaled[b].iter().sum::<f64>())));
+        let order: Vec<EdgeId> = order.iter().map(|&i| edges[i]).collect();
+        let spent = |tree: &[EdgeId], r: usize| tree.iter().map(|&edge| graph.resource_cost(edge, r)).sum::<f64>();
+        // cut when the cheapest completion of some resource does not fit anymore
+        let promising = |chosen: &[EdgeId], undecided: &[EdgeId]| (0..budgets.len()).all(|r| {
+            Util::completion(graph, chosen, undecided.iter().copied(), |edge| graph.resource_cost(edge, r))
+                .map_or(false, |cost| spent(chosen, r) + cost <= budgets[r])
+        });
+        let mut found = None;
+        Util::branch_and_bound(graph, &order, required, |_, _| true, promising, |tree| {
+            let fits = (0..budgets.len()).all(|r| spent(tree, r) <= budgets[r]);
+            if fits {
+                found = Some(tree.to_vec());
+            }
+            fits
+        });
+        match found {
+            Some(tree) => ThresholdCheck::Feasible(tree),
+            None => ThresholdCheck::Infeasible,
+        }
+    }
+
+    /// Kruskal on the combined costs through [`MutableGraph::mst`]; required edges go first. Returns positions in the
+    /// edge list of `graph`.
+    fn kruskal(graph: &MutableGraph, combined: &[f64]) -> Option<Vec<usize>> {
+        let shift = combined.iter().map(|c| c.abs()).sum::<f64>() + 1.0;
+        let edges: Vec<Edge> = graph.edges().iter().zip(combined.iter())
+            .map(|(&edge, &cost)| {
+                let (u, v) = graph.endpoints(edge);
+                let cost = if graph.is_required(edge) { cost - shift } else { cost };
+                Edge::new(u, v).weight(graph.weight(edge)).cost(cost)
+            })
+            .collect();
+        let combined_graph = MutableGraph::new(graph.nodes_copy(), edges);
+        combined_graph.mst(CalculationType::Cost).ok().map(|(tree, _, _)| tree.edges().iter().map(|&edge| edge as usize).collect())
+    }
+
+    /// Cheapest tree on the largest budget share of each edge, a second heuristic next to the Lagrangian trees.
+    fn bottleneck_resource_tree(graph: &MutableGraph, scaled: &[Vec<f64>]) -> Option<Vec<usize>> {
+        let worst: Vec<f64> = scaled.iter().map(|costs| costs.iter().copied().fold(0.0, f64::max)).collect();
+        Self::kruskal(graph, &worst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+    use crate::algorithms::constrained_bottleneck_spanning_tree::multi_resource::{MultiResource, EXACT_LIMIT};
+    use crate::algorithms::constrained_bottleneck_spanning_tree::solver::solver_by_name;
+    use crate::algorithms::constrained_bottleneck_spanning_tree::test_graphs::{brute_force, build, nodes, Link, SQUARE};
+    use crate::datastructures::graph::mutable_graph::MutableGraph;
+    use crate::error::CbstuError;
+
+    fn square_graph(resources: usize) -> MutableGraph {
+        // capex and crew-days per upgrade
+        let costs = [[4.0, 1.0], [3.0, 3.0], [5.0, 1.0], [2.0, 2.0], [6.0, 1.0]];
+        build(nodes(4), &SQUARE, |i, edge| edge.costs(costs[i][..resources].to_vec()))
+    }
+
+    #[test]
+    fn test_single_resource_matches_solver() {
+        let graph = square_graph(1);
+        for budget in [0.0, 3.0, 5.0, 9.0, 100.0] {
+            let solution = MultiResource::run(&graph, &[budget]).unwrap();
+            let expected = solver_by_name("berman").unwrap().solve(&graph, budget).unwrap();
+            assert_eq!(solution.bottleneck(), expected.bottleneck());
+            assert!(solution.is_optimal());
+        }
+    }
+
+    #[test]
+    fn test_two_resources() {
+        let graph = square_graph(2);
+        // (capex, crew-days, bottleneck)
+        for (capex, crew_days, bottleneck) in [(5.0, 5.0, -6.0), (5.0, 4.0, -5.0), (100.0, 4.0, -10.0), (9.0, 2.0, -5.0), (100.0, 0.0, -3.0)] {
+            let solution = MultiResource::run(&graph, &[capex, crew_days]).unwrap();
+            assert_eq!(solution.bottleneck(), bottleneck, "budgets {} and {}", capex, crew_days);
+            assert!(solution.is_optimal());
+            assert!(solution.costs()[0] <= capex && solution.costs()[1] <= crew_days);
+            assert!(solution.tree().is_spanning_tree());
+        }
+        assert!(matches!(MultiResource::run(&graph, &[-1.0, 0.0]), Err(CbstuError::InfeasibleBudget)));
+        assert!(matches!(MultiResource::run(&graph, &[1.0]), Err(CbstuError::MalformedInput(_))));
+    }
+
+    #[test]
+    fn test_bound_above_exact_limit() {
+        // a random core on 5 nodes under EXACT_LIMIT / 2 pendant links that are free at full capacity, so every
+        // threshold has too many usable edges for branch and bound while the optimum is decided in the core
+        let mut undecided = 0;
+        for seed in 0..150 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut links: Vec<Link> = Vec::new();
+            for v in 1..5 {
+                links.push((rng.gen_range(0..v), v, rng.gen_range(1..10) as f64, rng.gen_range(10..20) as f64, 0.0));
+            }
+            for _ in 0..4 {
+                let (u, v) = (rng.gen_range(0..5), rng.gen_range(0..5));
+                if u != v {
+                    links.push((u, v, rng.gen_range(1..10) as f64, rng.gen_range(10..20) as f64, 0.0));
+                }
+            }
+            let mut costs: Vec<Vec<f64>> = links.iter().map(|_| vec![rng.gen_range(0..10) as f64, rng.gen_range(0..10) as f64]).collect();
+            for p in 0..EXACT_LIMIT / 2 {
+                links.push((p % 5, 5 + p, 100.0, 101.0, 0.0));
+                costs.push(vec![1.0, 1.0]);
+            }
+            let budgets = [rng.gen_range(5..20) as f64, rng.gen_range(5..20) as f64];
+            let graph = build(nodes(5 + EXACT_LIMIT / 2), &links, |i, edge| edge.costs(costs[i].clone()));
+            let optimum = brute_force(&graph, 5, |tree| (0..2).all(|r| tree.iter().map(|&edge| graph.resource_cost(edge, r)).sum::<f64>() <= budgets[r]));
+            match MultiResource::run(&graph, &budgets) {
+                Ok(solution) => {
+                    assert!(solution.bound() <= optimum && optimum <= solution.bottleneck(), "seed {}", seed);
+                    undecided += !solution.is_optimal() as usize;
+                }
+                Err(err) => assert!(matches!(err, CbstuError::InfeasibleBudget) && optimum.is_infinite(), "seed {}", seed),
+            }
+        }
+        assert!(undecided > 0);
+    }
+}
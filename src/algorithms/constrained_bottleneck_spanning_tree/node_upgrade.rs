@@ -0,0 +1,326 @@
+use log::{debug, trace, warn};
+use crate::algorithms::constrained_bottleneck_spanning_tree::constraints::Constraints;
+use crate::datastructures::graph::edge_store::EdgeId;
+use crate::datastructures::graph::mutable_graph::MutableGraph;
+use crate::datastructures::uf::union_find::UF;
+use crate::error::CbstuError;
+
+/// Above this number of nodes whose upgrade matters for a threshold, the cheapest upgrade set is approximated.
+const EXACT_LIMIT: usize = 16;
+
+/// Result of the node upgrade variant. `capacity` is the bottleneck capacity (not inverted) of the links after
+/// upgrading `upgraded_nodes`. `optimal` is false if some threshold check fell back on the heuristic.
+#[derive(Debug, Clone)]
+pub struct NodeUpgradeSolution {
+    tree: MutableGraph,
+    capacity: f64,
+    cost: f64,
+    upgraded_nodes: Vec<usize>,
+    optimal: bool,
+}
+
+impl NodeUpgradeSolution {
+    pub fn tree(&self) -> &MutableGraph {
+        &self.tree
+    }
+
+    pub fn capacity(&self) -> f64 {
+        self.capacity
+    }
+
+    pub fn cost(&self) -> f64 {
+        self.cost
+    }
+
+    pub fn upgraded_nodes(&self) -> &Vec<usize> {
+        &self.upgraded_nodes
+    }
+
+    pub fn is_optimal(&self) -> bool {
+        self.optimal
+    }
+}
+
+/// Cheapest upgrade set found for a threshold: upgraded flags per node, their cost and whether the set is optimal.
+type UpgradeSet = (Vec<bool>, f64, bool);
+
+pub struct NodeUpgrade();
+
+impl NodeUpgrade {
+    /// Chooses the nodes to upgrade within the budget that maximize the bottleneck capacity of a spanning tree. The
+    /// graph holds the capacities `k` of the links as weights, not inverted; upgrades of the links are not used.
+    /// Bisection over the candidate capacities: for a threshold the cheapest set of nodes whose upgrade connects the
+    /// links reaching it is found by branch and bound, or by reverse deletion when too many nodes matter. Forbidden
+    /// links are left out and required links are always in the tree, their upgrade flags do not apply.
+    pub fn run(graph: &MutableGraph, budget: f64) -> Result<NodeUpgradeSolution, CbstuError> {
+        trace!("Solving constrained bottleneck spanning tree problem with node upgrades");
+        if graph.edges().is_empty() {
+            return Err(CbstuError::EmptyEdgeSet);
+        }
+        let graph = &graph.subgraph(Constraints::allowed_edges(graph)?);
+        let candidates = Self::candidate_weights(graph);
+        let worst = candidates.len() - 1;
+        let mut final_set = match Self::cheapest_upgrades(graph, candidates[worst]) {
+            None => return Err(CbstuError::DisconnectedGraph),
+            Some(set) if set.1 > budget => return Err(CbstuError::InfeasibleBudget),
+            Some(set) => (worst, set),
+        };
+        let mut optimal = final_set.1.2;
+        let mut min = 0;
+        let mut max = worst;
+        while min < max {
+            let pivot = (min + max) / 2;
+            match Self::cheapest_upgrades(graph, candidates[pivot]) {
+                Some(set) if set.1 <= budget => {
+                    debug!("Feasible threshold [capacity: {}, cost: {}]", candidates[pivot], set.1);
+                    optimal &= set.2;
+                    final_set = (pivot, set);
+                    max = pivot;
+                }
+                set => {
+                    optimal &= set.map_or(true, |set| set.2);
+                    min = pivot + 1;
+                }
+            }
+        }
+        if !optimal {
+            warn!("Upgrade sets above {} nodes are approximated, solution may not be optimal", EXACT_LIMIT);
+        }
+        let (_, (upgraded, cost, _)) = final_set;
+        let (tree, capacity) = Self::widest_tree(graph, &upgraded);
+        let upgraded_nodes = (0..upgraded.len()).filter(|&node| upgraded[node]).collect();
+        Ok(NodeUpgradeSolution { tree, capacity, cost, upgraded_nodes, optimal })
+    }
+
+    /// Capacity of a link with the given endpoints upgraded or not.
+    pub fn effective_weight(graph: &MutableGraph, edge: EdgeId, upgraded: &[bool]) -> f64 {
+        let (u, v) = graph.endpoints(edge);
+        let (node_u, node_v) = (&graph.nodes()[u], &graph.nodes()[v]);
+        let mut weight = graph.weight(edge);
+        let mut cap = f64::INFINITY;
+        for (node, upgraded) in [(node_u, upgraded[u]), (node_v, upgraded[v])] {
+            match upgraded {
+                true => {
+                    weight *= node.get_multiplier();
+                    cap = cap.min(node.get_upgraded_capacity());
+                }
+                false => cap = cap.min(node.get_capacity()),
+            }
+        }
+        weight.min(cap)
+    }
+
+    /// Every capacity a link can get from upgrading none, one or both of its endpoints, best first.
+    fn candidate_weights(graph: &MutableGraph) -> Vec<f64> {
+        let mut upgraded = vec![false; graph.nodes().len()];
+        let mut candidates = Vec::with_capacity(4 * graph.edges().len());
+        for &edge in graph.edges() {
+            let (u, v) = graph.endpoints(edge);
+            for (upgrade_u, upgrade_v) in [(false, false), (true, false), (false, true), (true, true)] {
+                upgraded[u] = upgrade_u;
+                upgraded[v] = upgrade_v;
+                candidates.push(Self::effective_weight(graph, edge, &upgraded));
+            }
+            upgraded[u] = false;
+            upgraded[v] = false;
+        }
+        candidates.sort_by(|a, b| b.total_cmp(a));
+        candidates.dedup();
+        candidates
+    }
+
+    /// Cheapest set of upgraded nodes for which the links of capacity `threshold` or more span the graph, `None` if
+    /// not even upgrading every node does. Free upgrades are always taken, paid ones only matter for nodes with an
+    /// incident link below the threshold.
+    fn cheapest_upgrades(graph: &MutableGraph, threshold: f64) -> Option<UpgradeSet> {
+        let nodes = graph.nodes();
+        let mut upgraded: Vec<bool> = nodes.iter().map(|node| node.is_upgradable() && node.get_upgrade_cost() <= 0.0).collect();
+        let mut relevant = vec![false; nodes.len()];
+        for &edge in graph.edges() {
+            if Self::effective_weight(graph, edge, &upgraded) < threshold {
+                let (u, v) = graph.endpoints(edge);
+                relevant[u] = true;
+                relevant[v] = true;
+            }
+        }
+        let mut relevant: Vec<usize> = (0..nodes.len())
+            .filter(|&node| relevant[node] && !upgraded[node] && nodes[node].is_upgradable())
+            .collect();
+        relevant.sort_by(|&a, &b| nodes[b].get_upgrade_cost().total_cmp(&nodes[a].get_upgrade_cost()));
+        relevant.iter().for_each(|&node| upgraded[node] = true);
+        if !Self::spans(graph, threshold, &upgraded) {
+            return None;
+        }
+        if relevant.len() > EXACT_LIMIT {
+            trace!("{} relevant nodes, reverse deletion", relevant.len());
+            for &node in &relevant {
+                upgraded[node] = false;
+                if !Self::spans(graph, threshold, &upgraded) {
+                    upgraded[node] = true;
+                }
+            }
+            let cost = relevant.iter().filter(|&&node| upgraded[node]).map(|&node| nodes[node].get_upgrade_cost()).sum();
+            return Some((upgraded, cost, false));
+        }
+        let total: f64 = relevant.iter().map(|&node| nodes[node].get_upgrade_cost()).sum();
+        let mut best = (upgraded.clone(), total);
+        Self::branch(graph, threshold, &relevant, 0, 0.0, &mut upgraded, &mut best);
+        Some((best.0, best.1, true))
+    }
+
+    /// Decides the upgrade of `relevant[index..]`, undecided nodes count as upgraded. Skipping an upgrade is tried
+    /// first; a branch is cut when it cannot beat the best set or cannot span the graph anymore.
+    fn branch(graph: &MutableGraph, threshold: f64, relevant: &[usize], index: usize, cost: f64, upgraded: &mut Vec<bool>, best: &mut (Vec<bool>, f64)) {
+        if cost >= best.1 || !Self::spans(graph, threshold, upgraded) {
+            return;
+        }
+        if index == relevant.len() {
+            *best = (upgraded.clone(), cost);
+            return;
+        }
+        let node = relevant[index];
+        upgraded[node] = false;
+        Self::branch(graph, threshold, relevant, index + 1, cost, upgraded, best);
+        upgraded[node] = true;
+        Self::branch(graph, threshold, relevant, index + 1, cost + graph.nodes()[node].get_upgrade_cost(), upgraded, best);
+    }
+
+    /// Whether the links of capacity `threshold` or more span the graph and include every required link.
+    fn spans(graph: &MutableGraph, threshold: f64, upgraded: &[bool]) -> bool {
+        let mut uf = UF::new(graph.nodes().len() as i32);
+        for &edge in graph.edges() {
+            let weight = Self::effective_weight(graph, edge, upgraded);
+            if graph.is_required(edge) && weight < threshold {
+                return false;
+            }
+            if weight >= threshold {
+                let (u, v) = graph.endpoints(edge);
+                uf.union(u, v);
+            }
+        }
+        uf.count() == 1
+    }
+
+    /// Maximum bottleneck spanning tree holding the required links for the upgraded capacities and its bottleneck.
+    fn widest_tree(graph: &MutableGraph, upgraded: &[bool]) -> (MutableGraph, f64) {
+        let edges = graph.edges();
+        let weights: Vec<f64> = edges.iter().map(|&edge| Self::effective_weight(graph, edge, upgraded)).collect();
+        let mut order: Vec<usize> = (0..edges.len()).collect();
+        order.sort_by(|&a, &b| graph.is_required(edges[b]).cmp(&graph.is_required(edges[a])).then(weights[b].total_cmp(&weights[a])));
+        let mut uf = UF::new(graph.nodes().len() as i32);
+        let mut bottleneck = f64::INFINITY;
+        let mut tree = Vec::new();
+        for index in order {
+            let (u, v) = graph.endpoints(edges[index]);
+            if !uf.connected(u, v) {
+                uf.union(u, v);
+                tree.push(edges[index]);
+                bottleneck = bottleneck.min(weights[index]);
+            }
+        }
+        (graph.subgraph(tree), bottleneck)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+    use crate::algorithms::constrained_bottleneck_spanning_tree::node_upgrade::NodeUpgrade;
+    use crate::algorithms::constrained_bottleneck_spanning_tree::test_graphs::unprocessed;
+    use crate::datastructures::graph::edge::Edge;
+    use crate::datastructures::graph::mutable_graph::MutableGraph;
+    use crate::datastructures::graph::node::Node;
+    use crate::error::CbstuError;
+    use crate::io::generator::Generator;
+
+    fn check(graph: &MutableGraph, expected: &[(f64, f64, f64)]) {
+        for &(budget, capacity, cost) in expected {
+            let solution = NodeUpgrade::run(graph, budget).unwrap();
+            assert_eq!((solution.capacity(), solution.cost()), (capacity, cost), "budget {}", budget);
+            assert!(solution.tree().is_spanning_tree());
+            assert!(solution.is_optimal());
+        }
+    }
+
+    #[test]
+    fn test_node_capacity() {
+        let nodes = vec![
+            Node::default(0),
+            Node::default(1).capacity(4.0, 10.0).upgrade_cost(5.0),
+            Node::default(2).capacity(3.0, 10.0).upgrade_cost(2.0),
+            Node::default(3),
+        ];
+        // links keep their capacity when upgraded, only node upgrades count
+        let graph = unprocessed(nodes, &[(0, 1, 8.0, 8.0, 0.0), (1, 2, 8.0, 8.0, 0.0), (2, 3, 8.0, 8.0, 0.0), (3, 0, 8.0, 8.0, 0.0), (0, 2, 8.0, 8.0, 0.0)], |_, edge| edge);
+        check(&graph, &[(0.0, 3.0, 0.0), (2.0, 4.0, 2.0), (5.0, 4.0, 2.0), (7.0, 8.0, 7.0)]);
+        let solution = NodeUpgrade::run(&graph, 7.0).unwrap();
+        assert_eq!(solution.upgraded_nodes(), &vec![1, 2]);
+    }
+
+    #[test]
+    fn test_node_multiplier() {
+        let nodes = vec![
+            Node::default(0),
+            Node::default(1).multiplier(2.0).upgrade_cost(1.0),
+            Node::default(2),
+            Node::default(3).multiplier(3.0).upgrade_cost(4.0),
+        ];
+        let graph = unprocessed(nodes, &[(0, 1, 2.0, 2.0, 0.0), (1, 2, 2.0, 2.0, 0.0), (2, 3, 2.0, 2.0, 0.0), (0, 3, 3.0, 3.0, 0.0), (1, 3, 1.0, 1.0, 0.0)], |_, edge| edge);
+        check(&graph, &[(0.0, 2.0, 0.0), (1.0, 3.0, 1.0), (4.0, 3.0, 1.0), (5.0, 6.0, 5.0)]);
+    }
+
+    #[test]
+    fn test_link_constraints() {
+        let nodes = vec![
+            Node::default(0),
+            Node::default(1).capacity(4.0, 10.0).upgrade_cost(5.0),
+            Node::default(2).capacity(3.0, 10.0).upgrade_cost(2.0),
+            Node::default(3),
+        ];
+        let edges = vec![
+            Edge::new(0, 1).weight(8.0),
+            Edge::new(1, 2).weight(8.0),
+            Edge::new(2, 3).weight(8.0).forbidden(true),
+            Edge::new(3, 0).weight(2.0).required(true),
+            Edge::new(0, 2).weight(8.0),
+        ];
+        let graph = MutableGraph::new(Arc::new(nodes.clone()), edges);
+        check(&graph, &[(0.0, 2.0, 0.0), (7.0, 2.0, 0.0)]);
+        let solution = NodeUpgrade::run(&graph, 7.0).unwrap();
+        assert!(solution.tree().edges().iter().any(|&edge| solution.tree().endpoints(edge) == (3, 0)));
+        let cut = MutableGraph::new(Arc::new(nodes), vec![Edge::new(0, 1).weight(8.0), Edge::new(1, 2).weight(8.0).forbidden(true), Edge::new(2, 3).weight(8.0)]);
+        assert!(matches!(NodeUpgrade::run(&cut, 7.0), Err(CbstuError::ConstraintConflict { .. })));
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        for seed in 0..20 {
+            let base = Generator::random_instance(6, 9, seed).unwrap();
+            let mut rng = StdRng::seed_from_u64(seed);
+            let nodes: Vec<Node> = (0..6).map(|id| {
+                let capacity = rng.gen_range(5..50) as f64;
+                Node::default(id)
+                    .capacity(capacity, capacity + rng.gen_range(0..30) as f64)
+                    .multiplier(rng.gen_range(1..4) as f64)
+                    .upgrade_cost(rng.gen_range(0..10) as f64)
+            }).collect();
+            let edges = base.edges().iter().map(|&edge| base.edge(edge)).collect();
+            let graph = MutableGraph::new(Arc::new(nodes), edges);
+            for budget in [0.0, 5.0, 12.0, 30.0] {
+                let mut best = f64::NEG_INFINITY;
+                for mask in 0u32..(1 << 6) {
+                    let upgraded: Vec<bool> = (0..6).map(|node| mask & (1 << node) != 0).collect();
+                    let cost: f64 = (0..6).filter(|&node| upgraded[node]).map(|node| graph.nodes()[node].get_upgrade_cost()).sum();
+                    if cost <= budget {
+                        best = best.max(NodeUpgrade::widest_tree(&graph, &upgraded).1);
+                    }
+                }
+                let solution = NodeUpgrade::run(&graph, budget).unwrap();
+                assert_eq!(solution.capacity(), best, "seed {} budget {}", seed, budget);
+                assert!(solution.cost() <= budget);
+            }
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use log::{debug, trace};
+use crate::algorithms::constrained_bottleneck_spanning_tree::constraints::Constraints;
+use crate::algorithms::min_sum_spanning_tree::kruskal::{CalculationType, ConnectionType};
+use crate::algorithms::util::Util;
+use crate::datastructures::graph::mutable_graph::MutableGraph;
+use crate::error::CbstuError;
+
+/// Breakpoint of the budget–bottleneck trade-off: `cost` is the minimum upgrade cost of a spanning tree with
+/// bottleneck `bottleneck` (inverted weight), `tree` is such a tree.
+#[derive(Debug, Clone)]
+pub struct ParetoPoint {
+    tree: MutableGraph,
+    cost: f64,
+    bottleneck: f64,
+}
+
+impl ParetoPoint {
+    pub fn tree(&self) -> &MutableGraph {
+        &self.tree
+    }
+
+    pub fn cost(&self) -> f64 {
+        self.cost
+    }
+
+    pub fn bottleneck(&self) -> f64 {
+        self.bottleneck
+    }
+}
+
+pub struct ParetoFrontier();
+
+impl ParetoFrontier {
+    /// Returns the non-dominated (bottleneck, cost) points of the preprocessed graph, best bottleneck first. Costs are
+    /// strictly decreasing along the frontier, the last point is the min-cost spanning tree. The thresholds are swept
+    /// from best to worst: like in the edge elimination search, the min-cost forest of a threshold only needs the
+    /// forest of the previous threshold and the edges of the new weight class. Under link constraints the sweep runs
+    /// on the reduced graph of [`Constraints::reduce`], thresholds that exclude a required link give no point.
+    pub fn run(graph: &MutableGraph) -> Result<Vec<ParetoPoint>, CbstuError> {
+        trace!("Computing budget-bottleneck Pareto frontier");
+        let reduced = Constraints::reduce(graph, None)?;
+        let original = graph;
+        let graph = reduced.graph();
+        let unique_weights = Util::sorted_unique_weights(graph)?;
+        let mut sorted_edges = graph.edges_copy();
+        sorted_edges.sort_by(|&a, &b| graph.weight(a).total_cmp(&graph.weight(b)));
+        let mut next = 0;
+        let mut forest = Vec::new();
+        let mut frontier: Vec<ParetoPoint> = Vec::new();
+        for &pivot_weight in &unique_weights {
+            let start = next;
+            while next < sorted_edges.len() && graph.weight(sorted_edges[next]) <= pivot_weight {
+                next += 1;
+            }
+            let working_graph = graph.subgraph(Util::union_edges(&forest, &sorted_edges[start..next]));
+            let (connection_type, st, _, bottleneck) = working_graph.mst_disconnected(CalculationType::Cost)?;
+            forest = st.edges_copy();
+            if let ConnectionType::Disconnected = connection_type {
+                trace!("Threshold {} does not span the graph", pivot_weight);
+                continue;
+            }
+            if !reduced.satisfies(&st) {
+                trace!("Threshold {} excludes a required link", pivot_weight);
+                continue;
+            }
+            let (tree, cost) = reduced.restore(original, &st);
+            if frontier.last().map_or(true, |point| cost < point.cost) {
+                debug!("Pareto point [bottleneck: {}, cost: {}]", bottleneck, cost);
+                frontier.push(ParetoPoint { tree, cost, bottleneck });
+            }
+        }
+        if frontier.is_empty() {
+            return Err(CbstuError::DisconnectedGraph);
+        }
+        Ok(frontier)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithms::constrained_bottleneck_spanning_tree::pareto::ParetoFrontier;
+    use crate::algorithms::constrained_bottleneck_spanning_tree::solver::{solver_by_name, SolutionStatus};
+    use crate::algorithms::constrained_bottleneck_spanning_tree::test_graphs::{graph, preprocess, square};
+    use crate::error::CbstuError;
+    use crate::io::generator::Generator;
+
+    #[test]
+    fn test_square_frontier() {
+        let graph = square();
+        let frontier = ParetoFrontier::run(&graph).unwrap();
+        let points: Vec<(f64, f64)> = frontier.iter().map(|point| (point.bottleneck(), point.cost())).collect();
+        assert_eq!(points, vec![(-10.0, 15.0), (-9.0, 11.0), (-8.0, 9.0), (-6.0, 5.0), (-5.0, 2.0), (-3.0, 0.0)]);
+        for point in &frontier {
+            assert!(point.tree().is_spanning_tree());
+            assert_eq!(point.tree().calculate_total_cost(), point.cost());
+        }
+    }
+
+    #[test]
+    fn test_frontier_matches_solver() {
+        let graph = preprocess(Generator::random_instance(15, 30, 3).unwrap());
+        let frontier = ParetoFrontier::run(&graph).unwrap();
+        let solver = solver_by_name("fast_edge_elimination").unwrap();
+        for (i, point) in frontier.iter().enumerate() {
+            assert_eq!(solver.solve(&graph, point.cost()).unwrap().bottleneck(), point.bottleneck());
+            let below = solver.solve(&graph, point.cost() - 0.5).unwrap();
+            match frontier.get(i + 1) {
+                Some(next) => assert_eq!(below.bottleneck(), next.bottleneck()),
+                None => assert_eq!(below.status(), SolutionStatus::Infeasible),
+            }
+        }
+    }
+
+    #[test]
+    fn test_disconnected() {
+        let graph = graph(3, &[(0, 1, 1.0, 2.0, 1.0)]);
+        assert!(matches!(ParetoFrontier::run(&graph), Err(CbstuError::DisconnectedGraph)));
+    }
+}
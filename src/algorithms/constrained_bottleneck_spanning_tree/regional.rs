@@ -0,0 +1,162 @@
+use log::trace;
+use crate::algorithms::constrained_bottleneck_spanning_tree::multi_resource::MultiResource;
+use crate::algorithms::util::BoundedSolution;
+use crate::datastructures::graph::edge::Edge;
+use crate::datastructures::graph::mutable_graph::MutableGraph;
+use crate::error::CbstuError;
+
+pub const CHARGE_RULES: [&str; 4] = ["source", "target", "split", "both"];
+
+/// Which region pays for an upgraded link whose endpoints lie in different regions. Links inside one region are always
+/// charged once to that region.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChargeRule {
+    /// The region of the source node pays.
+    Source,
+    /// The region of the target node pays.
+    Target,
+    /// Both regions pay half.
+    Split,
+    /// Both regions pay the full cost.
+    Both,
+}
+
+impl ChargeRule {
+    /// Returns the rule registered under the given name.
+    pub fn by_name(name: &str) -> Option<ChargeRule> {
+        match name {
+            "source" => Some(ChargeRule::Source),
+            "target" => Some(ChargeRule::Target),
+            "split" => Some(ChargeRule::Split),
+            "both" => Some(ChargeRule::Both),
+            _ => None,
+        }
+    }
+}
+
+/// CBSTU with a budget per region of nodes: the cost of an upgrade is charged to the regions of its endpoints
+/// following the `rule`, and every region has to stay within its own budget.
+pub struct RegionalBudgets {
+    budgets: Vec<(String, f64)>,
+    rule: ChargeRule,
+}
+
+impl RegionalBudgets {
+    pub fn new(budgets: Vec<(String, f64)>, rule: ChargeRule) -> RegionalBudgets {
+        RegionalBudgets { budgets, rule }
+    }
+
+    /// Solves the preprocessed graph as a multi-resource problem with one resource per region, see
+    /// [`MultiResource::run`]. Spending in the solution follows the order of the budgets.
+    pub fn solve(&self, graph: &MutableGraph) -> Result<BoundedSolution, CbstuError> {
+        trace!("Solving constrained bottleneck spanning tree problem with {} regional budgets", self.budgets.len());
+        let budgets: Vec<f64> = self.budgets.iter().map(|&(_, budget)| budget).collect();
+        MultiResource::run(&self.charged_graph(graph)?, &budgets)
+    }
+
+    /// Copy of the graph whose cost vectors hold the charge of each region.
+    pub fn charged_graph(&self, graph: &MutableGraph) -> Result<MutableGraph, CbstuError> {
+        let edges = graph.edges().iter().map(|&edge| {
+            let cost = graph.cost(edge);
+            let mut charges = vec![0.0; self.budgets.len()];
+            if cost != 0.0 {
+                let (source, target) = graph.endpoints(edge);
+                let (source, target) = (self.region(graph, source)?, self.region(graph, target)?);
+                match self.rule {
+                    _ if source == target => charges[source] += cost,
+                    ChargeRule::Source => charges[source] += cost,
+                    ChargeRule::Target => charges[target] += cost,
+                    ChargeRule::Split => {
+                        charges[source] += cost / 2.0;
+                        charges[target] += cost / 2.0;
+                    }
+                    ChargeRule::Both => {
+                        charges[source] += cost;
+                        charges[target] += cost;
+                    }
+                }
+            }
+            Ok(graph.edge(edge).costs(charges))
+        }).collect::<Result<Vec<Edge>, CbstuError>>()?;
+        Ok(MutableGraph::new(graph.nodes_copy(), edges))
+    }
+
+    /// Index of the budget of the region of `node`.
+    fn region(&self, graph: &MutableGraph, node: usize) -> Result<usize, CbstuError> {
+        let region = graph.nodes()[node].get_region()
+            .ok_or_else(|| CbstuError::MalformedInput(format!("node {} has no region", node)))?;
+        self.budgets.iter().position(|(name, _)| name == region)
+            .ok_or_else(|| CbstuError::MalformedInput(format!("no budget for region '{}'", region)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithms::constrained_bottleneck_spanning_tree::regional::{ChargeRule, RegionalBudgets};
+    use crate::algorithms::constrained_bottleneck_spanning_tree::test_graphs::{brute_force, build, Link, SQUARE};
+    use crate::datastructures::graph::mutable_graph::MutableGraph;
+    use crate::datastructures::graph::node::Node;
+    use crate::error::CbstuError;
+
+    /// Square with nodes 0 and 1 in the north and nodes 2 and 3 in the south.
+    fn square_graph() -> MutableGraph {
+        let nodes = (0..4).map(|id| Node::default(id).region(if id < 2 { "north" } else { "south" })).collect();
+        build(nodes, &SQUARE, |_, edge| edge)
+    }
+
+    fn budgets(north: f64, south: f64) -> Vec<(String, f64)> {
+        vec![(String::from("north"), north), (String::from("south"), south)]
+    }
+
+    #[test]
+    fn test_regional_budgets() {
+        let graph = square_graph();
+        // (north, south, rule, bottleneck): -6 upgrades (1,2) from north to south and (3,0) from south to north
+        for (north, south, rule, bottleneck) in [
+            (3.0, 2.0, ChargeRule::Source, -6.0),
+            (5.0, 0.0, ChargeRule::Source, -5.0),
+            (5.0, 0.0, ChargeRule::Target, -5.0),
+            (2.0, 3.0, ChargeRule::Target, -6.0),
+            (2.5, 2.5, ChargeRule::Split, -6.0),
+            (2.5, 2.4, ChargeRule::Split, -5.0),
+            (5.0, 4.0, ChargeRule::Both, -5.0),
+            (5.0, 5.0, ChargeRule::Both, -6.0),
+            (100.0, 0.0, ChargeRule::Split, -3.0),
+        ] {
+            let solution = RegionalBudgets::new(budgets(north, south), rule).solve(&graph).unwrap();
+            assert_eq!(solution.bottleneck(), bottleneck, "{} and {} with {:?}", north, south, rule);
+            assert!(solution.is_optimal());
+            assert!(solution.costs()[0] <= north && solution.costs()[1] <= south);
+        }
+        let missing = vec![(String::from("north"), 5.0)];
+        assert!(matches!(RegionalBudgets::new(missing, ChargeRule::Split).solve(&graph), Err(CbstuError::MalformedInput(_))));
+    }
+
+    #[test]
+    fn test_bound_when_undecided() {
+        // a core on nodes 0 to 4 under 20 pendant links that are free at full capacity: every threshold has too many
+        // usable edges for branch and bound and some are left undecided, while the optimum is decided in the core
+        let mut links: Vec<Link> = vec![
+            (0, 1, 5.0, 16.0, 5.0),
+            (0, 2, 3.0, 14.0, 0.0),
+            (2, 3, 7.0, 12.0, 7.0),
+            (1, 4, 8.0, 17.0, 4.0),
+            (1, 4, 7.0, 19.0, 5.0),
+            (3, 4, 2.0, 15.0, 7.0),
+            (0, 3, 4.0, 17.0, 3.0),
+            (0, 1, 7.0, 10.0, 5.0),
+        ];
+        links.extend((0..20).map(|p| (p % 5, 5 + p, 100.0, 101.0, 1.0)));
+        let nodes = (0..25).map(|id| Node::default(id).region(if id % 5 < 3 { "north" } else { "south" })).collect();
+        let graph = build(nodes, &links, |_, edge| edge);
+        let regional = RegionalBudgets::new(budgets(8.0, 9.0), ChargeRule::Split);
+        let charged = regional.charged_graph(&graph).unwrap();
+        let optimum = brute_force(&charged, 5, |tree| {
+            [8.0, 9.0].iter().enumerate().all(|(r, &budget)| tree.iter().map(|&edge| charged.resource_cost(edge, r)).sum::<f64>() <= budget)
+        });
+        assert_eq!(optimum, -14.0);
+        let solution = regional.solve(&graph).unwrap();
+        assert!(!solution.is_optimal());
+        assert!(solution.bound() <= optimum && optimum <= solution.bottleneck());
+    }
+}
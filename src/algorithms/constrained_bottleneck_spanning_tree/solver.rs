@@ -0,0 +1,249 @@
+use crate::algorithms::constrained_bottleneck_spanning_tree::berman::Berman;
+use crate::algorithms::constrained_bottleneck_spanning_tree::edge_elimination::EdgeEliminationOld;
+use crate::algorithms::constrained_bottleneck_spanning_tree::fast_edge_elimination::FastEdgeElimination;
+use crate::algorithms::constrained_bottleneck_spanning_tree::punnen::Punnen;
+use crate::datastructures::graph::edge::Edge;
+use crate::datastructures::graph::mutable_graph::MutableGraph;
+use crate::error::CbstuError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolutionStatus {
+    Feasible,
+    Infeasible,
+}
+
+/// Result of a CBSTU algorithm. The bottleneck is expressed in the (inverted) weights of the solved graph.
+pub struct Solution {
+    tree: Option<MutableGraph>,
+    cost: f64,
+    bottleneck: f64,
+    upgraded_edges: Vec<Edge>,
+    built_edges: Vec<Edge>,
+    status: SolutionStatus,
+}
+
+impl Solution {
+    pub fn feasible(tree: MutableGraph, cost: f64, bottleneck: f64) -> Solution {
+        let (built_edges, upgraded_edges) = tree.edges().iter()
+            .filter(|&&edge| tree.is_upgraded(edge))
+            .map(|&edge| tree.edge(edge))
+            .partition(|edge| edge.is_candidate());
+        Solution {
+            tree: Some(tree),
+            cost,
+            bottleneck,
+            upgraded_edges,
+            built_edges,
+            status: SolutionStatus::Feasible,
+        }
+    }
+
+    pub fn infeasible() -> Solution {
+        Solution {
+            tree: None,
+            cost: 0.0,
+            bottleneck: 0.0,
+            upgraded_edges: Vec::new(),
+            built_edges: Vec::new(),
+            status: SolutionStatus::Infeasible,
+        }
+    }
+
+    /// Converts the `(spanning tree, cost, bottleneck)` result of the algorithms. An exceeded budget is not an error
+    /// for the caller of a solver but an infeasible solution, all other errors are passed on.
+    pub fn from_result(result: Result<(MutableGraph, f64, f64), CbstuError>) -> Result<Solution, CbstuError> {
+        match result {
+            Ok((tree, cost, bottleneck)) => Ok(Solution::feasible(tree, cost, bottleneck)),
+            Err(CbstuError::InfeasibleBudget) => Ok(Solution::infeasible()),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn tree(&self) -> Option<&MutableGraph> {
+        self.tree.as_ref()
+    }
+
+    pub fn into_tree(self) -> Option<MutableGraph> {
+        self.tree
+    }
+
+    pub fn cost(&self) -> f64 {
+        self.cost
+    }
+
+    pub fn bottleneck(&self) -> f64 {
+        self.bottleneck
+    }
+
+    /// Upgraded links of the base network in the tree.
+    pub fn upgraded_edges(&self) -> &Vec<Edge> {
+        &self.upgraded_edges
+    }
+
+    /// Candidate links built for the tree.
+    pub fn built_edges(&self) -> &Vec<Edge> {
+        &self.built_edges
+    }
+
+    pub fn status(&self) -> SolutionStatus {
+        self.status
+    }
+
+    pub fn is_feasible(&self) -> bool {
+        self.status == SolutionStatus::Feasible
+    }
+}
+
+/// Common interface of the CBSTU algorithms. The graph is expected to be preprocessed: weights inverted and
+/// edges duplicated with [`Util::duplicate_edges`](crate::algorithms::util::Util::duplicate_edges).
+/// Solvers leave the given graph untouched, so one instance can be solved for many budgets.
+pub trait CbstuSolver: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn solve(&self, graph: &MutableGraph, budget: f64) -> Result<Solution, CbstuError>;
+}
+
+pub const SOLVER_NAMES: [&str; 4] = ["berman", "punnen", "edge_elimination_old", "fast_edge_elimination"];
+
+/// Returns the solver registered under the given name.
+pub fn solver_by_name(name: &str) -> Option<Box<dyn CbstuSolver>> {
+    match name {
+        "berman" => Some(Box::new(Berman())),
+        "punnen" => Some(Box::new(Punnen())),
+        "edge_elimination_old" => Some(Box::new(EdgeEliminationOld())),
+        "fast_edge_elimination" => Some(Box::new(FastEdgeElimination())),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::thread;
+    use crate::algorithms::constrained_bottleneck_spanning_tree::solver::{solver_by_name, SolutionStatus, SOLVER_NAMES};
+    use crate::algorithms::constrained_bottleneck_spanning_tree::verify::Verifier;
+    use crate::algorithms::constrained_bottleneck_spanning_tree::test_graphs::{build, nodes, preprocess, square};
+    use crate::datastructures::graph::edge::Edge;
+    use crate::datastructures::graph::mutable_graph::MutableGraph;
+
+    #[test]
+    fn test_solvers_agree() {
+        for budget in [0.0, 3.0, 5.0, 100.0] {
+            let bottlenecks: Vec<f64> = SOLVER_NAMES.iter().map(|name| {
+                let solver = solver_by_name(name).unwrap();
+                let solution = solver.solve(&square(), budget).unwrap();
+                assert_eq!(solution.status(), SolutionStatus::Feasible);
+                assert!(solution.tree().unwrap().is_spanning_tree());
+                assert!(solution.cost() <= budget);
+                solution.bottleneck()
+            }).collect();
+            assert!(bottlenecks.iter().all(|b| *b == bottlenecks[0]), "{:?}", bottlenecks);
+        }
+    }
+
+    #[test]
+    fn test_graph_untouched() {
+        let graph = square();
+        let snapshot = |graph: &MutableGraph| -> Vec<Edge> {
+            graph.edges().iter().map(|&edge| graph.edge(edge)).collect()
+        };
+        let before = snapshot(&graph);
+        for budget in [0.0, 3.0, 5.0, 100.0] {
+            let expected = solver_by_name("berman").unwrap().solve(&square(), budget).unwrap().bottleneck();
+            for name in SOLVER_NAMES {
+                let solution = solver_by_name(name).unwrap().solve(&graph, budget).unwrap();
+                assert_eq!(solution.bottleneck(), expected);
+            }
+        }
+        let after = snapshot(&graph);
+        assert_eq!(before, after);
+        before.iter().zip(after.iter()).for_each(|(a, b)| assert_eq!(a.original_endpoints(), b.original_endpoints()));
+    }
+
+    #[test]
+    fn test_upgraded_edges() {
+        let graph = square();
+        let solution = solver_by_name("berman").unwrap().solve(&graph, 5.0).unwrap();
+        assert_eq!(solution.bottleneck(), -6.0);
+        assert_eq!(solution.cost(), 5.0);
+        assert_eq!(solution.upgraded_edges().len(), 2);
+        assert!(solution.upgraded_edges().iter().all(|edge| edge.is_upgraded()));
+        assert!(solver_by_name("unknown").is_none());
+    }
+
+    #[test]
+    fn test_best_weight_feasible() {
+        for name in SOLVER_NAMES {
+            let triangle = build(nodes(3), &[(0, 1, 1.0, 10.0, 1.0), (1, 2, 1.0, 10.0, 1.0), (2, 0, 1.0, 10.0, 1.0)], |_, edge| edge);
+            let solution = solver_by_name(name).unwrap().solve(&triangle, 10.0).unwrap();
+            assert_eq!(solution.bottleneck(), -10.0);
+            assert_eq!(solution.cost(), 2.0);
+        }
+    }
+
+    #[test]
+    fn test_infeasible_budget() {
+        for name in SOLVER_NAMES {
+            let solution = solver_by_name(name).unwrap().solve(&square(), -1.0).unwrap();
+            assert_eq!(solution.status(), SolutionStatus::Infeasible);
+            assert!(solution.tree().is_none());
+        }
+    }
+
+    #[test]
+    fn test_upgrade_tiers() {
+        let graph = build(nodes(3), &[(0, 1, 2.0, 5.0, 1.0), (1, 2, 3.0, 6.0, 2.0), (0, 2, 1.0, 4.0, 3.0)], |i, edge| match i {
+            0 => edge.tier(9.0, 4.0),
+            1 => edge.tier(10.0, 5.0),
+            _ => edge,
+        });
+        assert_eq!(graph.edges().len(), 8);
+        for (budget, bottleneck) in [(0.0, -2.0), (3.0, -5.0), (6.0, -6.0), (9.0, -9.0), (100.0, -9.0)] {
+            for name in SOLVER_NAMES {
+                let solution = solver_by_name(name).unwrap().solve(&graph, budget).unwrap();
+                assert_eq!(solution.bottleneck(), bottleneck, "{} with budget {}", name, budget);
+                let mut links: Vec<(usize, usize)> = solution.tree().unwrap().edges().iter()
+                    .map(|&edge| graph.endpoints(edge))
+                    .collect();
+                links.sort();
+                links.dedup();
+                assert_eq!(links.len(), 2);
+                assert!(Verifier::verify(&graph, budget, &solution).unwrap().is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn test_candidate_links() {
+        let edges = vec![
+            Edge::new(0, 1).weight(5.0).upgraded_weight(9.0).cost(6.0),
+            Edge::new(1, 2).weight(1.0).upgraded_weight(3.0).cost(10.0),
+            Edge::new(0, 2).upgraded_weight(8.0).cost(4.0).candidate(true),
+        ];
+        let graph = preprocess(MutableGraph::new(Arc::new(nodes(3)), edges));
+        assert_eq!(graph.edges().len(), 5);
+        for (budget, bottleneck, builds, upgrades) in [(3.0, -1.0, 0, 0), (4.0, -5.0, 1, 0), (10.0, -8.0, 1, 1)] {
+            for name in SOLVER_NAMES {
+                let solution = solver_by_name(name).unwrap().solve(&graph, budget).unwrap();
+                assert_eq!(solution.bottleneck(), bottleneck, "{} with budget {}", name, budget);
+                assert_eq!((solution.built_edges().len(), solution.upgraded_edges().len()), (builds, upgrades));
+                assert!(Verifier::verify(&graph, budget, &solution).unwrap().is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn test_solve_in_parallel() {
+        let graph = square();
+        let expected: Vec<f64> = [0.0, 3.0, 5.0, 100.0].iter()
+            .map(|&budget| solver_by_name("berman").unwrap().solve(&graph, budget).unwrap().bottleneck())
+            .collect();
+        thread::scope(|scope| {
+            let handles: Vec<_> = [0.0, 3.0, 5.0, 100.0].iter().map(|&budget| {
+                let graph = &graph;
+                scope.spawn(move || solver_by_name("fast_edge_elimination").unwrap().solve(graph, budget).unwrap().bottleneck())
+            }).collect();
+            let bottlenecks: Vec<f64> = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+            assert_eq!(bottlenecks, expected);
+        });
+    }
+}
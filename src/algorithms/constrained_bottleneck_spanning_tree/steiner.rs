@@ -0,0 +1,333 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use log::trace;
+use crate::algorithms::util::{BoundedSolution, ThresholdCheck, Util};
+use crate::datastructures::graph::edge_store::EdgeId;
+use crate::datastructures::graph::mutable_graph::MutableGraph;
+use crate::datastructures::uf::union_find::UF;
+use crate::error::CbstuError;
+
+/// Above this number of terminals a threshold is checked with the metric closure heuristic.
+const EXACT_LIMIT: usize = 10;
+
+/// How the dynamic program reached a terminal set and node.
+#[derive(Clone, Copy)]
+enum Step {
+    Start,
+    Split(usize),
+    Edge(usize, EdgeId),
+}
+
+/// CBSTU where the tree only has to connect the `terminals`, other nodes are used when they help.
+pub struct SteinerTree {
+    terminals: Vec<usize>,
+}
+
+impl SteinerTree {
+    pub fn new(mut terminals: Vec<usize>) -> SteinerTree {
+        terminals.sort();
+        terminals.dedup();
+        SteinerTree { terminals }
+    }
+
+    /// Bisection over the thresholds of the preprocessed graph. A threshold is feasible if the min-cost Steiner tree of
+    /// the edges within it fits the budget. With at most `EXACT_LIMIT` terminals that tree is found by dynamic
+    /// programming over the terminal subsets. Otherwise the tree of Kou, Markowsky and Berman, a min-cost spanning tree
+    /// of the shortest paths between the terminals, stands in; it costs at most `2 (1 - 1/t)` times the optimum for `t`
+    /// terminals, so a threshold it cannot fit in the budget stays undecided unless that factor rules it out.
+    pub fn solve(&self, graph: &MutableGraph, budget: f64) -> Result<BoundedSolution, CbstuError> {
+        trace!("Solving constrained bottleneck Steiner tree problem with {} terminals", self.terminals.len());
+        if self.terminals.len() < 2 {
+            return Err(CbstuError::MalformedInput(String::from("a Steiner tree needs at least two terminals")));
+        }
+        if let Some(&terminal) = self.terminals.iter().find(|&&terminal| terminal >= graph.nodes().len()) {
+            return Err(CbstuError::MalformedInput(format!("terminal {} is not a node of the graph", terminal)));
+        }
+        if let Some(&edge) = graph.edges().iter().find(|&&edge| graph.cost(edge) < 0.0) {
+            return Err(CbstuError::MalformedInput(format!("edge {:?} has a negative cost", graph.endpoints(edge))));
+        }
+        if let Some(&edge) = graph.edges().iter().find(|&&edge| graph.is_required(edge)) {
+            return Err(CbstuError::MalformedInput(format!("required link {:?} is not supported by the Steiner variant", graph.endpoints(edge))));
+        }
+        let graph = graph.filter(|edge| !graph.is_forbidden(edge));
+        let unique_weights = Util::sorted_unique_weights(&graph)?;
+        let (found, bound) = Util::bisect_thresholds(&unique_weights, |threshold| self.check(&graph.smaller_or_eq_than(threshold), budget));
+        let tree = match found {
+            Some(tree) => graph.subgraph(tree),
+            None if self.shortest_paths(&graph, self.terminals[0]).0.iter().any(|distance| distance.is_infinite()) => {
+                return Err(CbstuError::DisconnectedGraph);
+            }
+            None => return Err(CbstuError::InfeasibleBudget),
+        };
+        let cost = tree.calculate_total_cost();
+        Ok(BoundedSolution::new(tree, vec![cost], bound))
+    }
+
+    fn check(&self, graph: &MutableGraph, budget: f64) -> ThresholdCheck<Vec<EdgeId>> {
+        if self.terminals.len() <= EXACT_LIMIT {
+            return match self.exact(graph) {
+                Some(tree) if Self::cost(graph, &tree) <= budget => ThresholdCheck::Feasible(tree),
+                _ => ThresholdCheck::Infeasible,
+            };
+        }
+        let (tree, closure_cost) = match self.heuristic(graph) {
+            Some(result) => result,
+            None => return ThresholdCheck::Infeasible,
+        };
+        let t = self.terminals.len() as f64;
+        match Self::cost(graph, &tree) <= budget {
+            true => ThresholdCheck::Feasible(tree),
+            false if closure_cost / (2.0 * (1.0 - 1.0 / t)) > budget => ThresholdCheck::Infeasible,
+            false => ThresholdCheck::Unknown,
+        }
+    }
+
+    /// Min-cost Steiner tree after Dreyfus and Wagner: the cheapest tree joining a terminal set and a node either
+    /// splits into two trees at that node or reaches it along a shortest path, so every set is first combined from
+    /// its subsets and then spread over the graph by Dijkstra's algorithm. `None` if the terminals are not connected.
+    fn exact(&self, graph: &MutableGraph) -> Option<Vec<EdgeId>> {
+        let n = graph.nodes().len();
+        let full = (1usize << self.terminals.len()) - 1;
+        let adjacency = Self::adjacency(graph);
+        let mut cheapest = vec![vec![f64::INFINITY; n]; full + 1];
+        let mut steps = vec![vec![Step::Start; n]; full + 1];
+        for (i, &terminal) in self.terminals.iter().enumerate() {
+            cheapest[1 << i][terminal] = 0.0;
+        }
+        for set in 1..=full {
+            for v in 0..n {
+                let mut subset = (set - 1) & set;
+                while subset > 0 {
+                    let joined = cheapest[subset][v] + cheapest[set ^ subset][v];
+                    if joined < cheapest[set][v] {
+                        cheapest[set][v] = joined;
+                        steps[set][v] = Step::Split(subset);
+                    }
+                    subset = (subset - 1) & set;
+                }
+            }
+            for (v, predecessor) in Self::dijkstra(&adjacency, &mut cheapest[set]) {
+                steps[set][v] = Step::Edge(predecessor.0, predecessor.1);
+            }
+        }
+        let root = self.terminals[0];
+        if cheapest[full][root].is_infinite() {
+            return None;
+        }
+        let mut edges = Vec::new();
+        let mut stack = vec![(full, root)];
+        while let Some((set, v)) = stack.pop() {
+            match steps[set][v] {
+                Step::Start => {}
+                Step::Split(subset) => stack.extend([(subset, v), (set ^ subset, v)]),
+                Step::Edge(u, edge) => {
+                    edges.push(edge);
+                    stack.push((set, u));
+                }
+            }
+        }
+        Some(self.prune(graph, edges))
+    }
+
+    /// Tree of Kou, Markowsky and Berman with the cost of the min-cost spanning tree of the metric closure over the
+    /// terminals, `None` if the terminals are not connected.
+    fn heuristic(&self, graph: &MutableGraph) -> Option<(Vec<EdgeId>, f64)> {
+        let paths: Vec<(Vec<f64>, Vec<Option<(usize, EdgeId)>>)> = self.terminals.iter()
+            .map(|&terminal| self.shortest_paths(graph, terminal))
+            .collect();
+        // Prim's algorithm on the metric closure
+        let t = self.terminals.len();
+        let mut in_tree = vec![false; t];
+        let mut link: Vec<(f64, usize)> = (0..t).map(|j| (paths[0].0[self.terminals[j]], 0)).collect();
+        in_tree[0] = true;
+        let mut closure_cost = 0.0;
+        let mut edges = Vec::new();
+        for _ in 1..t {
+            let next = (0..t).filter(|&j| !in_tree[j]).min_by(|&a, &b| link[a].0.total_cmp(&link[b].0))?;
+            let (distance, from) = link[next];
+            if distance.is_infinite() {
+                return None;
+            }
+            closure_cost += distance;
+            in_tree[next] = true;
+            let mut v = self.terminals[next];
+            while let Some((u, edge)) = paths[from].1[v] {
+                edges.push(edge);
+                v = u;
+            }
+            for j in (0..t).filter(|&j| !in_tree[j]) {
+                if paths[next].0[self.terminals[j]] < link[j].0 {
+                    link[j] = (paths[next].0[self.terminals[j]], next);
+                }
+            }
+        }
+        Some((self.prune(graph, edges), closure_cost))
+    }
+
+    /// Distances from `source` and the last node and edge of each shortest path.
+    fn shortest_paths(&self, graph: &MutableGraph, source: usize) -> (Vec<f64>, Vec<Option<(usize, EdgeId)>>) {
+        let mut distances = vec![f64::INFINITY; graph.nodes().len()];
+        let mut predecessors = vec![None; graph.nodes().len()];
+        distances[source] = 0.0;
+        for (v, predecessor) in Self::dijkstra(&Self::adjacency(graph), &mut distances) {
+            predecessors[v] = Some(predecessor);
+        }
+        (distances, predecessors)
+    }
+
+    /// Cheapest edge to each neighbour of every node.
+    fn adjacency(graph: &MutableGraph) -> Vec<Vec<(usize, f64, EdgeId)>> {
+        let mut adjacency: Vec<Vec<(usize, f64, EdgeId)>> = vec![Vec::new(); graph.nodes().len()];
+        for &edge in graph.edges() {
+            let (u, v) = graph.endpoints(edge);
+            for (from, to) in [(u, v), (v, u)] {
+                match adjacency[from].iter_mut().find(|(neighbour, _, _)| *neighbour == to) {
+                    Some(entry) if graph.cost(edge) < entry.1 => *entry = (to, graph.cost(edge), edge),
+                    Some(_) => {}
+                    None => adjacency[from].push((to, graph.cost(edge), edge)),
+                }
+            }
+        }
+        adjacency
+    }
+
+    /// Dijkstra's algorithm from every node with a finite distance at once. Lowers `distances` to the shortest ones and
+    /// returns the nodes it lowered with the node and edge they were reached over, the last one per node being final.
+    fn dijkstra(adjacency: &[Vec<(usize, f64, EdgeId)>], distances: &mut [f64]) -> Vec<(usize, (usize, EdgeId))> {
+        // non-negative floats order like their bit patterns
+        let mut queue: BinaryHeap<Reverse<(u64, usize)>> = (0..distances.len())
+            .filter(|&v| distances[v].is_finite())
+            .map(|v| Reverse((distances[v].to_bits(), v)))
+            .collect();
+        let mut lowered = Vec::new();
+        while let Some(Reverse((distance, u))) = queue.pop() {
+            if distance != distances[u].to_bits() {
+                continue;
+            }
+            for &(v, cost, edge) in &adjacency[u] {
+                if distances[u] + cost < distances[v] {
+                    distances[v] = distances[u] + cost;
+                    lowered.push((v, (u, edge)));
+                    queue.push(Reverse((distances[v].to_bits(), v)));
+                }
+            }
+        }
+        lowered
+    }
+
+    /// Turns a set of edges connecting the terminals into a tree: a min-cost spanning forest of the edges, from which
+    /// leaves that are no terminal are cut off until none is left.
+    fn prune(&self, graph: &MutableGraph, mut edges: Vec<EdgeId>) -> Vec<EdgeId> {
+        edges.sort_by(|&a, &b| graph.cost(a).total_cmp(&graph.cost(b)));
+        edges.dedup();
+        let mut uf = UF::new(graph.nodes().len() as i32);
+        let mut tree: Vec<EdgeId> = edges.into_iter().filter(|&edge| {
+            let (u, v) = graph.endpoints(edge);
+            !uf.connected(u, v) && {
+                uf.union(u, v);
+                true
+            }
+        }).collect();
+        let mut terminal = vec![false; graph.nodes().len()];
+        self.terminals.iter().for_each(|&v| terminal[v] = true);
+        loop {
+            let mut degree = vec![0; graph.nodes().len()];
+            for &edge in &tree {
+                let (u, v) = graph.endpoints(edge);
+                degree[u] += 1;
+                degree[v] += 1;
+            }
+            let before = tree.len();
+            tree.retain(|&edge| {
+                let (u, v) = graph.endpoints(edge);
+                (degree[u] > 1 || terminal[u]) && (degree[v] > 1 || terminal[v])
+            });
+            if tree.len() == before {
+                return tree;
+            }
+        }
+    }
+
+    fn cost(graph: &MutableGraph, tree: &[EdgeId]) -> f64 {
+        tree.iter().map(|&edge| graph.cost(edge)).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use crate::algorithms::constrained_bottleneck_spanning_tree::steiner::SteinerTree;
+    use crate::algorithms::constrained_bottleneck_spanning_tree::solver::solver_by_name;
+    use crate::algorithms::constrained_bottleneck_spanning_tree::test_graphs::{brute_force, graph, square, steiner_brute_force, Link};
+    use crate::datastructures::graph::edge_store::EdgeId;
+    use crate::datastructures::uf::union_find::UF;
+    use crate::error::CbstuError;
+
+    #[test]
+    fn test_all_terminals_match_solver() {
+        let graph = square();
+        let berman = solver_by_name("berman").unwrap();
+        for budget in [0.0, 2.0, 5.0, 9.0, 13.0, 15.0, 30.0] {
+            let solution = SteinerTree::new(vec![0, 1, 2, 3]).solve(&graph, budget).unwrap();
+            let expected = berman.solve(&graph, budget).unwrap();
+            assert_eq!((solution.bottleneck(), solution.cost()), (expected.bottleneck(), expected.cost()), "budget {}", budget);
+            assert!(solution.is_optimal());
+        }
+    }
+
+    #[test]
+    fn test_terminals() {
+        let graph = square();
+        // (budget, bottleneck, cost) between 0 and 2: directly or around node 1 or node 3
+        for (budget, bottleneck, cost) in [(0.0, -3.0, 0.0), (5.0, -6.0, 2.0), (6.0, -20.0, 6.0)] {
+            let solution = SteinerTree::new(vec![0, 2]).solve(&graph, budget).unwrap();
+            assert_eq!((solution.bottleneck(), solution.cost()), (bottleneck, cost), "budget {}", budget);
+        }
+        // between 1 and 3 the upgrades of (3,0) and (0,1) beat those around node 2
+        for (budget, bottleneck, cost) in [(5.0, -6.0, 3.0), (6.0, -9.0, 6.0)] {
+            let solution = SteinerTree::new(vec![1, 3]).solve(&graph, budget).unwrap();
+            assert_eq!((solution.bottleneck(), solution.cost(), solution.tree().edges().len()), (bottleneck, cost, 2), "budget {}", budget);
+        }
+        assert!(matches!(SteinerTree::new(vec![1, 3]).solve(&graph, -1.0), Err(CbstuError::InfeasibleBudget)));
+        assert!(matches!(SteinerTree::new(vec![1]).solve(&graph, 5.0), Err(CbstuError::MalformedInput(_))));
+        assert!(matches!(SteinerTree::new(vec![1, 4]).solve(&graph, 5.0), Err(CbstuError::MalformedInput(_))));
+    }
+
+    #[test]
+    fn test_random_terminals() {
+        // random graphs on 6 nodes with 2 to 4 terminals, against the best tree joining them within budget
+        let mut steiner = 0;
+        for seed in 0..100 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut links: Vec<Link> = Vec::new();
+            for v in 1..6 {
+                links.push((rng.gen_range(0..v), v, rng.gen_range(1..10) as f64, rng.gen_range(10..20) as f64, rng.gen_range(0..10) as f64));
+            }
+            for _ in 0..4 {
+                let (u, v) = (rng.gen_range(0..6), rng.gen_range(0..6));
+                if u != v {
+                    links.push((u, v, rng.gen_range(1..10) as f64, rng.gen_range(10..20) as f64, rng.gen_range(0..10) as f64));
+                }
+            }
+            let graph = graph(6, &links);
+            let mut terminals: Vec<usize> = (0..6).collect();
+            terminals.shuffle(&mut rng);
+            terminals.truncate(rng.gen_range(2..5));
+            let budget = rng.gen_range(0..20) as f64;
+            let fits = |tree: &[EdgeId]| tree.iter().map(|&edge| graph.cost(edge)).sum::<f64>() <= budget;
+            let optimum = steiner_brute_force(&graph, 6, &terminals, fits);
+            let solution = SteinerTree::new(terminals.clone()).solve(&graph, budget).unwrap();
+            assert!(solution.is_optimal(), "seed {}", seed);
+            assert_eq!(solution.bottleneck(), optimum, "seed {}", seed);
+            assert!(solution.cost() <= budget, "seed {}", seed);
+            let mut uf = UF::new(6);
+            solution.tree().edges().iter().for_each(|&edge| uf.union(solution.tree().endpoints(edge).0, solution.tree().endpoints(edge).1));
+            assert!(terminals.iter().all(|&terminal| uf.connected(terminals[0], terminal)), "seed {}", seed);
+            steiner += (optimum < brute_force(&graph, 6, fits)) as usize;
+        }
+        // leaving nodes out of the tree pays off in some of the graphs
+        assert!(steiner > 0);
+    }
+}
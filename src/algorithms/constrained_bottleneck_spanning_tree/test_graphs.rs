@@ -0,0 +1,95 @@
+use std::sync::Arc;
+use crate::algorithms::constrained_bottleneck_spanning_tree::lexicographic::Lexicographic;
+use crate::algorithms::util::Util;
+use crate::datastructures::graph::edge::Edge;
+use crate::datastructures::graph::edge_store::EdgeId;
+use crate::datastructures::graph::mutable_graph::MutableGraph;
+use crate::datastructures::graph::node::Node;
+use crate::datastructures::uf::union_find::UF;
+
+/// A link as `(source, target, capacity, upgraded capacity, upgrade cost)`.
+pub type Link = (usize, usize, f64, f64, f64);
+
+/// The square 0-1-2-3 with the diagonal 0-2.
+pub const SQUARE: [Link; 5] = [(0, 1, 5.0, 10.0, 4.0), (1, 2, 2.0, 8.0, 3.0), (2, 3, 6.0, 12.0, 5.0), (3, 0, 3.0, 9.0, 2.0), (0, 2, 1.0, 20.0, 6.0)];
+
+/// Inverts the weights of `graph` and duplicates its edges, as the binary does before solving.
+pub fn preprocess(mut graph: MutableGraph) -> MutableGraph {
+    graph.inverse_weights();
+    Util::duplicate_edges(&graph)
+}
+
+/// Preprocessed graph of `nodes` and `links`; `edge` completes the edge of the link at a position.
+pub fn build(nodes: Vec<Node>, links: &[Link], edge: impl Fn(usize, Edge) -> Edge) -> MutableGraph {
+    preprocess(unprocessed(nodes, links, edge))
+}
+
+/// Graph of `nodes` and `links` before preprocessing, for the variants that read the capacities as given.
+pub fn unprocessed(nodes: Vec<Node>, links: &[Link], edge: impl Fn(usize, Edge) -> Edge) -> MutableGraph {
+    let edges = links.iter().enumerate()
+        .map(|(i, &(v, w, k, k_bar, c))| edge(i, Edge::new(v, w).weight(k).upgraded_weight(k_bar).cost(c)))
+        .collect();
+    MutableGraph::new(Arc::new(nodes), edges)
+}
+
+/// Duplicated graph of `links` between `n` plain nodes that keeps their weights, for the variants minimizing weights
+/// such as latencies.
+pub fn latency_graph(n: usize, links: &[Link]) -> MutableGraph {
+    Util::duplicate_edges(&unprocessed(nodes(n), links, |_, edge| edge))
+}
+
+/// Preprocessed graph of `links` between `n` plain nodes.
+pub fn graph(n: usize, links: &[Link]) -> MutableGraph {
+    build(nodes(n), links, |_, edge| edge)
+}
+
+pub fn nodes(n: usize) -> Vec<Node> {
+    (0..n).map(Node::default).collect()
+}
+
+/// Preprocessed [`SQUARE`].
+pub fn square() -> MutableGraph {
+    graph(4, &SQUARE)
+}
+
+/// Best bottleneck over the spanning trees of the first `n` nodes of the preprocessed `graph` that `fits` accepts,
+/// infinite if there is none.
+pub fn brute_force(graph: &MutableGraph, n: usize, fits: impl Fn(&[EdgeId]) -> bool) -> f64 {
+    steiner_brute_force(graph, n, &(0..n).collect::<Vec<usize>>(), fits)
+}
+
+/// Best bottleneck over the trees among the first `n` nodes of the preprocessed `graph` that join the `terminals` and
+/// that `fits` accepts, infinite if there is none.
+pub fn steiner_brute_force(graph: &MutableGraph, n: usize, terminals: &[usize], fits: impl Fn(&[EdgeId]) -> bool) -> f64 {
+    brute_force_ranks(graph, n, terminals, fits).map_or(f64::INFINITY, |ranks| ranks.first().copied().unwrap_or(f64::NEG_INFINITY))
+}
+
+/// Lexicographically smallest [ranks](Lexicographic::ranks) over the trees among the first `n` nodes of the
+/// preprocessed `graph` that join the `terminals` and that `fits` accepts, `None` if there is none.
+pub fn brute_force_ranks(graph: &MutableGraph, n: usize, terminals: &[usize], fits: impl Fn(&[EdgeId]) -> bool) -> Option<Vec<f64>> {
+    let edges: Vec<EdgeId> = graph.edges().iter().copied()
+        .filter(|&edge| graph.endpoints(edge).0 < n && graph.endpoints(edge).1 < n)
+        .collect();
+    let mut best: Option<Vec<f64>> = None;
+    for mask in 0u32..(1 << edges.len()) {
+        // a tree among n nodes has fewer than n edges
+        if mask.count_ones() as usize >= n {
+            continue;
+        }
+        let tree: Vec<EdgeId> = (0..edges.len()).filter(|&i| mask & (1 << i) != 0).map(|i| edges[i]).collect();
+        let mut uf = UF::new(n as i32);
+        let acyclic = tree.iter().all(|&edge| {
+            let (u, v) = graph.endpoints(edge);
+            let joins = !uf.connected(u, v);
+            uf.union(u, v);
+            joins
+        });
+        if acyclic && terminals.iter().all(|&terminal| uf.connected(terminals[0], terminal)) && fits(&tree) {
+            let ranks = Lexicographic::ranks(graph, &tree);
+            if best.as_ref().map_or(true, |best| ranks < *best) {
+                best = Some(ranks);
+            }
+        }
+    }
+    best
+}
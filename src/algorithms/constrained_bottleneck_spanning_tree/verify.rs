@@ -0,0 +1,244 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use log::{debug, trace};
+use crate::algorithms::constrained_bottleneck_spanning_tree::constraints::{ConstrainedGraph, Constraints};
+use crate::algorithms::constrained_bottleneck_spanning_tree::solver::Solution;
+use crate::algorithms::min_sum_spanning_tree::kruskal::CalculationType;
+use crate::algorithms::util::Util;
+use crate::datastructures::graph::mutable_graph::MutableGraph;
+use crate::error::CbstuError;
+
+/// Proof that no spanning tree within budget has a better bottleneck than the verified solution.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Certificate {
+    /// The bottleneck is the best weight in the graph, so nothing better exists.
+    BestWeight,
+    /// The edges of the next better weight class `threshold` (and better) do not span the graph.
+    Disconnected { threshold: f64 },
+    /// The min-cost spanning tree using only edges of the next better weight class `threshold` (and better) costs
+    /// `min_cost`, which exceeds the budget.
+    CostExceedsBudget { threshold: f64, min_cost: f64 },
+    /// A required link has no edge of the next better weight class `threshold` or better.
+    RequiredExcluded { threshold: f64 },
+}
+
+/// Reason why a claimed solution is rejected.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    NotSpanningTree,
+    /// Tree edge that is neither the base (`k`) nor an upgrade tier (`kBar`) of a link of the graph.
+    UnknownEdge { endpoints: (usize, usize), weight: f64, upgraded: bool },
+    CostMismatch { claimed: f64, actual: f64 },
+    BottleneckMismatch { claimed: f64, actual: f64 },
+    OverBudget { cost: f64, budget: f64 },
+    /// A spanning tree with bottleneck `bottleneck` and cost `cost` fits the budget. For a solution claimed
+    /// infeasible, this is any tree within budget.
+    NotOptimal { bottleneck: f64, cost: f64 },
+    /// The tree uses a forbidden edge of the link or leaves out a required link.
+    ConstraintViolated { endpoints: (usize, usize) },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::NotSpanningTree => write!(f, "solution is not a spanning tree"),
+            Violation::UnknownEdge { endpoints, weight, upgraded } =>
+                write!(f, "edge {:?} with weight {} (upgraded: {}) is not part of the graph", endpoints, -weight, upgraded),
+            Violation::CostMismatch { claimed, actual } => write!(f, "claimed cost {} but tree costs {}", claimed, actual),
+            Violation::BottleneckMismatch { claimed, actual } =>
+                write!(f, "claimed bottleneck {} but tree has bottleneck {}", -claimed, -actual),
+            Violation::OverBudget { cost, budget } => write!(f, "tree costs {} which exceeds budget {}", cost, budget),
+            Violation::NotOptimal { bottleneck, cost } =>
+                write!(f, "a spanning tree with bottleneck {} and cost {} fits the budget", -bottleneck, cost),
+            Violation::ConstraintViolated { endpoints } => write!(f, "link {:?} violates its required or forbidden constraint", endpoints),
+        }
+    }
+}
+
+pub struct Verifier();
+
+impl Verifier {
+    /// Checks a claimed solution of the preprocessed (inverted, duplicated) graph against the budget. A feasible
+    /// solution must be a spanning tree of the graph's nodes built from base or upgraded links, fit the budget and
+    /// report its own cost and bottleneck. Optimality is proven by the threshold cut: the spanning trees of the next
+    /// better weight class are either disconnected or too expensive. An infeasible solution is proven by the min-cost
+    /// spanning tree of the whole graph. Link constraints are checked on the tree, the threshold cuts run on the reduced
+    /// graph of [`Constraints::reduce`]. Errors only come from the graph itself, not from the solution.
+    pub fn verify(graph: &MutableGraph, budget: f64, solution: &Solution) -> Result<Result<Certificate, Violation>, CbstuError> {
+        let unique_weights = Util::sorted_unique_weights(graph)?;
+        let reduced = Constraints::reduce(graph, Some(budget))?;
+        let tree = match solution.tree() {
+            Some(tree) => tree,
+            None => {
+                trace!("Verifying infeasibility claim");
+                return Self::threshold_cut(graph, &reduced, budget, unique_weights[unique_weights.len() - 1]);
+            }
+        };
+        if let Err(violation) = Self::check_tree(graph, tree) {
+            return Ok(Err(violation));
+        }
+        let cost = tree.calculate_total_cost();
+        if cost != solution.cost() {
+            return Ok(Err(Violation::CostMismatch { claimed: solution.cost(), actual: cost }));
+        }
+        if cost > budget {
+            return Ok(Err(Violation::OverBudget { cost, budget }));
+        }
+        let bottleneck = tree.edges().iter().map(|&e| tree.weight(e)).fold(f64::NEG_INFINITY, f64::max);
+        if bottleneck != solution.bottleneck() {
+            return Ok(Err(Violation::BottleneckMismatch { claimed: solution.bottleneck(), actual: bottleneck }));
+        }
+        match unique_weights.iter().rev().find(|&&w| w < bottleneck) {
+            Some(&threshold) => Self::threshold_cut(graph, &reduced, budget, threshold),
+            None => {
+                debug!("Bottleneck {} is the best weight of the graph", bottleneck);
+                Ok(Ok(Certificate::BestWeight))
+            }
+        }
+    }
+
+    /// Min-cost spanning tree on the edges with weight smaller than or equal to `threshold`. If it fits the budget,
+    /// a tree with a strictly better bottleneck than the verified one exists.
+    fn threshold_cut(graph: &MutableGraph, reduced: &ConstrainedGraph, budget: f64, threshold: f64) -> Result<Result<Certificate, Violation>, CbstuError> {
+        match reduced.graph().smaller_or_eq_than(threshold).mst(CalculationType::Cost) {
+            Ok((st, _, _)) if !reduced.satisfies(&st) => {
+                debug!("Threshold {} excludes a required link", threshold);
+                Ok(Ok(Certificate::RequiredExcluded { threshold }))
+            }
+            Ok((st, _, better)) => {
+                let (_, min_cost) = reduced.restore(graph, &st);
+                if min_cost <= budget {
+                    debug!("Threshold {} admits a tree within budget [bottleneck {}, cost {}]", threshold, better, min_cost);
+                    Ok(Err(Violation::NotOptimal { bottleneck: better, cost: min_cost }))
+                } else {
+                    debug!("Min-cost tree of threshold {} costs {} > {}", threshold, min_cost, budget);
+                    Ok(Ok(Certificate::CostExceedsBudget { threshold, min_cost }))
+                }
+            }
+            Err(CbstuError::DisconnectedGraph) | Err(CbstuError::EmptyEdgeSet) => {
+                debug!("Edges of threshold {} do not span the graph", threshold);
+                Ok(Ok(Certificate::Disconnected { threshold }))
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Checks that every tree edge matches a distinct base or upgraded link of the graph that is not forbidden, that
+    /// every required link is used and that the edges form a spanning tree of the graph's nodes.
+    fn check_tree(graph: &MutableGraph, tree: &MutableGraph) -> Result<(), Violation> {
+        let n = graph.nodes().len();
+        if tree.nodes().len() != n || tree.edges().len() + 1 != n {
+            return Err(Violation::NotSpanningTree);
+        }
+        let mut available: HashMap<(usize, usize, bool), Vec<(f64, f64, bool)>> = HashMap::new();
+        let mut required = HashSet::new();
+        for &edge in graph.edges() {
+            let (u, v) = graph.endpoints(edge);
+            available.entry((u.min(v), u.max(v), graph.is_upgraded(edge))).or_default()
+                .push((graph.weight(edge), graph.cost(edge), graph.is_forbidden(edge)));
+            if graph.is_required(edge) {
+                required.insert((u.min(v), u.max(v)));
+            }
+        }
+        // allowed edges first, a forbidden one only matches when nothing else does
+        available.values_mut().for_each(|edges| edges.sort_by_key(|&(_, _, forbidden)| !forbidden));
+        for &edge in tree.edges() {
+            let edge = tree.edge(edge);
+            let (u, v) = edge.endpoints();
+            if u >= n || v >= n {
+                return Err(Violation::NotSpanningTree);
+            }
+            let unknown = Violation::UnknownEdge { endpoints: (u, v), weight: edge.get_weight(), upgraded: edge.is_upgraded() };
+            let candidates = available.get_mut(&(u.min(v), u.max(v), edge.is_upgraded())).ok_or(unknown.clone())?;
+            let index = candidates.iter()
+                .position(|&(weight, cost, _)| weight == edge.get_weight() && cost == edge.get_cost())
+                .ok_or(unknown)?;
+            if candidates.remove(index).2 {
+                return Err(Violation::ConstraintViolated { endpoints: (u, v) });
+            }
+            required.remove(&(u.min(v), u.max(v)));
+        }
+        if let Some(&endpoints) = required.iter().min() {
+            return Err(Violation::ConstraintViolated { endpoints });
+        }
+        if !tree.is_spanning_tree() {
+            return Err(Violation::NotSpanningTree);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithms::constrained_bottleneck_spanning_tree::solver::{solver_by_name, Solution, SOLVER_NAMES};
+    use crate::algorithms::constrained_bottleneck_spanning_tree::verify::{Certificate, Verifier, Violation};
+    use crate::algorithms::constrained_bottleneck_spanning_tree::test_graphs::{build, nodes, square, SQUARE};
+    use crate::datastructures::graph::edge::Edge;
+    use crate::datastructures::graph::mutable_graph::MutableGraph;
+
+    #[test]
+    fn test_verify_solver_output() {
+        let graph = square();
+        for name in SOLVER_NAMES {
+            for budget in [-1.0, 0.0, 3.0, 5.0, 9.0, 100.0] {
+                let solution = solver_by_name(name).unwrap().solve(&graph, budget).unwrap();
+                let certificate = Verifier::verify(&graph, budget, &solution).unwrap();
+                assert!(certificate.is_ok(), "{} with budget {}: {:?}", name, budget, certificate);
+            }
+        }
+        let solution = solver_by_name("berman").unwrap().solve(&graph, 100.0).unwrap();
+        assert_eq!(Verifier::verify(&graph, 100.0, &solution).unwrap(), Ok(Certificate::Disconnected { threshold: -12.0 }));
+        let solution = solver_by_name("berman").unwrap().solve(&graph, 5.0).unwrap();
+        assert_eq!(Verifier::verify(&graph, 5.0, &solution).unwrap(), Ok(Certificate::CostExceedsBudget { threshold: -8.0, min_cost: 9.0 }));
+        let single = build(nodes(2), &[(0, 1, 1.0, 2.0, 1.0)], |_, edge| edge);
+        let solution = solver_by_name("berman").unwrap().solve(&single, 5.0).unwrap();
+        assert_eq!(Verifier::verify(&single, 5.0, &solution).unwrap(), Ok(Certificate::BestWeight));
+    }
+
+    #[test]
+    fn test_reject_claims() {
+        let graph = square();
+        let optimal = solver_by_name("berman").unwrap().solve(&graph, 5.0).unwrap();
+        // suboptimal: the optimal tree for budget 0 within budget 5
+        let suboptimal = solver_by_name("berman").unwrap().solve(&graph, 0.0).unwrap();
+        assert!(matches!(Verifier::verify(&graph, 5.0, &suboptimal).unwrap(), Err(Violation::NotOptimal { .. })));
+        // over budget
+        assert!(matches!(Verifier::verify(&graph, 4.0, &optimal).unwrap(), Err(Violation::OverBudget { .. })));
+        // wrong claims
+        let tree = optimal.tree().unwrap().clone();
+        let wrong_cost = Solution::feasible(tree.clone(), 4.0, optimal.bottleneck());
+        assert!(matches!(Verifier::verify(&graph, 5.0, &wrong_cost).unwrap(), Err(Violation::CostMismatch { .. })));
+        let wrong_bottleneck = Solution::feasible(tree.clone(), optimal.cost(), -7.0);
+        assert!(matches!(Verifier::verify(&graph, 5.0, &wrong_bottleneck).unwrap(), Err(Violation::BottleneckMismatch { .. })));
+        // upgraded weight without paying for it
+        let mut edges: Vec<Edge> = tree.edges().iter().map(|&edge| tree.edge(edge)).collect();
+        let upgraded = edges.iter().position(|edge| edge.is_upgraded()).unwrap();
+        edges[upgraded] = edges[upgraded].clone().cost(0.0);
+        let free_upgrade = Solution::feasible(MutableGraph::new(graph.nodes_copy(), edges), 0.0, optimal.bottleneck());
+        assert!(matches!(Verifier::verify(&graph, 5.0, &free_upgrade).unwrap(), Err(Violation::UnknownEdge { .. })));
+        // not spanning
+        let mut edges = tree.edges_copy();
+        edges.pop();
+        let forest = Solution::feasible(tree.subgraph(edges), 0.0, optimal.bottleneck());
+        assert_eq!(Verifier::verify(&graph, 5.0, &forest).unwrap(), Err(Violation::NotSpanningTree));
+        // infeasibility claim while a tree fits the budget
+        assert!(matches!(Verifier::verify(&graph, 0.0, &Solution::infeasible()).unwrap(), Err(Violation::NotOptimal { .. })));
+    }
+
+    #[test]
+    fn test_reject_constraint_violations() {
+        let graph = square();
+        // uses (2, 3) at its base weight
+        let solution = solver_by_name("berman").unwrap().solve(&graph, 5.0).unwrap();
+        let constrained = |flag: fn(usize, Edge) -> Edge| build(nodes(4), &SQUARE, flag);
+        let forbidden = constrained(|i, edge| edge.upgrade_required(i == 2));
+        assert_eq!(Verifier::verify(&forbidden, 5.0, &solution).unwrap(), Err(Violation::ConstraintViolated { endpoints: (2, 3) }));
+        let required = constrained(|i, edge| edge.required(i == 4));
+        assert_eq!(Verifier::verify(&required, 5.0, &solution).unwrap(), Err(Violation::ConstraintViolated { endpoints: (0, 2) }));
+        let weak = solver_by_name("berman").unwrap().solve(&required, 5.0).unwrap();
+        assert_eq!(Verifier::verify(&required, 5.0, &weak).unwrap(), Ok(Certificate::CostExceedsBudget { threshold: -2.0, min_cost: 6.0 }));
+        let base_only = constrained(|i, edge| edge.required(i == 4).upgrade_forbidden(i == 4));
+        let weak = solver_by_name("berman").unwrap().solve(&base_only, 5.0).unwrap();
+        assert_eq!(Verifier::verify(&base_only, 5.0, &weak).unwrap(), Ok(Certificate::RequiredExcluded { threshold: -2.0 }));
+    }
+}
@@ -0,0 +1,93 @@
+use crate::error::CbstuError;
+
+/// Cost of raising the capacity of a link from its base capacity to any capacity up to its maximum. The curve is
+/// piecewise linear through `points`: the first point is `(base capacity, 0)`, the last one `(maximum capacity,
+/// full upgrade cost)`. Capacities are not inverted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostCurve {
+    points: Vec<(f64, f64)>,
+}
+
+impl CostCurve {
+    /// Upgrade at a constant `rate` per unit of capacity, up to `max_capacity`.
+    pub fn linear(base_capacity: f64, max_capacity: f64, rate: f64) -> Result<CostCurve, CbstuError> {
+        Self::piecewise(base_capacity, vec![(max_capacity, rate * (max_capacity - base_capacity))])
+    }
+
+    /// Upgrade through the given `(capacity, cost)` points, linear in between. Capacities must increase and costs must
+    /// not decrease. Without points the link cannot be upgraded.
+    pub fn piecewise(base_capacity: f64, points: Vec<(f64, f64)>) -> Result<CostCurve, CbstuError> {
+        let mut curve = vec![(base_capacity, 0.0)];
+        for (capacity, cost) in points {
+            let &(last_capacity, last_cost) = curve.last().unwrap();
+            if !(capacity > last_capacity) || !(cost >= last_cost) {
+                return Err(CbstuError::MalformedInput(format!("cost curve point ({}, {}) does not follow ({}, {})", capacity, cost, last_capacity, last_cost)));
+            }
+            curve.push((capacity, cost));
+        }
+        Ok(CostCurve { points: curve })
+    }
+
+    pub fn points(&self) -> &Vec<(f64, f64)> {
+        &self.points
+    }
+
+    pub fn base_capacity(&self) -> f64 {
+        self.points[0].0
+    }
+
+    pub fn max_capacity(&self) -> f64 {
+        self.points[self.points.len() - 1].0
+    }
+
+    pub fn full_cost(&self) -> f64 {
+        self.points[self.points.len() - 1].1
+    }
+
+    /// Cost of reaching at least `capacity`, `None` if the link cannot be upgraded that far.
+    pub fn cost_at(&self, capacity: f64) -> Option<f64> {
+        let (intercept, slope) = self.line(capacity)?;
+        Some(intercept + slope * capacity)
+    }
+
+    /// Cost as `(intercept, slope)` of the linear piece ending at or after `capacity`, so the line holds on an
+    /// interval `(lower, capacity]` without breakpoints. Zero up to the base capacity, `None` above the maximum
+    /// capacity.
+    pub fn line(&self, capacity: f64) -> Option<(f64, f64)> {
+        if capacity <= self.base_capacity() {
+            return Some((0.0, 0.0));
+        }
+        let end = self.points.iter().position(|&(x, _)| x >= capacity)?;
+        let (x0, y0) = self.points[end - 1];
+        let (x1, y1) = self.points[end];
+        let slope = (y1 - y0) / (x1 - x0);
+        Some((y0 - slope * x0, slope))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithms::continuous_bottleneck_spanning_tree::cost_curve::CostCurve;
+    use crate::error::CbstuError;
+
+    #[test]
+    fn test_cost_curve() {
+        let linear = CostCurve::linear(2.0, 10.0, 1.5).unwrap();
+        assert_eq!(linear.full_cost(), 12.0);
+        assert_eq!(linear.cost_at(1.0), Some(0.0));
+        assert_eq!(linear.cost_at(4.0), Some(3.0));
+        assert_eq!(linear.cost_at(10.5), None);
+        let piecewise = CostCurve::piecewise(1.0, vec![(5.0, 1.0), (20.0, 31.0)]).unwrap();
+        assert_eq!(piecewise.cost_at(3.0), Some(0.5));
+        assert_eq!(piecewise.cost_at(5.0), Some(1.0));
+        assert_eq!(piecewise.cost_at(6.0), Some(3.0));
+        assert_eq!(piecewise.line(5.0), Some((-0.25, 0.25)));
+        assert_eq!(piecewise.line(6.0), Some((-9.0, 2.0)));
+        assert_eq!(piecewise.line(21.0), None);
+        let fixed = CostCurve::piecewise(3.0, Vec::new()).unwrap();
+        assert_eq!((fixed.cost_at(3.0), fixed.cost_at(3.5)), (Some(0.0), None));
+        assert_eq!((fixed.line(3.0), fixed.line(3.5)), (Some((0.0, 0.0)), None));
+        assert!(matches!(CostCurve::piecewise(1.0, vec![(5.0, 2.0), (4.0, 3.0)]), Err(CbstuError::MalformedInput(_))));
+        assert!(matches!(CostCurve::piecewise(1.0, vec![(5.0, 2.0), (6.0, 1.0)]), Err(CbstuError::MalformedInput(_))));
+    }
+}
@@ -0,0 +1,2 @@
+pub mod cost_curve;
+pub mod parametric;
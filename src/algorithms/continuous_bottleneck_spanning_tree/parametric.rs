@@ -0,0 +1,295 @@
+use std::sync::Arc;
+use log::{debug, trace};
+use crate::algorithms::continuous_bottleneck_spanning_tree::cost_curve::CostCurve;
+use crate::datastructures::graph::edge::Edge;
+use crate::datastructures::graph::edge_store::EdgeId;
+use crate::datastructures::graph::mutable_graph::MutableGraph;
+use crate::datastructures::graph::node::Node;
+use crate::datastructures::uf::union_find::UF;
+use crate::error::CbstuError;
+
+const EPSILON: f64 = 1e-9;
+
+/// Instance with continuous upgrades. The graph holds the base capacity `k` as weight, the maximum capacity as upgraded
+/// weight and the full upgrade cost as cost; weights are not inverted. The cost curve of edge `id` is `curves[id]`.
+#[derive(Debug, Clone)]
+pub struct ContinuousInstance {
+    graph: MutableGraph,
+    curves: Vec<CostCurve>,
+}
+
+impl ContinuousInstance {
+    pub fn new(nodes: Arc<Vec<Node>>, links: Vec<(usize, usize, CostCurve)>) -> ContinuousInstance {
+        let edges = links.iter().map(|(either, other, curve)| {
+            Edge::new(*either, *other).weight(curve.base_capacity()).upgraded_weight(curve.max_capacity()).cost(curve.full_cost())
+        }).collect();
+        let curves = links.into_iter().map(|(_, _, curve)| curve).collect();
+        ContinuousInstance { graph: MutableGraph::new(nodes, edges), curves }
+    }
+
+    pub fn graph(&self) -> &MutableGraph {
+        &self.graph
+    }
+
+    pub fn curve(&self, edge: EdgeId) -> &CostCurve {
+        &self.curves[edge as usize]
+    }
+
+    /// Capacities where the cost of some edge changes slope or the edge can no longer reach, sorted ascending.
+    fn breakpoints(&self) -> Vec<f64> {
+        let mut breakpoints: Vec<f64> = self.curves.iter().flat_map(|curve| curve.points().iter().map(|&(x, _)| x)).collect();
+        breakpoints.sort_by(|a, b| a.total_cmp(b));
+        breakpoints.dedup();
+        breakpoints
+    }
+}
+
+/// Upgrade of a tree edge from its base capacity `from` to capacity `to`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgeUpgrade {
+    edge: EdgeId,
+    from: f64,
+    to: f64,
+    cost: f64,
+}
+
+impl EdgeUpgrade {
+    pub fn edge(&self) -> EdgeId {
+        self.edge
+    }
+
+    pub fn from(&self) -> f64 {
+        self.from
+    }
+
+    pub fn to(&self) -> f64 {
+        self.to
+    }
+
+    pub fn cost(&self) -> f64 {
+        self.cost
+    }
+}
+
+/// Result of the continuous search. `capacity` is the bottleneck capacity of the tree (not inverted).
+#[derive(Debug, Clone)]
+pub struct ContinuousSolution {
+    tree: MutableGraph,
+    capacity: f64,
+    cost: f64,
+    upgrades: Vec<EdgeUpgrade>,
+}
+
+impl ContinuousSolution {
+    pub fn tree(&self) -> &MutableGraph {
+        &self.tree
+    }
+
+    pub fn capacity(&self) -> f64 {
+        self.capacity
+    }
+
+    pub fn cost(&self) -> f64 {
+        self.cost
+    }
+
+    pub fn upgrades(&self) -> &Vec<EdgeUpgrade> {
+        &self.upgrades
+    }
+}
+
+/// Cheapest tree for a target capacity: edges, total cost and the slope of the total cost in the target.
+type PricedTree = (Vec<EdgeId>, f64, f64);
+
+pub struct ParametricSearch();
+
+impl ParametricSearch {
+    /// Maximizes the bottleneck capacity of a spanning tree whose upgrades fit the budget. The cheapest tree for a
+    /// target capacity `t` costs `g(t)`, which is non-decreasing, so a bisection over the breakpoints of the cost
+    /// curves finds the last affordable breakpoint. Between two breakpoints every edge cost is linear in `t` and
+    /// `g` is the minimum over trees of linear functions, so Newton steps from the left reach the exact optimum.
+    pub fn run(instance: &ContinuousInstance, budget: f64) -> Result<ContinuousSolution, CbstuError> {
+        trace!("Solving continuous upgrade bottleneck spanning tree problem with parametric search");
+        if instance.graph().edges().is_empty() {
+            return Err(CbstuError::EmptyEdgeSet);
+        }
+        let breakpoints = instance.breakpoints();
+        match Self::cheapest_tree(instance, breakpoints[0], breakpoints[0]) {
+            None => return Err(CbstuError::DisconnectedGraph),
+            Some((_, cost, _)) if cost > budget => return Err(CbstuError::InfeasibleBudget),
+            Some(_) => {}
+        }
+        let mut min = 0;
+        let mut max = breakpoints.len() - 1;
+        while min < max {
+            let pivot = (min + max + 1) / 2;
+            match Self::cheapest_tree(instance, breakpoints[pivot], breakpoints[pivot]) {
+                Some((_, cost, _)) if cost <= budget => min = pivot,
+                _ => max = pivot - 1,
+            }
+        }
+        debug!("Last affordable breakpoint {}", breakpoints[min]);
+        let mut capacity = breakpoints[min];
+        let segment_end = match breakpoints.get(min + 1) {
+            Some(&next) => next,
+            None => capacity,
+        };
+        let mut tree = Self::cheapest_tree(instance, segment_end, capacity);
+        while let Some((_, cost, slope)) = tree {
+            if slope <= 0.0 {
+                break;
+            }
+            let next = (capacity + (budget - cost) / slope).min(segment_end);
+            if next <= capacity + EPSILON * (1.0 + capacity.abs()) {
+                break;
+            }
+            trace!("Newton step to capacity {}", next);
+            capacity = next;
+            tree = Self::cheapest_tree(instance, segment_end, capacity);
+        }
+        // without a step the tree of the breakpoint itself is needed, edges ending there are still usable
+        let (edges, _, _) = match tree {
+            Some(tree) if capacity > breakpoints[min] => tree,
+            _ => Self::cheapest_tree(instance, capacity, capacity).unwrap(),
+        };
+        Ok(Self::solution(instance, edges, capacity))
+    }
+
+    /// Kruskal on the costs of reaching `capacity`, with the edge costs taken from their linear piece ending at
+    /// `segment_end`. Cost ties are broken on slope, so the tree is also the cheapest just above `capacity`.
+    fn cheapest_tree(instance: &ContinuousInstance, segment_end: f64, capacity: f64) -> Option<PricedTree> {
+        let graph = instance.graph();
+        let mut priced: Vec<(EdgeId, f64, f64)> = graph.edges().iter()
+            .filter_map(|&edge| {
+                let (intercept, slope) = instance.curve(edge).line(segment_end)?;
+                Some((edge, intercept + slope * capacity, slope))
+            })
+            .collect();
+        priced.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.2.total_cmp(&b.2)));
+        let mut uf = UF::new(graph.nodes().len() as i32);
+        let mut tree = (Vec::new(), 0.0, 0.0);
+        for (edge, cost, slope) in priced {
+            let (v, w) = graph.endpoints(edge);
+            if !uf.connected(v, w) {
+                uf.union(v, w);
+                tree.0.push(edge);
+                tree.1 += cost;
+                tree.2 += slope;
+            }
+        }
+        match uf.count() {
+            1 => Some(tree),
+            _ => None,
+        }
+    }
+
+    fn solution(instance: &ContinuousInstance, edges: Vec<EdgeId>, target: f64) -> ContinuousSolution {
+        let upgrades: Vec<EdgeUpgrade> = edges.iter()
+            .filter(|&&edge| instance.curve(edge).base_capacity() < target)
+            .map(|&edge| EdgeUpgrade {
+                edge,
+                from: instance.curve(edge).base_capacity(),
+                to: target,
+                cost: instance.curve(edge).cost_at(target).unwrap(),
+            })
+            .collect();
+        let cost = upgrades.iter().map(|upgrade| upgrade.cost).sum();
+        let capacity = edges.iter()
+            .map(|&edge| instance.curve(edge).base_capacity().max(target))
+            .fold(f64::INFINITY, f64::min);
+        debug!("Continuous solution [capacity: {}, cost: {}, upgrades: {}]", capacity, cost, upgrades.len());
+        ContinuousSolution { tree: instance.graph().subgraph(edges), capacity, cost, upgrades }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+    use crate::algorithms::continuous_bottleneck_spanning_tree::cost_curve::CostCurve;
+    use crate::algorithms::continuous_bottleneck_spanning_tree::parametric::{ContinuousInstance, ParametricSearch};
+    use crate::datastructures::graph::node::Node;
+    use crate::datastructures::uf::union_find::UF;
+    use crate::error::CbstuError;
+    use crate::io::generator::Generator;
+
+    fn triangle() -> ContinuousInstance {
+        let nodes = (0..3).map(Node::default).collect();
+        ContinuousInstance::new(Arc::new(nodes), vec![
+            (0, 1, CostCurve::linear(2.0, 10.0, 1.0).unwrap()),
+            (1, 2, CostCurve::linear(4.0, 8.0, 2.0).unwrap()),
+            (0, 2, CostCurve::piecewise(1.0, vec![(5.0, 1.0), (20.0, 31.0)]).unwrap()),
+        ])
+    }
+
+    #[test]
+    fn test_triangle() {
+        let instance = triangle();
+        for (budget, capacity, cost) in [(0.0, 2.0, 0.0), (3.0, 5.0, 3.0), (6.0, 5.75, 6.0), (1000.0, 10.0, 19.0)] {
+            let solution = ParametricSearch::run(&instance, budget).unwrap();
+            assert!((solution.capacity() - capacity).abs() < 1e-9, "budget {}: {}", budget, solution.capacity());
+            assert!((solution.cost() - cost).abs() < 1e-9, "budget {}: {}", budget, solution.cost());
+            assert!(solution.tree().is_spanning_tree());
+        }
+        let solution = ParametricSearch::run(&instance, 6.0).unwrap();
+        let mut upgrades: Vec<_> = solution.upgrades().iter().map(|upgrade| (upgrade.edge(), upgrade.from(), upgrade.to(), upgrade.cost())).collect();
+        upgrades.sort_by_key(|upgrade| upgrade.0);
+        assert_eq!(upgrades, vec![(1, 4.0, 5.75, 3.5), (2, 1.0, 5.75, 2.5)]);
+        assert!(matches!(ParametricSearch::run(&instance, -1.0), Err(CbstuError::InfeasibleBudget)));
+    }
+
+    /// Best capacity over all spanning trees, each tree's own cost is continuous and non-decreasing in the target.
+    fn brute_force(instance: &ContinuousInstance, budget: f64) -> f64 {
+        let graph = instance.graph();
+        let n = graph.nodes().len();
+        let m = graph.edges().len();
+        let mut best = f64::NEG_INFINITY;
+        for mask in 0u32..(1 << m) {
+            if mask.count_ones() as usize != n - 1 {
+                continue;
+            }
+            let tree: Vec<u32> = (0..m as u32).filter(|edge| mask & (1 << edge) != 0).collect();
+            let mut uf = UF::new(n as i32);
+            tree.iter().for_each(|&edge| uf.union(graph.endpoints(edge).0, graph.endpoints(edge).1));
+            if uf.count() != 1 {
+                continue;
+            }
+            let cost = |target: f64| tree.iter().map(|&edge| instance.curve(edge).cost_at(target).unwrap()).sum::<f64>();
+            let mut low = tree.iter().map(|&edge| instance.curve(edge).base_capacity()).fold(f64::INFINITY, f64::min);
+            let mut high = tree.iter().map(|&edge| instance.curve(edge).max_capacity()).fold(f64::INFINITY, f64::min);
+            if cost(high) <= budget {
+                low = high;
+            }
+            for _ in 0..100 {
+                let mid = (low + high) / 2.0;
+                if cost(mid) <= budget { low = mid } else { high = mid }
+            }
+            best = best.max(low);
+        }
+        best
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        for seed in 0..20 {
+            let graph = Generator::random_instance(5, 8, seed).unwrap();
+            let mut rng = StdRng::seed_from_u64(seed);
+            let links = graph.edges().iter().map(|&edge| {
+                let (v, w) = graph.endpoints(edge);
+                let (k, k_bar, c) = (graph.weight(edge), graph.upgraded_weight(edge), graph.cost(edge));
+                let curve = match rng.gen_bool(0.5) {
+                    true => CostCurve::linear(k, k_bar, c / (k_bar - k)).unwrap(),
+                    false => CostCurve::piecewise(k, vec![((k + k_bar) / 2.0, rng.gen_range(0.0..c)), (k_bar, c)]).unwrap(),
+                };
+                (v, w, curve)
+            }).collect();
+            let instance = ContinuousInstance::new(graph.nodes_copy(), links);
+            for budget in [0.0, 20.0, 75.0, 200.0] {
+                let solution = ParametricSearch::run(&instance, budget).unwrap();
+                assert!(solution.cost() <= budget + 1e-6);
+                assert!((solution.capacity() - brute_force(&instance, budget)).abs() < 1e-6, "seed {} budget {}", seed, budget);
+            }
+        }
+    }
+}
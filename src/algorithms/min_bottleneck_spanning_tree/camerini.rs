@@ -1,44 +1,71 @@
-use std::cell::{Ref, RefCell};
 use std::cmp::Ordering;
-use std::rc::Rc;
 use log::{trace};
 use crate::algorithms::quick_select::QuickSelect;
 use crate::algorithms::util::Util;
-use crate::datastructures::graph::edge::Edge;
+use crate::datastructures::graph::edge_store::EdgeId;
 use crate::datastructures::graph::mutable_graph::MutableGraph;
-use crate::datastructures::graph::node::Node;
 use crate::datastructures::uf::union_find::UF;
+use crate::error::CbstuError;
 use crate::print_edges;
 
+/// Edge of the (super) graph of a recursion step: the id of the edge in the original graph and its endpoints in the
+/// super graph, where each node is a component of the previous step.
+#[derive(Debug, Clone, Copy)]
+struct SuperEdge {
+    id: EdgeId,
+    either: usize,
+    other: usize,
+}
+
 pub struct MBST();
 
 impl MBST {
-    pub fn run(graph: &mut MutableGraph) -> (Option<MutableGraph>, f64) {
-        let st_edges = Self::recursive_search(graph);
-        let bottleneck = Self::find_bottleneck(&st_edges);
-        let st = MutableGraph::new(graph.nodes_copy(), st_edges);
+    pub fn run(graph: &MutableGraph) -> Result<(MutableGraph, f64), CbstuError> {
+        if graph.edges().is_empty() {
+            return Err(CbstuError::EmptyEdgeSet);
+        }
+        // the median search reorders the edge list, so work on a copy
+        let mut working_edges: Vec<SuperEdge> = graph.edges().iter().map(|&id| {
+            let (either, other) = graph.endpoints(id);
+            SuperEdge { id, either, other }
+        }).collect();
+        let st_edges = Self::recursive_search(graph, &mut working_edges, graph.nodes().len());
+        if !Self::spans(graph, &st_edges) {
+            return Err(CbstuError::DisconnectedGraph);
+        }
+        let bottleneck = Self::find_bottleneck(graph, &st_edges);
+        let st = graph.subgraph(st_edges);
         debug_assert!(st.is_spanning_tree());
-        (Some(st), bottleneck)
+        Ok((st, bottleneck))
     }
 
-    fn recursive_search(graph: &mut MutableGraph) -> Vec<Rc<RefCell<Edge>>> {
-        if graph.edges().len() == 1 {
-            return graph.edges_copy();
+    /// Checks that the found edges connect all n nodes, which fails when the graph is disconnected.
+    fn spans(graph: &MutableGraph, st_edges: &[EdgeId]) -> bool {
+        let mut uf = UF::new(graph.nodes().len() as i32);
+        st_edges.iter().for_each(|&edge| {
+            let (u, v) = graph.endpoints(edge);
+            uf.union(u, v);
+        });
+        uf.count() == 1
+    }
+
+    fn recursive_search(graph: &MutableGraph, edges: &mut [SuperEdge], n: usize) -> Vec<EdgeId> {
+        if edges.len() == 1 {
+            return vec![edges[0].id];
         }
-        let mut res = Vec::with_capacity(graph.nodes().len() - 1);
-        let median = QuickSelect::find_median_edges_mut(graph.edges_mut());
+        let mut res = Vec::with_capacity(n - 1);
+        let median = QuickSelect::find_median_by_weight(edges, |edge| graph.weight(edge.id));
         let mut big_half = Vec::new();
         let mut small_half = Vec::new();
-        let mut uf = UF::new(graph.nodes().len() as i32);
-        for edge in graph.edges() {
-            if Self::compare_edge_ref(&edge.borrow(), &median.borrow()) == Ordering::Greater {
-                big_half.push(Rc::clone(edge));
+        let mut uf = UF::new(n as i32);
+        for edge in edges.iter() {
+            if Self::compare_edges(graph, edge, &median) == Ordering::Greater {
+                big_half.push(*edge);
             } else {
-                small_half.push(Rc::clone(edge));
-                let (u, v) = edge.borrow().original_endpoints();
-                if !uf.connected(u, v) {
-                    uf.union(u, v);
-                    res.push(Rc::clone(edge));
+                small_half.push(*edge);
+                if !uf.connected(edge.either, edge.other) {
+                    uf.union(edge.either, edge.other);
+                    res.push(edge.id);
                 }
             }
         }
@@ -46,68 +73,61 @@ impl MBST {
             return res;
         }
         if uf.count() == 1 {
-            let mut small_half_graph = MutableGraph::new(graph.nodes_copy(), small_half);
-            return Self::recursive_search(&mut small_half_graph);
+            return Self::recursive_search(graph, &mut small_half, n);
         }
-        let mut super_graph = Self::build_super_graph(&big_half, &mut uf, graph.nodes().len());
-        res.append(&mut Self::recursive_search(&mut super_graph));
+        let (mut super_edges, super_n) = Self::build_super_graph(&big_half, &mut uf, n);
+        res.append(&mut Self::recursive_search(graph, &mut super_edges, super_n));
         res
     }
 
-    fn build_super_graph(big_half: &[Rc<RefCell<Edge>>], uf: &mut UF, n: usize) -> MutableGraph {
-        let mut nodes = Vec::new();
-        let mut edges = Vec::new();
+    /// Contracts the components of the small half, returns the edges of the big half between the contracted nodes
+    /// and the number of contracted nodes.
+    fn build_super_graph(big_half: &[SuperEdge], uf: &mut UF, n: usize) -> (Vec<SuperEdge>, usize) {
+        let mut edges = Vec::with_capacity(big_half.len());
         let mut keys = vec![(false, 0); n];
         let mut ids = 0;
         for edge in big_half {
-            let (u, v) = edge.borrow().original_endpoints();
-            let u_parent = uf.find(u);
-            let v_parent = uf.find(v);
+            let u_parent = uf.find(edge.either);
+            let v_parent = uf.find(edge.other);
             if !keys[u_parent].0 {
                 keys[u_parent] = (true, ids);
                 ids += 1;
-                nodes.push(Rc::new(Node::default(u)));
             }
             if !keys[v_parent].0 {
                 keys[v_parent] = (true, ids);
                 ids += 1;
-                nodes.push(Rc::new(Node::default(v)));
             }
-            edge.borrow_mut().set_original_endpoints_self(keys[u_parent].1, keys[v_parent].1);
-            edges.push(Rc::clone(edge));
+            edges.push(SuperEdge { id: edge.id, either: keys[u_parent].1, other: keys[v_parent].1 });
         }
-        MutableGraph::new(Rc::new(nodes), edges)
+        (edges, ids)
     }
 
-    fn find_bottleneck(st_edges: &[Rc<RefCell<Edge>>]) -> f64 {
-        let inverse = matches!(st_edges[0].borrow().get_weight(), w if w < 0.0);
+    fn find_bottleneck(graph: &MutableGraph, st_edges: &[EdgeId]) -> f64 {
+        let inverse = matches!(graph.weight(st_edges[0]), w if w < 0.0);
         let mut bottleneck = match inverse {
             true => f64::NEG_INFINITY,
             false => f64::INFINITY,
         };
-        st_edges.iter().for_each(|edge| {
-            bottleneck = Util::update_bottleneck(bottleneck, edge, inverse);
+        st_edges.iter().for_each(|&edge| {
+            bottleneck = Util::update_bottleneck(bottleneck, graph.weight(edge), inverse);
         });
         bottleneck
     }
 
-    fn compare_edge_ref(edge1: &Ref<Edge>, edge2: &Ref<Edge>) -> Ordering {
-        if edge1.get_weight() == edge2.get_weight() {
-            let (v1, w1) = edge1.original_endpoints();
-            let (v2, w2) = edge2.original_endpoints();
-            if v1 == v2 {
-                return w1.cmp(&w2);
+    fn compare_edges(graph: &MutableGraph, edge1: &SuperEdge, edge2: &SuperEdge) -> Ordering {
+        if graph.weight(edge1.id) == graph.weight(edge2.id) {
+            if edge1.either == edge2.either {
+                return edge1.other.cmp(&edge2.other);
             }
-            return v1.cmp(&v2);
+            return edge1.either.cmp(&edge2.either);
         }
-        edge1.get_weight().total_cmp(&edge2.get_weight())
+        graph.weight(edge1.id).total_cmp(&graph.weight(edge2.id))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::cell::RefCell;
-    use std::rc::Rc;
+    use std::sync::Arc;
     use crate::algorithms::min_bottleneck_spanning_tree::camerini::MBST;
     use crate::algorithms::min_sum_spanning_tree::kruskal::CalculationType;
     use crate::datastructures::graph::edge::Edge;
@@ -118,7 +138,7 @@ mod tests {
     fn test_mbst() {
         let mut nodes = Vec::new();
         for i in 0..8 {
-            nodes.push(Rc::new(Node::default(i)));
+            nodes.push(Node::default(i));
         }
         let mut edges = Vec::new();
         vec![
@@ -151,13 +171,12 @@ mod tests {
             (5, 7, 2.0),
             (6, 7, 1.0),
         ].iter().for_each(|(v, w, weight)| {
-            edges.push(Rc::new(RefCell::new(Edge::new(*v, *w).weight(*weight))));
+            edges.push(Edge::new(*v, *w).weight(*weight));
         });
-        let mut graph = MutableGraph::new(Rc::new(nodes), edges);
-        let (_, _, bottleneck_kruskal) = graph.mst(CalculationType::Weight);
-        let (st_cam, bottleneck_cam) = MBST::run(&mut graph);
-        assert!(st_cam.is_some());
-        assert!(st_cam.unwrap().is_spanning_tree());
+        let mut graph = MutableGraph::new(Arc::new(nodes), edges);
+        let (_, _, bottleneck_kruskal) = graph.mst(CalculationType::Weight).unwrap();
+        let (st_cam, bottleneck_cam) = MBST::run(&graph).unwrap();
+        assert!(st_cam.is_spanning_tree());
         assert_eq!(bottleneck_cam, bottleneck_kruskal);
     }
 
@@ -165,7 +184,7 @@ mod tests {
     fn test_negative_weights() {
         let mut nodes = Vec::new();
         for i in 0..8 {
-            nodes.push(Rc::new(Node::default(i)));
+            nodes.push(Node::default(i));
         }
         let mut edges = Vec::new();
         vec![
@@ -198,13 +217,12 @@ mod tests {
             (5, 7, -2.0),
             (6, 7, -1.0),
         ].iter().for_each(|(v, w, weight)| {
-            edges.push(Rc::new(RefCell::new(Edge::new(*v, *w).weight(*weight))));
+            edges.push(Edge::new(*v, *w).weight(*weight));
         });
-        let mut graph = MutableGraph::new(Rc::new(nodes), edges);
-        let (_, _, bottleneck_kruskal) = graph.mst(CalculationType::Weight);
-        let (st_cam, bottleneck_cam) = MBST::run(&mut graph);
-        assert!(st_cam.is_some());
-        assert!(st_cam.unwrap().is_spanning_tree());
+        let mut graph = MutableGraph::new(Arc::new(nodes), edges);
+        let (_, _, bottleneck_kruskal) = graph.mst(CalculationType::Weight).unwrap();
+        let (st_cam, bottleneck_cam) = MBST::run(&graph).unwrap();
+        assert!(st_cam.is_spanning_tree());
         assert_eq!(bottleneck_cam, bottleneck_kruskal);
         assert_eq!(bottleneck_cam, -4.0);
     }
@@ -213,7 +231,7 @@ mod tests {
     fn test_mutable_graph() {
         let mut nodes = Vec::new();
         for i in 0..8 {
-            nodes.push(Rc::new(Node::default(i)));
+            nodes.push(Node::default(i));
         }
         let mut edges = Vec::new();
         vec![
@@ -246,11 +264,14 @@ mod tests {
             (5, 7, 2.0),
             (6, 7, 1.0),
         ].iter().for_each(|(v, w, weight)| {
-            edges.push(Rc::new(RefCell::new(Edge::new(*v, *w).weight(*weight))));
+            edges.push(Edge::new(*v, *w).weight(*weight));
         });
-        let mut graph = MutableGraph::new(Rc::new(nodes), edges);
-        let (st_cam, bottleneck_cam) = MBST::run(&mut graph);
-        assert!(st_cam.is_some());
+        let graph = MutableGraph::new(Arc::new(nodes), edges);
+        let (st_cam, bottleneck_cam) = MBST::run(&graph).unwrap();
+        assert!(st_cam.is_spanning_tree());
         assert_eq!(bottleneck_cam, 1.0);
+        assert_eq!(graph.edges(), &(0..28).collect::<Vec<_>>());
+        let (_, bottleneck_second_run) = MBST::run(&graph).unwrap();
+        assert_eq!(bottleneck_second_run, 1.0);
     }
 }
\ No newline at end of file
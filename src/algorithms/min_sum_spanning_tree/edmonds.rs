@@ -0,0 +1,133 @@
+use log::trace;
+use crate::datastructures::graph::edge_store::EdgeId;
+use crate::datastructures::graph::mutable_graph::MutableGraph;
+use crate::error::CbstuError;
+
+/// Arc of the (contracted) graph as (tail, head, cost).
+type DirectedArc = (usize, usize, f64);
+
+// Algorithm of Chu-Liu and Edmonds for minimum-cost arborescences
+pub struct Edmonds();
+
+impl Edmonds {
+    /// Returns a min-cost arborescence of the given graph rooted at `root`, its total cost and the bottleneck WEIGHT
+    /// (not cost) of the tree. Directed edges run from `either` to `other`, undirected edges can be used either way.
+    pub fn run(graph: &MutableGraph, root: usize) -> Result<(MutableGraph, f64, f64), CbstuError> {
+        if graph.edges().is_empty() {
+            return Err(CbstuError::EmptyEdgeSet);
+        }
+        let mut arcs = Vec::with_capacity(2 * graph.edges().len());
+        let mut origin: Vec<EdgeId> = Vec::with_capacity(2 * graph.edges().len());
+        for &edge in graph.edges() {
+            let (u, v) = graph.endpoints(edge);
+            arcs.push((u, v, graph.cost(edge)));
+            origin.push(edge);
+            if !graph.is_directed(edge) {
+                arcs.push((v, u, graph.cost(edge)));
+                origin.push(edge);
+            }
+        }
+        let chosen = Self::arborescence(graph.nodes().len(), root, &arcs).ok_or(CbstuError::DisconnectedGraph)?;
+        let tree = graph.subgraph(chosen.iter().map(|&arc| origin[arc]).collect());
+        let cost = tree.calculate_total_cost();
+        let bottleneck = tree.edges().iter().map(|&edge| tree.weight(edge)).fold(f64::NEG_INFINITY, f64::max);
+        trace!("Arborescence found [cost: {}, bottleneck: {}]", cost, bottleneck);
+        Ok((tree, cost, bottleneck))
+    }
+
+    /// Positions in `arcs` of a min-cost arborescence over nodes `0..n`, `None` if some node cannot be reached from
+    /// the root. Every node takes its cheapest incoming arc; the cycles among those are contracted, with the cost of
+    /// an arc entering a cycle lowered by the arc it replaces, and the arborescence of the contracted graph is expanded
+    /// by the cycle arcs that are not replaced.
+    fn arborescence(n: usize, root: usize, arcs: &[DirectedArc]) -> Option<Vec<usize>> {
+        let mut cheapest: Vec<Option<usize>> = vec![None; n];
+        for (i, &(u, v, cost)) in arcs.iter().enumerate() {
+            if u != v && v != root && cheapest[v].map_or(true, |best| cost < arcs[best].2) {
+                cheapest[v] = Some(i);
+            }
+        }
+        if (0..n).any(|v| v != root && cheapest[v].is_none()) {
+            return None;
+        }
+        let incoming = |v: usize| cheapest[v].unwrap();
+        // nodes on a cycle of cheapest arcs share a component, all other nodes get one of their own
+        let mut component = vec![usize::MAX; n];
+        let mut on_path = vec![usize::MAX; n];
+        let mut cycle = Vec::new();
+        for start in 0..n {
+            let mut v = start;
+            while v != root && on_path[v] == usize::MAX && component[v] == usize::MAX {
+                on_path[v] = start;
+                v = arcs[incoming(v)].0;
+            }
+            if v != root && on_path[v] == start && component[v] == usize::MAX {
+                let mut u = v;
+                loop {
+                    component[u] = cycle.len();
+                    u = arcs[incoming(u)].0;
+                    if u == v {
+                        break;
+                    }
+                }
+                cycle.push(true);
+            }
+        }
+        if cycle.is_empty() {
+            return Some((0..n).filter(|&v| v != root).map(incoming).collect());
+        }
+        for v in 0..n {
+            if component[v] == usize::MAX {
+                component[v] = cycle.len();
+                cycle.push(false);
+            }
+        }
+        let mut contracted = Vec::with_capacity(arcs.len());
+        let mut origin = Vec::with_capacity(arcs.len());
+        for (i, &(u, v, cost)) in arcs.iter().enumerate() {
+            if component[u] != component[v] {
+                let replaced = if cycle[component[v]] { arcs[incoming(v)].2 } else { 0.0 };
+                contracted.push((component[u], component[v], cost - replaced));
+                origin.push(i);
+            }
+        }
+        let mut chosen: Vec<usize> = Self::arborescence(cycle.len(), component[root], &contracted)?
+            .into_iter()
+            .map(|arc| origin[arc])
+            .collect();
+        let mut entered = vec![false; n];
+        chosen.iter().for_each(|&arc| entered[arcs[arc].1] = true);
+        chosen.extend((0..n).filter(|&v| cycle[component[v]] && !entered[v]).map(incoming));
+        Some(chosen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::algorithms::min_sum_spanning_tree::edmonds::Edmonds;
+    use crate::datastructures::graph::edge::Edge;
+    use crate::datastructures::graph::mutable_graph::MutableGraph;
+    use crate::datastructures::graph::node::Node;
+    use crate::error::CbstuError;
+
+    #[test]
+    fn test_min_cost_arborescence() {
+        let nodes = Arc::new((0..4).map(Node::default).collect::<Vec<Node>>());
+        // the cheap arcs 1 -> 2 -> 3 -> 1 form a cycle that has to be entered from the root
+        let arcs = vec![(0, 1, 10.0), (0, 2, 12.0), (0, 3, 11.0), (1, 2, 1.0), (2, 3, 2.0), (3, 1, 3.0), (2, 1, 6.0)];
+        let edges = arcs.iter().map(|&(u, v, c)| Edge::new(u, v).weight(-c).cost(c).directed(true)).collect();
+        let graph = MutableGraph::new(Arc::clone(&nodes), edges);
+        let (tree, cost, _) = Edmonds::run(&graph, 0).unwrap();
+        assert_eq!(cost, 13.0);
+        assert_eq!(tree.edges().len(), 3);
+        let mut endpoints: Vec<(usize, usize)> = tree.edges().iter().map(|&edge| tree.endpoints(edge)).collect();
+        endpoints.sort();
+        assert_eq!(endpoints, vec![(0, 1), (1, 2), (2, 3)]);
+        // from node 1 the root cannot be reached
+        assert!(matches!(Edmonds::run(&graph, 1), Err(CbstuError::DisconnectedGraph)));
+        // undirected edges can be used both ways
+        let edges = arcs.iter().map(|&(u, v, c)| Edge::new(u, v).weight(-c).cost(c)).collect();
+        let (_, cost, _) = Edmonds::run(&MutableGraph::new(nodes, edges), 1).unwrap();
+        assert_eq!(cost, 13.0);
+    }
+}
@@ -1,11 +1,10 @@
-use std::cell::RefCell;
-use std::rc::Rc;
 use log::{debug, error, info, trace};
 use crate::algorithms::min_sum_spanning_tree::kruskal::ConnectionType::{Connected, Disconnected};
 use crate::algorithms::util::Util;
-use crate::datastructures::graph::edge::Edge;
+use crate::datastructures::graph::edge_store::EdgeId;
 use crate::datastructures::graph::mutable_graph::MutableGraph;
 use crate::datastructures::uf::union_find::UF;
+use crate::error::CbstuError;
 use crate::print_edges;
 
 const FLOATING_POINT_EPSILON: f64 = 1.0E-12;
@@ -24,46 +23,64 @@ pub struct Kruskal();
 
 impl Kruskal {
     /// Returns a minimal spanning tree of the given graph, the total weight/cost of the tree and the bottleneck WEIGHT (not cost) of the tree.
-    pub fn run(graph: &mut MutableGraph, calculation_type: CalculationType) -> (Option<MutableGraph>, f64, f64) {
+    pub fn run(graph: &MutableGraph, calculation_type: CalculationType) -> Result<(MutableGraph, f64, f64), CbstuError> {
+        if graph.edges().is_empty() {
+            return Err(CbstuError::EmptyEdgeSet);
+        }
         let start = std::time::Instant::now();
-        Self::sort_by_calculation_type(graph, &calculation_type);
+        let edges = Self::sorted_by_calculation_type(graph, &calculation_type);
         let end = start.elapsed().as_nanos() as f64 / 1_000_000.0;
-        trace!("Kruskal sorting over {} edges took {} ms", graph.edges().len(), end);
-        Self::sorted_build(graph, calculation_type)
+        trace!("Kruskal sorting over {} edges took {} ms", edges.len(), end);
+        Self::sorted_build(graph, &edges, calculation_type)
     }
 
     /// Returns a minimal spanning tree of the given graph, the total weight/cost of the tree and the bottleneck WEIGHT (not cost) of the tree.
     /// If the graph is not connected, the minimum spanning forest is returned.
-    pub fn run_with_disconnected(graph: &mut MutableGraph, calculation_type: CalculationType) -> (ConnectionType, MutableGraph, f64, f64) {
+    pub fn run_with_disconnected(graph: &MutableGraph, calculation_type: CalculationType) -> Result<(ConnectionType, MutableGraph, f64, f64), CbstuError> {
+        if graph.edges().is_empty() {
+            return Err(CbstuError::EmptyEdgeSet);
+        }
         let start = std::time::Instant::now();
-        Self::sort_by_calculation_type(graph, &calculation_type);
+        let edges = Self::sorted_by_calculation_type(graph, &calculation_type);
         let end = start.elapsed().as_nanos() as f64 / 1_000_000.0;
-        trace!("Kruskal sorting over {} edges took {} ms", graph.edges().len(), end);
-        Self::sorted_build_with_disconnected_components(graph, calculation_type)
+        trace!("Kruskal sorting over {} edges took {} ms", edges.len(), end);
+        Ok(Self::sorted_build_with_disconnected_components(graph, &edges, calculation_type))
+    }
+
+    /// Returns a spanning tree built from the edges in the given order, for orders Kruskal does not sort on itself such as
+    /// a combination of weight and cost, the total weight/cost of the tree and the bottleneck WEIGHT (not cost) of the tree.
+    pub fn run_in_order(graph: &MutableGraph, edges: &[EdgeId], calculation_type: CalculationType) -> Result<(MutableGraph, f64, f64), CbstuError> {
+        if edges.is_empty() {
+            return Err(CbstuError::EmptyEdgeSet);
+        }
+        Self::sorted_build(graph, edges, calculation_type)
     }
 
-    fn sort_by_calculation_type(graph: &mut MutableGraph, calculation_type: &CalculationType) {
+    /// Sorts a copy of the edge list, the order of the edges in the graph itself is left untouched.
+    /// Cost ties are broken on weight, so of all cheapest trees the one with the best bottleneck is built.
+    fn sorted_by_calculation_type(graph: &MutableGraph, calculation_type: &CalculationType) -> Vec<EdgeId> {
+        let mut edges = graph.edges_copy();
         match calculation_type {
-            CalculationType::Cost => graph.edges_mut().sort_by(|a, b| a.borrow().get_cost().partial_cmp(&b.borrow().get_cost()).unwrap()),
-            CalculationType::Weight => graph.edges_mut().sort_by(|a, b| a.borrow().get_weight().partial_cmp(&b.borrow().get_weight()).unwrap()),
+            CalculationType::Cost => edges.sort_by(|&a, &b| graph.cost(a).partial_cmp(&graph.cost(b)).unwrap()
+                .then_with(|| graph.weight(a).partial_cmp(&graph.weight(b)).unwrap())),
+            CalculationType::Weight => edges.sort_by(|&a, &b| graph.weight(a).partial_cmp(&graph.weight(b)).unwrap()),
         }
+        edges
     }
 
-    fn sorted_build(graph: &mut MutableGraph, calculation_type: CalculationType) -> (Option<MutableGraph>, f64, f64) {
-        let (uf, st_edges, mut weight, mut bottleneck) = Self::construct(graph, calculation_type);
+    fn sorted_build(graph: &MutableGraph, edges: &[EdgeId], calculation_type: CalculationType) -> Result<(MutableGraph, f64, f64), CbstuError> {
+        let (uf, st_edges, weight, bottleneck) = Self::construct(graph, edges, calculation_type);
         if uf.count() > 1 {
-            weight = f64::INFINITY;
-            bottleneck = f64::INFINITY;
-            return (None, weight, bottleneck);
+            return Err(CbstuError::DisconnectedGraph);
         }
-        let st = MutableGraph::new(graph.nodes_copy(), st_edges);
+        let st = graph.subgraph(st_edges);
         debug_assert!(st.is_spanning_tree());
-        (Some(st), weight, bottleneck)
+        Ok((st, weight, bottleneck))
     }
 
-    fn sorted_build_with_disconnected_components(graph: &mut MutableGraph, calculation_type: CalculationType) -> (ConnectionType, MutableGraph, f64, f64) {
-        let (uf, st_edges, mut weight, mut bottleneck) = Self::construct(graph, calculation_type);
-        let st = MutableGraph::new(graph.nodes_copy(), st_edges);
+    fn sorted_build_with_disconnected_components(graph: &MutableGraph, edges: &[EdgeId], calculation_type: CalculationType) -> (ConnectionType, MutableGraph, f64, f64) {
+        let (uf, st_edges, mut weight, mut bottleneck) = Self::construct(graph, edges, calculation_type);
+        let st = graph.subgraph(st_edges);
         if uf.count() > 1 {
             trace!("Kruskal found {} disconnected components", uf.count());
             return (Disconnected, st, weight, bottleneck);
@@ -72,28 +89,28 @@ impl Kruskal {
         (Connected, st, weight, bottleneck)
     }
 
-    fn construct(graph: &mut MutableGraph, calculation_type: CalculationType) -> (UF, Vec<Rc<RefCell<Edge>>>, f64, f64) {
+    fn construct(graph: &MutableGraph, edges: &[EdgeId], calculation_type: CalculationType) -> (UF, Vec<EdgeId>, f64, f64) {
         let mut st_edges = Vec::new();
         let mut weight = 0.0;
-        let inverse = matches!(graph.edges()[0].borrow().get_weight(), w if w < 0.0);
+        let inverse = matches!(graph.weight(edges[0]), w if w < 0.0);
         let mut bottleneck = match inverse {
             true => f64::NEG_INFINITY,
             false => f64::INFINITY,
         };
         let mut uf = UF::new(graph.nodes().len() as i32);
-        for edge in graph.edges() {
-            let (v, w) = edge.borrow().endpoints();
+        for &edge in edges {
+            let (v, w) = graph.endpoints(edge);
             if !uf.connected(v, w) {
                 uf.union(v, w);
-                st_edges.push(Rc::clone(edge));
+                st_edges.push(edge);
                 match calculation_type {
                     CalculationType::Cost => {
-                        weight += edge.borrow().get_cost();
-                        bottleneck = Util::update_bottleneck(bottleneck, edge, inverse);
+                        weight += graph.cost(edge);
+                        bottleneck = Util::update_bottleneck(bottleneck, graph.weight(edge), inverse);
                     }
                     CalculationType::Weight => {
-                        weight += edge.borrow().get_weight();
-                        bottleneck = Util::update_bottleneck(bottleneck, edge, inverse);
+                        weight += graph.weight(edge);
+                        bottleneck = Util::update_bottleneck(bottleneck, graph.weight(edge), inverse);
                     }
                 }
             }
@@ -109,10 +126,10 @@ impl Kruskal {
         }
         //check weight
         let mut total_weight = 0.0;
-        for edge in st.edges() {
+        for &edge in st.edges() {
             match calculation_type {
-                CalculationType::Cost => total_weight += edge.borrow().get_cost(),
-                CalculationType::Weight => total_weight += edge.borrow().get_weight(),
+                CalculationType::Cost => total_weight += st.cost(edge),
+                CalculationType::Weight => total_weight += st.weight(edge),
             }
         }
         if (weight - total_weight).abs() > FLOATING_POINT_EPSILON {
@@ -122,8 +139,8 @@ impl Kruskal {
         debug!("Weight of MST is valid");
         // check that it is acyclic
         let mut uf = UF::new(st.nodes().len() as i32);
-        for edge in st.edges() {
-            let (v, w) = edge.borrow().endpoints();
+        for &edge in st.edges() {
+            let (v, w) = st.endpoints(edge);
             if uf.connected(v, w) {
                 error!("Not a forest");
                 return false;
@@ -134,34 +151,34 @@ impl Kruskal {
         // check that it is a minimal spanning forest (cut optimality conditions)
         debug!("Checking cut optimality conditions");
         let mut i = 0;
-        for edge in st.edges() {
+        for &edge in st.edges() {
             i += 1;
             if i % 100 == 0 {
                 trace!("Progress: {}/{}", i, st.edges().len());
             }
             // all edges in MST except e
             uf = UF::new(st.nodes().len() as i32);
-            for e in st.edges() {
-                if Rc::ptr_eq(e, edge) {
+            for &e in st.edges() {
+                if e == edge {
                     continue;
                 }
-                let (v, w) = e.borrow().endpoints();
+                let (v, w) = st.endpoints(e);
                 uf.union(v, w);
             }
             // check that e is min weight edge in crossing cut
-            for e in st.edges() {
-                let (v, w) = e.borrow().endpoints();
+            for &e in st.edges() {
+                let (v, w) = st.endpoints(e);
                 if !uf.connected(v, w) {
                     match calculation_type {
                         CalculationType::Cost => {
-                            if e.borrow().get_cost() < edge.borrow().get_cost() {
-                                error!("Edge {:?} violates cut optimality conditions", edge);
+                            if st.cost(e) < st.cost(edge) {
+                                error!("Edge {:?} violates cut optimality conditions", st.edge(edge));
                                 return false;
                             }
                         }
                         CalculationType::Weight => {
-                            if e.borrow().get_weight() < edge.borrow().get_weight() {
-                                error!("Edge {:?} violates cut optimality conditions", edge);
+                            if st.weight(e) < st.weight(edge) {
+                                error!("Edge {:?} violates cut optimality conditions", st.edge(edge));
                                 return false;
                             }
                         }
@@ -175,7 +192,7 @@ impl Kruskal {
 
 #[cfg(test)]
 mod tests {
-    use std::cell::RefCell;
+    use std::sync::Arc;
     use super::*;
     use crate::datastructures::graph::edge::Edge;
     use crate::datastructures::graph::node::Node;
@@ -184,7 +201,7 @@ mod tests {
     fn test_kruskal() {
         let mut nodes = Vec::new();
         for i in 0..8 {
-            nodes.push(Rc::new(Node::default(i)));
+            nodes.push(Node::default(i));
         }
         let mut edges = Vec::new();
         vec![
@@ -217,12 +234,11 @@ mod tests {
             (5, 7, 1.0),
             (6, 7, 1.0),
         ].iter().for_each(|(v, w, weight)| {
-            edges.push(Rc::new(RefCell::new(Edge::new(*v, *w).weight(*weight))));
+            edges.push(Edge::new(*v, *w).weight(*weight));
         });
-        let mut graph = MutableGraph::new(Rc::new(nodes), edges);
-        let (st, weight, bottleneck) = Kruskal::run(&mut graph, CalculationType::Weight);
-        assert!(st.is_some());
-        assert!(st.unwrap().is_spanning_tree());
+        let mut graph = MutableGraph::new(Arc::new(nodes), edges);
+        let (st, weight, bottleneck) = Kruskal::run(&graph, CalculationType::Weight).unwrap();
+        assert!(st.is_spanning_tree());
         assert_eq!(weight, 7.0);
         assert_eq!(bottleneck, 1.0);
     }
@@ -231,7 +247,7 @@ mod tests {
     fn test_kruskal2() {
         let mut nodes = Vec::new();
         for i in 0..=8 {
-            nodes.push(Rc::new(Node::default(i)));
+            nodes.push(Node::default(i));
         }
         let mut edges_mut = Vec::new();
         vec![
@@ -250,13 +266,12 @@ mod tests {
             (1, 7, 11.0),
             (3, 5, 14.0),
         ].iter().for_each(|(v, w, weight)| {
-            edges_mut.push(Rc::new(RefCell::new(Edge::new(*v, *w).weight(*weight))))
+            edges_mut.push(Edge::new(*v, *w).weight(*weight))
         });
-        let nodes_rc = Rc::new(nodes);
-        let mut graph_mut = MutableGraph::new(Rc::clone(&nodes_rc), edges_mut);
-        let (st_mut, weight_mut, bottleneck_mut) = Kruskal::run(&mut graph_mut, CalculationType::Weight);
-        assert!(st_mut.is_some());
-        assert!(st_mut.unwrap().is_spanning_tree());
+        let nodes_rc = Arc::new(nodes);
+        let mut graph_mut = MutableGraph::new(Arc::clone(&nodes_rc), edges_mut);
+        let (st_mut, weight_mut, bottleneck_mut) = Kruskal::run(&graph_mut, CalculationType::Weight).unwrap();
+        assert!(st_mut.is_spanning_tree());
         assert_eq!(bottleneck_mut, 1.0);
         assert_eq!(weight_mut, 37.0);
     }
@@ -265,7 +280,7 @@ mod tests {
     fn test_kruskal3() {
         let mut nodes = Vec::new();
         for i in 0..=8 {
-            nodes.push(Rc::new(Node::default(i)));
+            nodes.push(Node::default(i));
         }
         let mut edges = Vec::new();
         vec![
@@ -284,12 +299,24 @@ mod tests {
             (1, 7, 11.0),
             (3, 5, 14.0),
         ].iter().for_each(|(v, w, weight)| {
-            edges.push(Rc::new(RefCell::new(Edge::new(*v, *w).cost(*weight))));
+            edges.push(Edge::new(*v, *w).cost(*weight));
         });
-        let mut graph = MutableGraph::new(Rc::new(nodes), edges);
-        let (st, weight, bottleneck) = Kruskal::run(&mut graph, CalculationType::Cost);
-        assert!(st.is_some());
-        assert!(st.unwrap().is_spanning_tree());
+        let mut graph = MutableGraph::new(Arc::new(nodes), edges);
+        let order: Vec<f64> = graph.edges().iter().map(|&edge| graph.cost(edge)).collect();
+        let (st, weight, bottleneck) = Kruskal::run(&graph, CalculationType::Cost).unwrap();
+        assert!(st.is_spanning_tree());
         assert_eq!(weight, 37.0);
+        let order_after: Vec<f64> = graph.edges().iter().map(|&edge| graph.cost(edge)).collect();
+        assert_eq!(order, order_after);
+    }
+
+    #[test]
+    fn test_kruskal_errors() {
+        let nodes: Vec<Node> = (0..3).map(Node::default).collect();
+        let edges = vec![Edge::new(0, 1).weight(1.0)];
+        let mut graph = MutableGraph::new(Arc::new(nodes), edges);
+        assert!(matches!(Kruskal::run(&graph, CalculationType::Weight), Err(CbstuError::DisconnectedGraph)));
+        graph.edges_mut().clear();
+        assert!(matches!(Kruskal::run(&graph, CalculationType::Weight), Err(CbstuError::EmptyEdgeSet)));
     }
 }
@@ -1 +1,3 @@
-pub mod kruskal;
\ No newline at end of file
+pub mod kruskal;
+pub mod edmonds;
+pub mod upgrades;
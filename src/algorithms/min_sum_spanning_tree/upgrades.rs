@@ -0,0 +1,224 @@
+use std::cell::Cell;
+use log::{debug, trace, warn};
+use crate::algorithms::constrained_bottleneck_spanning_tree::constraints::Constraints;
+use crate::algorithms::min_sum_spanning_tree::kruskal::{CalculationType, Kruskal};
+use crate::algorithms::util::Util;
+use crate::datastructures::graph::edge_store::EdgeId;
+use crate::datastructures::graph::mutable_graph::MutableGraph;
+use crate::error::CbstuError;
+
+/// Above this number of usable edges the gap left by the Lagrangian relaxation and local search is not closed.
+const EXACT_LIMIT: usize = 30;
+/// Bisection steps on the multiplier of the budget.
+const ITERATIONS: usize = 60;
+/// Gap, relative to the weight of the tree, below which a solution counts as optimal.
+const GAP_EPSILON: f64 = 1.0E-9;
+
+/// Result of the min-sum variant, in the weights of the solved graph. `bound` is a lower bound on the total weight of
+/// any spanning tree within budget, equal to `weight` when the solution is proven optimal.
+#[derive(Debug, Clone)]
+pub struct MinSumSolution {
+    tree: MutableGraph,
+    weight: f64,
+    cost: f64,
+    bound: f64,
+}
+
+impl MinSumSolution {
+    /// Solution for `tree` with a lower bound on the weight of every tree within budget, capped at the weight of the
+    /// tree.
+    fn new(tree: MutableGraph, bound: f64) -> MinSumSolution {
+        let weight = tree.edges().iter().map(|&edge| tree.weight(edge)).sum::<f64>();
+        let solution = MinSumSolution { cost: tree.calculate_total_cost(), tree, weight, bound: bound.min(weight) };
+        if !solution.is_optimal() {
+            warn!("Solution not proven optimal [weight: {}, bound: {}]", solution.weight, solution.bound);
+        }
+        solution
+    }
+
+    pub fn tree(&self) -> &MutableGraph {
+        &self.tree
+    }
+
+    pub fn weight(&self) -> f64 {
+        self.weight
+    }
+
+    pub fn cost(&self) -> f64 {
+        self.cost
+    }
+
+    pub fn bound(&self) -> f64 {
+        self.bound
+    }
+
+    /// Difference between the weight of the tree and the lower bound.
+    pub fn gap(&self) -> f64 {
+        self.weight - self.bound
+    }
+
+    pub fn is_optimal(&self) -> bool {
+        self.gap() <= GAP_EPSILON * self.weight.abs().max(1.0)
+    }
+}
+
+/// Min-sum counterpart of CBSTU: upgrades within the budget that minimize the total weight of the spanning tree.
+pub struct MinSumUpgrades();
+
+impl MinSumUpgrades {
+    /// Works on the duplicated graph under its link constraints. Relaxing the budget with a multiplier leaves a
+    /// minimum spanning tree on weight plus multiplier times cost, built by Kruskal; bisection on the multiplier gives
+    /// the Lagrangian lower bound and the trees on both sides of the budget. The best tree within budget is improved by
+    /// edge swaps, and on graphs with at most `EXACT_LIMIT` usable edges branch and bound closes the remaining gap.
+    pub fn solve(graph: &MutableGraph, budget: f64) -> Result<MinSumSolution, CbstuError> {
+        trace!("Solving min-sum spanning tree problem with upgrades");
+        let allowed = graph.subgraph(Constraints::allowed_edges(graph)?);
+        let graph = &allowed;
+        let weight = |tree: &[EdgeId]| tree.iter().map(|&edge| graph.weight(edge)).sum::<f64>();
+        let cost = |tree: &[EdgeId]| tree.iter().map(|&edge| graph.cost(edge)).sum::<f64>();
+        let mut best = Self::kruskal(graph, |edge| graph.cost(edge))?;
+        if cost(&best) > budget {
+            return Err(CbstuError::InfeasibleBudget);
+        }
+        let lightest = Self::kruskal(graph, |edge| graph.weight(edge))?;
+        let mut bound = weight(&lightest);
+        if cost(&lightest) <= budget {
+            best = lightest;
+        } else {
+            let mut relax = |multiplier: f64| -> Result<bool, CbstuError> {
+                let tree = Self::kruskal(graph, |edge| graph.weight(edge) + multiplier * graph.cost(edge))?;
+                bound = bound.max(weight(&tree) + multiplier * (cost(&tree) - budget));
+                let fits = cost(&tree) <= budget;
+                if fits && weight(&tree) < weight(&best) {
+                    best = tree;
+                }
+                Ok(fits)
+            };
+            let mut low = 0.0;
+            let mut high = 1.0;
+            for _ in 0..ITERATIONS {
+                if relax(high)? {
+                    break;
+                }
+                low = high;
+                high *= 2.0;
+            }
+            for _ in 0..ITERATIONS {
+                let multiplier = (low + high) / 2.0;
+                match relax(multiplier)? {
+                    true => high = multiplier,
+                    false => low = multiplier,
+                }
+            }
+            debug!("Lagrangian relaxation [multiplier: {}, weight: {}, bound: {}]", high, weight(&best), bound);
+            best = Self::improve(graph, budget, best);
+            if weight(&best) - bound > GAP_EPSILON * weight(&best).abs().max(1.0) && graph.edges().len() <= EXACT_LIMIT {
+                let required = Util::required_links(graph);
+                let mut order = graph.edges_copy();
+                order.sort_by(|&a, &b| graph.is_required(b).cmp(&graph.is_required(a)).then(graph.weight(a).total_cmp(&graph.weight(b))));
+                // search for trees lighter than the incumbent: a branch is cut when its cheapest completion does not
+                // fit or its lightest completion is no lighter
+                let incumbent = Cell::new(weight(&best));
+                let promising = |chosen: &[EdgeId], undecided: &[EdgeId]| {
+                    let cheapest = Util::completion(graph, chosen, undecided.iter().copied(), |edge| graph.cost(edge));
+                    let lightest = Util::completion(graph, chosen, undecided.iter().copied(), |edge| graph.weight(edge));
+                    matches!((cheapest, lightest), (Some(cheapest), Some(lightest)) if cost(chosen) + cheapest <= budget && weight(chosen) + lightest < incumbent.get())
+                };
+                Util::branch_and_bound(graph, &order, required, |_, _| true, promising, |tree| {
+                    if cost(tree) <= budget && weight(tree) < incumbent.get() {
+                        incumbent.set(weight(tree));
+                        best = tree.to_vec();
+                    }
+                    false
+                });
+                bound = weight(&best);
+            }
+        }
+        Ok(MinSumSolution::new(graph.subgraph(best), bound))
+    }
+
+    /// Kruskal with the required edges first and the others on `key`, ties broken on cost and then on weight.
+    fn kruskal(graph: &MutableGraph, key: impl Fn(EdgeId) -> f64) -> Result<Vec<EdgeId>, CbstuError> {
+        let mut order = graph.edges_copy();
+        order.sort_by(|&a, &b| graph.is_required(b).cmp(&graph.is_required(a))
+            .then(key(a).total_cmp(&key(b)))
+            .then(graph.cost(a).total_cmp(&graph.cost(b)))
+            .then(graph.weight(a).total_cmp(&graph.weight(b))));
+        let (tree, _, _) = Kruskal::run_in_order(graph, &order, CalculationType::Weight)?;
+        Ok(tree.edges_copy())
+    }
+
+    /// Local search on swaps of a tree edge for a lighter edge that closes a cycle with it, keeping the budget and the
+    /// required links. Takes the best swap until none is left.
+    fn improve(graph: &MutableGraph, budget: f64, mut tree: Vec<EdgeId>) -> Vec<EdgeId> {
+        loop {
+            let spent: f64 = tree.iter().map(|&edge| graph.cost(edge)).sum();
+            let mut best_swap = None;
+            let mut best_gain = 0.0;
+            for &edge in graph.edges() {
+                if tree.contains(&edge) {
+                    continue;
+                }
+                let (u, v) = graph.endpoints(edge);
+                for position in Util::tree_path(graph, &tree, u, v) {
+                    let removed = tree[position];
+                    let gain = graph.weight(removed) - graph.weight(edge);
+                    let same_link = graph.endpoints(removed) == (u, v) || graph.endpoints(removed) == (v, u);
+                    if gain > best_gain && spent - graph.cost(removed) + graph.cost(edge) <= budget && (!graph.is_required(removed) || same_link) {
+                        best_gain = gain;
+                        best_swap = Some((position, edge));
+                    }
+                }
+            }
+            match best_swap {
+                Some((position, edge)) => tree[position] = edge,
+                None => return tree,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithms::constrained_bottleneck_spanning_tree::test_graphs::latency_graph;
+    use crate::algorithms::min_sum_spanning_tree::kruskal::CalculationType;
+    use crate::algorithms::min_sum_spanning_tree::upgrades::MinSumUpgrades;
+    use crate::error::CbstuError;
+
+    #[test]
+    fn test_square() {
+        let graph = latency_graph(4, &[(0, 1, 5.0, 2.0, 4.0), (1, 2, 4.0, 1.0, 3.0), (2, 3, 6.0, 3.0, 5.0), (3, 0, 3.0, 1.0, 2.0), (0, 2, 7.0, 2.0, 6.0)]);
+        for (budget, weight, cost) in [(0.0, 12.0, 0.0), (2.0, 10.0, 2.0), (5.0, 7.0, 5.0), (7.0, 6.0, 7.0), (9.0, 4.0, 9.0), (100.0, 4.0, 9.0)] {
+            let solution = MinSumUpgrades::solve(&graph, budget).unwrap();
+            assert_eq!((solution.weight(), solution.cost()), (weight, cost), "budget {}", budget);
+            assert!(solution.is_optimal());
+            assert!(solution.tree().is_spanning_tree());
+        }
+        assert!(matches!(MinSumUpgrades::solve(&graph, -1.0), Err(CbstuError::InfeasibleBudget)));
+    }
+
+    #[test]
+    fn test_gap_on_large_grid() {
+        // a 6x6 grid has 60 links, 120 edges after duplication, beyond the exact search
+        let mut links = Vec::new();
+        for v in 0..36 {
+            let k = (v * 7 % 11 + 2) as f64;
+            if v % 6 < 5 {
+                links.push((v, v + 1, k, k / 2.0, (v % 5 + 1) as f64));
+            }
+            if v < 30 {
+                links.push((v, v + 6, k + 1.0, 1.0, (v % 3 + 2) as f64));
+            }
+        }
+        let graph = latency_graph(36, &links);
+        let (_, unupgraded, _) = graph.subgraph(graph.edges().iter().copied().filter(|&edge| graph.cost(edge) == 0.0).collect()).mst(CalculationType::Weight).unwrap();
+        for budget in [5.0, 20.0, 60.0] {
+            let solution = MinSumUpgrades::solve(&graph, budget).unwrap();
+            assert!(solution.tree().is_spanning_tree());
+            assert!(solution.cost() <= budget);
+            assert!(solution.bound() <= solution.weight());
+            assert!(solution.gap() >= 0.0);
+            assert!(solution.weight() <= unupgraded, "budget {}", budget);
+        }
+    }
+}
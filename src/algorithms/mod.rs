@@ -2,4 +2,5 @@ pub mod min_sum_spanning_tree;
 pub mod min_bottleneck_spanning_tree;
 pub mod quick_select;
 pub mod constrained_bottleneck_spanning_tree;
+pub mod continuous_bottleneck_spanning_tree;
 pub mod util;
\ No newline at end of file
@@ -1,4 +1,3 @@
-use std::cell::RefCell;
 use std::rc::Rc;
 use rand::{random, Rng};
 use crate::datastructures::graph::edge::Edge;
@@ -22,27 +21,29 @@ impl QuickSelect {
         }
         Rc::clone(&array[median])
     }
-    pub fn find_median_edges_mut(array: &mut [Rc<RefCell<Edge>>]) -> Rc<RefCell<Edge>> {
+    /// Median of items ordered by the given weight, used for edge ids and other edge handles that only know their
+    /// weight through the graph.
+    pub fn find_median_by_weight<T: Copy>(array: &mut [T], weight: impl Fn(&T) -> f64) -> T {
         let mut left = 0;
         let mut right = array.len() - 1;
         let median = (left + right) / 2;
         while left < right {
-            let pivot_index = QuickSelect::partition_edges_mut(array, left, right);
+            let pivot_index = QuickSelect::partition_by_weight(array, left, right, &weight);
             match pivot_index {
                 i if i < median => left = i + 1,
                 i if i > median => right = i - 1,
-                _ => return Rc::clone(&array[median]),
+                _ => return array[median],
             }
         }
-        Rc::clone(&array[median])
+        array[median]
     }
-    fn partition_edges_mut(array: &mut [Rc<RefCell<Edge>>], left: usize, right: usize) -> usize {
+    fn partition_by_weight<T: Copy>(array: &mut [T], left: usize, right: usize, weight: &impl Fn(&T) -> f64) -> usize {
         let mut pivot_index = rand::thread_rng().gen_range(left..=right);
         array.swap(pivot_index, right);
         pivot_index = right;
         let mut i = left;
         for j in left..right {
-            if array[j].borrow().get_weight() < array[pivot_index].borrow().get_weight() {
+            if weight(&array[j]) < weight(&array[pivot_index]) {
                 array.swap(i, j);
                 i += 1;
             }
@@ -119,4 +120,11 @@ mod tests {
                              Rc::new(Edge::new(0, 5).weight(5.0))];
         assert_eq!(QuickSelect::find_median_edges(&mut array).get_weight(), 3.0);
     }
+
+    #[test]
+    fn test_quick_select_by_weight() {
+        let weights = [2.0, 1.0, 5.0, 4.0, 3.0];
+        let mut ids: Vec<usize> = (0..weights.len()).collect();
+        assert_eq!(QuickSelect::find_median_by_weight(&mut ids, |&id| weights[id]), 4);
+    }
 }
\ No newline at end of file
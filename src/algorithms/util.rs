@@ -1,19 +1,21 @@
-use std::cell::RefCell;
-use std::collections::HashSet;
-use std::rc::Rc;
+use std::collections::VecDeque;
 use array_tool::vec::{Union, Uniq};
 use fxhash::FxHashSet;
-use log::{debug, trace};
+use log::{debug, trace, warn};
+use crate::algorithms::constrained_bottleneck_spanning_tree::constraints::Constraints;
 use crate::algorithms::min_sum_spanning_tree::kruskal::CalculationType;
 use crate::datastructures::graph::edge::Edge;
+use crate::datastructures::graph::edge_store::EdgeId;
 use crate::datastructures::graph::mutable_graph::MutableGraph;
+use crate::datastructures::uf::union_find::UF;
+use crate::error::CbstuError;
 
-///macro to print edges of ```Vec<Rc<RefCell<Edge>>>```
+///macro to print edges of a ```MutableGraph```
 #[macro_export]
 macro_rules! print_edges {
-    ($edges:expr) => {
-        $edges.iter().for_each(|edge| {
-            println!("{} - {}, {} - {}, {}, {}", edge.borrow().endpoints().0, edge.borrow().endpoints().1, edge.borrow().original_endpoints().0, edge.borrow().original_endpoints().1, edge.borrow().get_weight(), edge.borrow().is_upgraded());
+    ($graph:expr) => {
+        $graph.edges().iter().for_each(|&edge| {
+            println!("{} - {}, {}, {}", $graph.endpoints(edge).0, $graph.endpoints(edge).1, $graph.weight(edge), $graph.is_upgraded(edge));
         });
     };
 }
@@ -28,67 +30,147 @@ pub enum PivotChecked {
     Infeasible(MutableGraph),
 }
 
+/// Outcome of the feasibility check of one threshold, for the searches whose check cannot always decide it.
+pub enum ThresholdCheck<T> {
+    Feasible(T),
+    Infeasible,
+    Unknown,
+}
+
+/// Result of a threshold search whose check may leave thresholds undecided, in the (inverted) weights of the solved
+/// graph. `costs` holds the spending per budget resource, a single entry for the variants with one budget. `bound` is
+/// the best bottleneck that is not proven out of reach, equal to `bottleneck` when the solution is proven optimal.
+#[derive(Debug, Clone)]
+pub struct BoundedSolution {
+    tree: MutableGraph,
+    costs: Vec<f64>,
+    bottleneck: f64,
+    bound: f64,
+}
+
+impl BoundedSolution {
+    /// Solution for `tree` with the bound of [`Util::bisect_thresholds`], capped at the bottleneck of the tree.
+    pub fn new(tree: MutableGraph, costs: Vec<f64>, bound: f64) -> BoundedSolution {
+        let bottleneck = tree.edges().iter().map(|&edge| tree.weight(edge)).fold(f64::NEG_INFINITY, f64::max);
+        let bound = bound.min(bottleneck);
+        if bound < bottleneck {
+            warn!("Solution not proven optimal [bottleneck: {}, bound: {}]", bottleneck, bound);
+        }
+        BoundedSolution { tree, costs, bottleneck, bound }
+    }
+
+    pub fn tree(&self) -> &MutableGraph {
+        &self.tree
+    }
+
+    /// Spending per budget resource.
+    pub fn costs(&self) -> &Vec<f64> {
+        &self.costs
+    }
+
+    /// Spending over all resources, the cost of the tree for a single budget.
+    pub fn cost(&self) -> f64 {
+        self.costs.iter().sum()
+    }
+
+    pub fn bottleneck(&self) -> f64 {
+        self.bottleneck
+    }
+
+    pub fn bound(&self) -> f64 {
+        self.bound
+    }
+
+    pub fn is_optimal(&self) -> bool {
+        self.bound == self.bottleneck
+    }
+}
+
 pub struct Util();
 
 impl Util {
     #[inline]
-    /// Creates a new graph with the same nodes, but each edge is duplicated with its original weight (cost 0) and upgraded weight (upgrade cost).
+    /// Creates a new graph with the same nodes, but each edge is duplicated with its original weight (cost 0) and the upgraded weight of
+    /// every upgrade tier (tier cost, or cost vector). A spanning tree holds at most one parallel copy, so it picks at most one tier per link.
+    /// Candidate links only get their paid copies, which stay marked as candidates. Link constraints carry over to the
+    /// copies: a copy is required if its link has to be in the tree, and forbidden if it may not be used. Only the paid
+    /// copies keep the upgrade bundle of their link.
     pub fn duplicate_edges(graph: &MutableGraph) -> MutableGraph {
-        let mut edges = Vec::new();
-        for edge in graph.edges() {
-            let (u, v) = edge.borrow().endpoints();
-            edges.push(Rc::new(RefCell::new(Edge::new(u, v).weight(edge.borrow().get_weight()).cost(0.0).upgraded(false))));
-            edges.push(Rc::new(RefCell::new(Edge::new(u, v).weight(edge.borrow().get_upgraded_weight()).cost(edge.borrow().get_cost()).upgraded(true).or_weight(edge.borrow().get_weight()))));
+        let mut edges = Vec::with_capacity(2 * graph.edges().len());
+        for &edge in graph.edges() {
+            let (u, v) = graph.endpoints(edge);
+            let candidate = graph.is_candidate(edge);
+            let required = graph.is_required(edge) || graph.is_upgrade_required(edge);
+            if !candidate {
+                edges.push(Edge::new(u, v).weight(graph.weight(edge)).cost(0.0).upgraded(false)
+                    .required(required)
+                    .forbidden(graph.is_forbidden(edge) || graph.is_upgrade_required(edge))
+                    .directed(graph.is_directed(edge)));
+            }
+            for ((upgraded_weight, _), costs) in graph.tiers(edge).zip(graph.tier_cost_vectors(edge)) {
+                edges.push(Edge::new(u, v).weight(upgraded_weight).costs(costs).upgraded(true).candidate(candidate).or_weight(graph.weight(edge))
+                    .required(required)
+                    .forbidden(graph.is_forbidden(edge) || graph.is_upgrade_forbidden(edge))
+                    .directed(graph.is_directed(edge))
+                    .bundle(graph.bundle(edge)));
+            }
         }
         MutableGraph::new(graph.nodes_copy(), edges)
     }
 
     #[inline]
     /// Updates bottleneck to bigger/smaller value according to inverse.
-    pub fn update_bottleneck(bottleneck: f64, edge: &Rc<RefCell<Edge>>, inverse: bool) -> f64 {
+    pub fn update_bottleneck(bottleneck: f64, weight: f64, inverse: bool) -> f64 {
         let mut bottleneck = bottleneck;
         if inverse {
-            if edge.borrow().get_weight() > bottleneck {
-                bottleneck = edge.borrow().get_weight();
+            if weight > bottleneck {
+                bottleneck = weight;
             }
-        } else if edge.borrow().get_weight() < bottleneck {
-            bottleneck = edge.borrow().get_weight();
+        } else if weight < bottleneck {
+            bottleneck = weight;
         }
         bottleneck
     }
 
     #[inline]
     ///union of 2 list of edges without duplicates
-    pub fn union_edges(edges1: &Vec<Rc<RefCell<Edge>>>, edges2: &Vec<Rc<RefCell<Edge>>>) -> Vec<Rc<RefCell<Edge>>> {
-        let mut edges = FxHashSet::default();
-        edges1.iter().for_each(|edge| {
-            edges.insert(edge.borrow().clone());
-        });
-        edges2.iter().for_each(|edge| {
-            edges.insert(edge.borrow().clone());
-        });
-        edges.into_iter().map(|edge| Rc::new(RefCell::new(edge))).collect()
+    pub fn union_edges(edges1: &[EdgeId], edges2: &[EdgeId]) -> Vec<EdgeId> {
+        let mut seen = FxHashSet::default();
+        edges1.iter().chain(edges2.iter()).copied().filter(|&edge| seen.insert(edge)).collect()
     }
 
     #[inline]
     ///Return unique list of weights with weight bigger then lower-bound and smaller then or equal to upperbound
-    pub fn unique_weight_list(edges: &[Rc<RefCell<Edge>>], lower_bound: f64, upper_bound: f64) -> Vec<f64> {
+    pub fn unique_weight_list(graph: &MutableGraph, lower_bound: f64, upper_bound: f64) -> Vec<f64> {
         let mut weights = FxHashSet::default();
-        edges.iter().for_each(|edge| {
-            if edge.borrow().get_weight() > lower_bound && edge.borrow().get_weight() <= upper_bound {
-                weights.insert(edge.borrow().get_weight() as i64);
+        graph.edges().iter().for_each(|&edge| {
+            if graph.weight(edge) > lower_bound && graph.weight(edge) <= upper_bound {
+                weights.insert(graph.weight(edge) as i64);
             }
         });
         weights.into_iter().map(|weight| weight as f64).collect()
     }
 
+    /// Returns the sorted unique (inverted) weights of the graph, the candidate bottlenecks of the threshold searches.
+    pub fn sorted_unique_weights(graph: &MutableGraph) -> Result<Vec<f64>, CbstuError> {
+        if graph.edges().is_empty() {
+            return Err(CbstuError::EmptyEdgeSet);
+        }
+        let mut unique_weights = Self::unique_weight_list(graph, f64::NEG_INFINITY, 0.0);
+        if unique_weights.is_empty() {
+            return Err(CbstuError::MalformedInput(String::from("edge weights are not inverted")));
+        }
+        unique_weights.sort_by(|a, b| a.total_cmp(b));
+        Ok(unique_weights)
+    }
+
     #[inline]
     ///Return unique list of weights with weight bigger then lower-bound and smaller then or equal to upperbound
-    pub fn unique_weight_list_above_or_eq(edges: &[Rc<RefCell<Edge>>], threshold: f64) -> Vec<f64> {
+    pub fn unique_weight_list_above_or_eq(graph: &MutableGraph, threshold: f64) -> Vec<f64> {
         let mut weights_set = FxHashSet::default();
-        edges.iter().for_each(|edge| {
-            if edge.borrow().get_weight() >= threshold {
-                weights_set.insert(edge.borrow().get_weight() as i64);
+        graph.edges().iter().for_each(|&edge| {
+            if graph.weight(edge) >= threshold {
+                weights_set.insert(graph.weight(edge) as i64);
             }
         });
         weights_set.into_iter().map(|weight| weight as f64).collect()
@@ -102,17 +184,161 @@ impl Util {
 
     #[inline]
     /// Check if graph with edge weights <= pivot_weight is feasible
-    pub fn check_pivot(graph: &MutableGraph, pivot_weight: f64, budget: f64) -> PivotResult {
-        let mut graph_below_pivot = graph.smaller_or_eq_than(pivot_weight);
-        let (op_mst, cost, bottleneck) = graph_below_pivot.mst(CalculationType::Cost);
-        match op_mst {
-            Some(st) => {
-                match cost {
-                    cost if cost <= budget => PivotResult::Feasible((st, cost, bottleneck)),
-                    _ => PivotResult::Infeasible
+    pub fn check_pivot(graph: &MutableGraph, pivot_weight: f64, budget: f64) -> Result<PivotResult, CbstuError> {
+        let graph_below_pivot = graph.smaller_or_eq_than(pivot_weight);
+        match graph_below_pivot.mst(CalculationType::Cost) {
+            Ok((st, cost, bottleneck)) if cost <= budget => Ok(PivotResult::Feasible((st, cost, bottleneck))),
+            Ok(_) | Err(CbstuError::DisconnectedGraph) | Err(CbstuError::EmptyEdgeSet) => Ok(PivotResult::Infeasible),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Bisection over the sorted `thresholds` with a check that may leave a threshold undecided, which is then searched
+    /// above like an infeasible one. Returns the solution of the smallest threshold found feasible and the smallest
+    /// threshold not proven infeasible. As feasibility is monotone in the threshold, no solution lies below the latter,
+    /// so it bounds the optimum from below; both are equal when every check was decided.
+    pub fn bisect_thresholds<T>(thresholds: &[f64], mut check: impl FnMut(f64) -> ThresholdCheck<T>) -> (Option<T>, f64) {
+        let mut min = 0;
+        let mut max = thresholds.len();
+        let mut proven = 0;
+        let mut found = None;
+        while min < max {
+            let pivot = (min + max) / 2;
+            match check(thresholds[pivot]) {
+                ThresholdCheck::Feasible(solution) => {
+                    debug!("Feasible pivot {}", thresholds[pivot]);
+                    found = Some(solution);
+                    max = pivot;
+                }
+                ThresholdCheck::Infeasible => {
+                    debug!("Infeasible pivot {}", thresholds[pivot]);
+                    proven = pivot + 1;
+                    min = pivot + 1;
+                }
+                ThresholdCheck::Unknown => {
+                    debug!("Undecided pivot {}", thresholds[pivot]);
+                    min = pivot + 1;
+                }
+            }
+        }
+        (found, thresholds.get(proven).copied().unwrap_or(f64::INFINITY))
+    }
+
+    /// Branch and bound over the spanning trees of `graph` that hold `required` required edges, for the checks that
+    /// settle a threshold exactly. Depth-first over the edges in `order`, each one first taken, if it closes no cycle
+    /// and `admits` it next to the chosen edges, then left out. A branch is cut once `promising` rejects the chosen
+    /// edges with the undecided ones after them. Every tree found goes to `found`, which ends the search by returning
+    /// true; the result tells whether it did.
+    pub fn branch_and_bound(graph: &MutableGraph, order: &[EdgeId], required: usize, admits: impl Fn(&[EdgeId], EdgeId) -> bool, promising: impl Fn(&[EdgeId], &[EdgeId]) -> bool, mut found: impl FnMut(&[EdgeId]) -> bool) -> bool {
+        let mut chosen = Vec::with_capacity(graph.nodes().len());
+        Self::branch(graph, order, required, &admits, &promising, &mut found, 0, &mut chosen)
+    }
+
+    fn branch(graph: &MutableGraph, order: &[EdgeId], required: usize, admits: &dyn Fn(&[EdgeId], EdgeId) -> bool, promising: &dyn Fn(&[EdgeId], &[EdgeId]) -> bool, found: &mut dyn FnMut(&[EdgeId]) -> bool, index: usize, chosen: &mut Vec<EdgeId>) -> bool {
+        let n = graph.nodes().len();
+        if chosen.len() + 1 == n {
+            return Self::required_in(graph, chosen.iter().copied()) == required && found(chosen);
+        }
+        if index == order.len() || !promising(chosen, &order[index..]) {
+            return false;
+        }
+        let edge = order[index];
+        let mut uf = UF::new(n as i32);
+        chosen.iter().for_each(|&edge| uf.union(graph.endpoints(edge).0, graph.endpoints(edge).1));
+        let (u, v) = graph.endpoints(edge);
+        if !uf.connected(u, v) && admits(chosen, edge) {
+            chosen.push(edge);
+            if Self::branch(graph, order, required, admits, promising, found, index + 1, chosen) {
+                return true;
+            }
+            chosen.pop();
+        }
+        Self::branch(graph, order, required, admits, promising, found, index + 1, chosen)
+    }
+
+    /// Total `key` of the cheapest forest of `undecided` edges that completes the `chosen` ones to a spanning tree of
+    /// `graph`, the bound of the branch and bound searches. `None` if they cannot.
+    pub fn completion(graph: &MutableGraph, chosen: &[EdgeId], undecided: impl IntoIterator<Item = EdgeId>, key: impl Fn(EdgeId) -> f64) -> Option<f64> {
+        let mut uf = UF::new(graph.nodes().len() as i32);
+        chosen.iter().for_each(|&edge| uf.union(graph.endpoints(edge).0, graph.endpoints(edge).1));
+        let mut undecided: Vec<EdgeId> = undecided.into_iter().collect();
+        undecided.sort_by(|&a, &b| key(a).total_cmp(&key(b)));
+        let mut total = 0.0;
+        for edge in undecided {
+            let (u, v) = graph.endpoints(edge);
+            if !uf.connected(u, v) {
+                uf.union(u, v);
+                total += key(edge);
+            }
+        }
+        (uf.count() == 1).then_some(total)
+    }
+
+    /// Kruskal over the edges in `order`, taking every edge that joins two components if `admits` accepts it, which
+    /// may count the edges taken. `None` if the result does not span `graph`.
+    pub fn kruskal(graph: &MutableGraph, order: impl IntoIterator<Item = EdgeId>, mut admits: impl FnMut(EdgeId) -> bool) -> Option<Vec<EdgeId>> {
+        let n = graph.nodes().len();
+        let mut uf = UF::new(n as i32);
+        let mut tree = Vec::with_capacity(n.saturating_sub(1));
+        for edge in order {
+            let (u, v) = graph.endpoints(edge);
+            if !uf.connected(u, v) && admits(edge) {
+                uf.union(u, v);
+                tree.push(edge);
+            }
+        }
+        (uf.count() == 1).then_some(tree)
+    }
+
+    /// Positions in `tree` of the edges on the path from `from` to `to` in the forest of `tree`, empty if there is none.
+    pub fn tree_path(graph: &MutableGraph, tree: &[EdgeId], from: usize, to: usize) -> Vec<usize> {
+        let n = graph.nodes().len();
+        let mut adjacency = vec![Vec::new(); n];
+        tree.iter().enumerate().for_each(|(position, &edge)| {
+            let (u, v) = graph.endpoints(edge);
+            adjacency[u].push((v, position));
+            adjacency[v].push((u, position));
+        });
+        let mut parent: Vec<Option<(usize, usize)>> = vec![None; n];
+        let mut visited = vec![false; n];
+        visited[from] = true;
+        let mut queue = VecDeque::from([from]);
+        while let Some(u) = queue.pop_front() {
+            if u == to {
+                break;
+            }
+            for &(v, position) in &adjacency[u] {
+                if !visited[v] {
+                    visited[v] = true;
+                    parent[v] = Some((u, position));
+                    queue.push_back(v);
                 }
             }
-            None => PivotResult::Infeasible
+        }
+        let mut path = Vec::new();
+        let mut node = to;
+        while let Some((previous, position)) = parent[node] {
+            path.push(position);
+            node = previous;
+        }
+        path
+    }
+
+    /// Number of links with required copies in the preprocessed graph, each of which a spanning tree has to hold.
+    pub fn required_links(graph: &MutableGraph) -> usize {
+        Constraints::required_links(graph).len()
+    }
+
+    /// Number of required edges among `edges`.
+    pub fn required_in(graph: &MutableGraph, edges: impl IntoIterator<Item = EdgeId>) -> usize {
+        edges.into_iter().filter(|&edge| graph.is_required(edge)).count()
+    }
+
+    /// Explains why no pivot was feasible: the graph itself is disconnected or even the cheapest spanning tree exceeds the budget.
+    pub fn infeasibility_reason(graph: &MutableGraph) -> CbstuError {
+        match graph.mst(CalculationType::Cost) {
+            Ok(_) => CbstuError::InfeasibleBudget,
+            Err(err) => err,
         }
     }
 }
\ No newline at end of file
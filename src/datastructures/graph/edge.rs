@@ -12,6 +12,16 @@ pub struct Edge {
     or_other: usize,
     or_weight: f64,
     upgraded: bool,
+    candidate: bool,
+    required: bool,
+    forbidden: bool,
+    upgrade_required: bool,
+    upgrade_forbidden: bool,
+    directed: bool,
+    bundle: Option<usize>,
+    tiers: Vec<(f64, f64)>,
+    resources: Vec<f64>,
+    tier_resources: Vec<Vec<f64>>,
 }
 
 impl Edge {
@@ -26,6 +36,16 @@ impl Edge {
             or_other: other,
             or_weight: 0.0,
             upgraded: false,
+            candidate: false,
+            required: false,
+            forbidden: false,
+            upgrade_required: false,
+            upgrade_forbidden: false,
+            directed: false,
+            bundle: None,
+            tiers: Vec::new(),
+            resources: Vec::new(),
+            tier_resources: Vec::new(),
         }
     }
     pub fn endpoints(&self) -> (usize, usize) {
@@ -45,6 +65,62 @@ impl Edge {
     pub fn is_upgraded(&self) -> bool {
         self.upgraded
     }
+    /// Marks a link that is absent from the base network and can be built at its upgrade tiers.
+    pub fn candidate(mut self, candidate: bool) -> Edge {
+        self.candidate = candidate;
+        self
+    }
+    pub fn is_candidate(&self) -> bool {
+        self.candidate
+    }
+    /// The link must be part of the tree.
+    pub fn required(mut self, required: bool) -> Edge {
+        self.required = required;
+        self
+    }
+    pub fn is_required(&self) -> bool {
+        self.required
+    }
+    /// The link must not be part of the tree.
+    pub fn forbidden(mut self, forbidden: bool) -> Edge {
+        self.forbidden = forbidden;
+        self
+    }
+    pub fn is_forbidden(&self) -> bool {
+        self.forbidden
+    }
+    /// The link must be part of the tree at one of its upgrade tiers.
+    pub fn upgrade_required(mut self, upgrade_required: bool) -> Edge {
+        self.upgrade_required = upgrade_required;
+        self
+    }
+    pub fn is_upgrade_required(&self) -> bool {
+        self.upgrade_required
+    }
+    /// The link can only be used at its base capacity.
+    pub fn upgrade_forbidden(mut self, upgrade_forbidden: bool) -> Edge {
+        self.upgrade_forbidden = upgrade_forbidden;
+        self
+    }
+    pub fn is_upgrade_forbidden(&self) -> bool {
+        self.upgrade_forbidden
+    }
+    /// The link only carries traffic from `either` to `other`.
+    pub fn directed(mut self, directed: bool) -> Edge {
+        self.directed = directed;
+        self
+    }
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+    /// Puts the upgrades of the link in a bundle that pays a fixed charge once when any of its links is upgraded.
+    pub fn bundle(mut self, bundle: Option<usize>) -> Edge {
+        self.bundle = bundle;
+        self
+    }
+    pub fn get_bundle(&self) -> Option<usize> {
+        self.bundle
+    }
     pub fn upgraded_weight(mut self, upgraded_weight: f64) -> Edge {
         self.upgraded_weight = upgraded_weight;
         self
@@ -53,6 +129,24 @@ impl Edge {
         self.cost = cost;
         self
     }
+    /// Cost vector of the upgrade over several budget resources, the first entry is `cost`.
+    pub fn costs(mut self, costs: Vec<f64>) -> Edge {
+        self.cost = costs.first().copied().unwrap_or(0.0);
+        self.resources = costs.into_iter().skip(1).collect();
+        self
+    }
+    /// Adds an upgrade tier next to the one given by `upgraded_weight` and `cost`.
+    pub fn tier(mut self, upgraded_weight: f64, cost: f64) -> Edge {
+        self.tiers.push((upgraded_weight, cost));
+        self.tier_resources.push(Vec::new());
+        self
+    }
+    /// Adds an upgrade tier with a cost vector, see [`Edge::costs`].
+    pub fn tier_costs(mut self, upgraded_weight: f64, costs: Vec<f64>) -> Edge {
+        self.tiers.push((upgraded_weight, costs.first().copied().unwrap_or(0.0)));
+        self.tier_resources.push(costs.into_iter().skip(1).collect());
+        self
+    }
     pub fn or_weight(mut self, or_weight: f64) -> Edge {
         self.or_weight = or_weight;
         self
@@ -66,9 +160,26 @@ impl Edge {
     pub fn get_cost(&self) -> f64 {
         self.cost
     }
+    /// Cost vector of the upgrade, a single entry unless the edge uses several resources.
+    pub fn get_costs(&self) -> Vec<f64> {
+        std::iter::once(self.cost).chain(self.resources.iter().copied()).collect()
+    }
     pub fn get_or_weight(&self) -> f64 {
         self.or_weight
     }
+    /// All upgrade tiers as `(upgraded weight, cost)`, starting with `upgraded_weight` and `cost`.
+    pub fn tiers(&self) -> Vec<(f64, f64)> {
+        let mut tiers = vec![(self.upgraded_weight, self.cost)];
+        tiers.extend_from_slice(&self.tiers);
+        tiers
+    }
+    /// Cost vectors of the upgrade tiers, in the order of [`Edge::tiers`].
+    pub fn tier_cost_vectors(&self) -> Vec<Vec<f64>> {
+        let mut costs = vec![self.get_costs()];
+        costs.extend(self.tiers.iter().zip(self.tier_resources.iter())
+            .map(|(&(_, cost), resources)| std::iter::once(cost).chain(resources.iter().copied()).collect()));
+        costs
+    }
     pub fn set_original_endpoints(mut self, either: usize, other: usize) -> Edge {
         self.or_either = either;
         self.or_other = other;
@@ -94,6 +205,7 @@ impl Edge {
     pub fn inverse_weights(&mut self) {
         self.weight = -self.weight;
         self.upgraded_weight = -self.upgraded_weight;
+        self.tiers.iter_mut().for_each(|tier| tier.0 = -tier.0);
     }
 }
 
@@ -109,6 +221,16 @@ impl PartialEq for Edge {
             && self.weight == other.weight
             && self.upgraded_weight == other.upgraded_weight
             && self.cost == other.cost
+            && self.candidate == other.candidate
+            && self.required == other.required
+            && self.forbidden == other.forbidden
+            && self.upgrade_required == other.upgrade_required
+            && self.upgrade_forbidden == other.upgrade_forbidden
+            && self.directed == other.directed
+            && self.bundle == other.bundle
+            && self.tiers == other.tiers
+            && self.resources == other.resources
+            && self.tier_resources == other.tier_resources
     }
 }
 impl Eq for Edge {}
@@ -120,6 +242,10 @@ impl Hash for Edge {
         self.weight.to_bits().hash(state);
         self.upgraded_weight.to_bits().hash(state);
         self.cost.to_bits().hash(state);
+        self.tiers.iter().for_each(|(upgraded_weight, cost)| {
+            upgraded_weight.to_bits().hash(state);
+            cost.to_bits().hash(state);
+        });
     }
 }
 
@@ -135,6 +261,16 @@ fn builder_test() {
         or_other: 3,
         or_weight: 10.0,
         upgraded: false,
+        candidate: false,
+        required: false,
+        forbidden: false,
+        upgrade_required: false,
+        upgrade_forbidden: false,
+        directed: false,
+        bundle: None,
+        tiers: Vec::new(),
+        resources: Vec::new(),
+        tier_resources: Vec::new(),
     };
     let edge_from_builder: Edge = Edge::new(0,1)
         .weight(10.0)
@@ -0,0 +1,262 @@
+use crate::datastructures::graph::edge::Edge;
+
+/// Index of an edge in an [`EdgeStore`].
+pub type EdgeId = u32;
+
+/// Struct-of-arrays storage of the edge attributes, indexed by [`EdgeId`]. A store is shared by a graph and all
+/// graphs derived from it, which only keep their own list of edge ids. Extra upgrade tiers of all edges are kept in
+/// one pair of arrays, the tiers of edge `id` end at `tier_end[id]` and start where those of `id - 1` end. Costs in
+/// further budget resources are only stored for edges that have them.
+#[derive(Debug, Clone, Default)]
+pub struct EdgeStore {
+    either: Vec<u32>,
+    other: Vec<u32>,
+    weight: Vec<f64>,
+    upgraded_weight: Vec<f64>,
+    cost: Vec<f64>,
+    or_weight: Vec<f64>,
+    upgraded: Vec<bool>,
+    candidate: Vec<bool>,
+    required: Vec<bool>,
+    forbidden: Vec<bool>,
+    upgrade_required: Vec<bool>,
+    upgrade_forbidden: Vec<bool>,
+    directed: Vec<bool>,
+    bundle: Vec<Option<usize>>,
+    tier_end: Vec<u32>,
+    tier_weight: Vec<f64>,
+    tier_cost: Vec<f64>,
+    resources: Vec<Vec<f64>>,
+    tier_resources: Vec<Vec<f64>>,
+}
+
+impl EdgeStore {
+    pub fn with_capacity(capacity: usize) -> EdgeStore {
+        EdgeStore {
+            either: Vec::with_capacity(capacity),
+            other: Vec::with_capacity(capacity),
+            weight: Vec::with_capacity(capacity),
+            upgraded_weight: Vec::with_capacity(capacity),
+            cost: Vec::with_capacity(capacity),
+            or_weight: Vec::with_capacity(capacity),
+            upgraded: Vec::with_capacity(capacity),
+            candidate: Vec::with_capacity(capacity),
+            required: Vec::with_capacity(capacity),
+            forbidden: Vec::with_capacity(capacity),
+            upgrade_required: Vec::with_capacity(capacity),
+            upgrade_forbidden: Vec::with_capacity(capacity),
+            directed: Vec::with_capacity(capacity),
+            bundle: Vec::with_capacity(capacity),
+            tier_end: Vec::with_capacity(capacity),
+            tier_weight: Vec::new(),
+            tier_cost: Vec::new(),
+            resources: Vec::with_capacity(capacity),
+            tier_resources: Vec::new(),
+        }
+    }
+
+    /// Appends the attributes of the given edge and returns its id.
+    pub fn push(&mut self, edge: &Edge) -> EdgeId {
+        let id = self.len() as EdgeId;
+        let (either, other) = edge.endpoints();
+        self.either.push(either as u32);
+        self.other.push(other as u32);
+        self.weight.push(edge.get_weight());
+        self.upgraded_weight.push(edge.get_upgraded_weight());
+        self.cost.push(edge.get_cost());
+        self.or_weight.push(edge.get_or_weight());
+        self.upgraded.push(edge.is_upgraded());
+        self.candidate.push(edge.is_candidate());
+        self.required.push(edge.is_required());
+        self.forbidden.push(edge.is_forbidden());
+        self.upgrade_required.push(edge.is_upgrade_required());
+        self.upgrade_forbidden.push(edge.is_upgrade_forbidden());
+        self.directed.push(edge.is_directed());
+        self.bundle.push(edge.get_bundle());
+        let costs = edge.tier_cost_vectors();
+        self.resources.push(costs[0][1..].to_vec());
+        for (&(upgraded_weight, cost), costs) in edge.tiers().iter().zip(costs.iter()).skip(1) {
+            self.tier_weight.push(upgraded_weight);
+            self.tier_cost.push(cost);
+            self.tier_resources.push(costs[1..].to_vec());
+        }
+        self.tier_end.push(self.tier_weight.len() as u32);
+        id
+    }
+
+    pub fn len(&self) -> usize {
+        self.weight.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.weight.is_empty()
+    }
+
+    #[inline]
+    pub fn endpoints(&self, id: EdgeId) -> (usize, usize) {
+        (self.either[id as usize] as usize, self.other[id as usize] as usize)
+    }
+
+    #[inline]
+    pub fn weight(&self, id: EdgeId) -> f64 {
+        self.weight[id as usize]
+    }
+
+    #[inline]
+    pub fn upgraded_weight(&self, id: EdgeId) -> f64 {
+        self.upgraded_weight[id as usize]
+    }
+
+    #[inline]
+    pub fn cost(&self, id: EdgeId) -> f64 {
+        self.cost[id as usize]
+    }
+
+    #[inline]
+    pub fn or_weight(&self, id: EdgeId) -> f64 {
+        self.or_weight[id as usize]
+    }
+
+    #[inline]
+    pub fn is_upgraded(&self, id: EdgeId) -> bool {
+        self.upgraded[id as usize]
+    }
+
+    #[inline]
+    pub fn is_candidate(&self, id: EdgeId) -> bool {
+        self.candidate[id as usize]
+    }
+
+    #[inline]
+    pub fn is_required(&self, id: EdgeId) -> bool {
+        self.required[id as usize]
+    }
+
+    #[inline]
+    pub fn is_forbidden(&self, id: EdgeId) -> bool {
+        self.forbidden[id as usize]
+    }
+
+    #[inline]
+    pub fn is_upgrade_required(&self, id: EdgeId) -> bool {
+        self.upgrade_required[id as usize]
+    }
+
+    #[inline]
+    pub fn is_upgrade_forbidden(&self, id: EdgeId) -> bool {
+        self.upgrade_forbidden[id as usize]
+    }
+
+    #[inline]
+    pub fn is_directed(&self, id: EdgeId) -> bool {
+        self.directed[id as usize]
+    }
+
+    #[inline]
+    pub fn bundle(&self, id: EdgeId) -> Option<usize> {
+        self.bundle[id as usize]
+    }
+
+    /// Upgrade tiers of the edge as `(upgraded weight, cost)`, starting with its upgraded weight and cost.
+    pub fn tiers(&self, id: EdgeId) -> impl Iterator<Item = (f64, f64)> + '_ {
+        let extra = self.extra_tiers(id);
+        std::iter::once((self.upgraded_weight(id), self.cost(id)))
+            .chain(self.tier_weight[extra.clone()].iter().copied().zip(self.tier_cost[extra].iter().copied()))
+    }
+
+    /// Cost vectors of the upgrade tiers, in the order of [`EdgeStore::tiers`].
+    pub fn tier_cost_vectors(&self, id: EdgeId) -> impl Iterator<Item = Vec<f64>> + '_ {
+        let extra = self.extra_tiers(id);
+        std::iter::once(self.costs(id)).chain(self.tier_cost[extra.clone()].iter().zip(self.tier_resources[extra].iter())
+            .map(|(&cost, resources)| std::iter::once(cost).chain(resources.iter().copied()).collect()))
+    }
+
+    /// Cost vector of the edge, starting with its cost.
+    pub fn costs(&self, id: EdgeId) -> Vec<f64> {
+        std::iter::once(self.cost(id)).chain(self.resources[id as usize].iter().copied()).collect()
+    }
+
+    /// Cost of the edge in budget resource `resource`, 0 for resources the edge does not list.
+    #[inline]
+    pub fn resource_cost(&self, id: EdgeId, resource: usize) -> f64 {
+        match resource {
+            0 => self.cost(id),
+            resource => self.resources[id as usize].get(resource - 1).copied().unwrap_or(0.0),
+        }
+    }
+
+    fn extra_tiers(&self, id: EdgeId) -> std::ops::Range<usize> {
+        let start = match id {
+            0 => 0,
+            id => self.tier_end[id as usize - 1] as usize,
+        };
+        start..self.tier_end[id as usize] as usize
+    }
+
+    /// Materializes the edge with the given id.
+    pub fn edge(&self, id: EdgeId) -> Edge {
+        let (either, other) = self.endpoints(id);
+        let edge = Edge::new(either, other)
+            .weight(self.weight(id))
+            .upgraded_weight(self.upgraded_weight(id))
+            .costs(self.costs(id))
+            .or_weight(self.or_weight(id))
+            .upgraded(self.is_upgraded(id))
+            .candidate(self.is_candidate(id))
+            .required(self.is_required(id))
+            .forbidden(self.is_forbidden(id))
+            .upgrade_required(self.is_upgrade_required(id))
+            .upgrade_forbidden(self.is_upgrade_forbidden(id))
+            .directed(self.is_directed(id))
+            .bundle(self.bundle(id));
+        self.tiers(id).zip(self.tier_cost_vectors(id)).skip(1)
+            .fold(edge, |edge, ((upgraded_weight, _), costs)| edge.tier_costs(upgraded_weight, costs))
+    }
+
+    /// Negates the weight and the upgraded weights of all tiers of the given edges.
+    pub fn inverse_weights(&mut self, ids: &[EdgeId]) {
+        for &id in ids {
+            self.weight[id as usize] = -self.weight[id as usize];
+            self.upgraded_weight[id as usize] = -self.upgraded_weight[id as usize];
+            for tier in self.extra_tiers(id) {
+                self.tier_weight[tier] = -self.tier_weight[tier];
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datastructures::graph::edge::Edge;
+    use crate::datastructures::graph::edge_store::EdgeStore;
+
+    #[test]
+    fn test_push_and_materialize() {
+        let mut store = EdgeStore::with_capacity(2);
+        let edge = Edge::new(3, 1).weight(2.0).upgraded_weight(5.0).cost(4.0).or_weight(1.0).upgraded(true);
+        assert_eq!(store.push(&Edge::new(0, 1).weight(1.0)), 0);
+        assert_eq!(store.push(&edge), 1);
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.endpoints(1), (3, 1));
+        assert_eq!(store.edge(1), edge);
+        assert!(store.edge(1).is_upgraded());
+        store.inverse_weights(&[1]);
+        assert_eq!(store.weight(0), 1.0);
+        assert_eq!((store.weight(1), store.upgraded_weight(1)), (-2.0, -5.0));
+    }
+
+    #[test]
+    fn test_tiers() {
+        let mut store = EdgeStore::default();
+        let tiered = Edge::new(1, 2).weight(10.0).upgraded_weight(40.0).cost(3.0).tier(100.0, 8.0);
+        store.push(&Edge::new(0, 1).weight(1.0).upgraded_weight(2.0).cost(1.0));
+        store.push(&tiered);
+        store.push(&Edge::new(2, 0).weight(1.0).upgraded_weight(3.0).cost(1.0).tier(5.0, 2.0).tier(7.0, 4.0));
+        assert_eq!(store.tiers(0).collect::<Vec<_>>(), vec![(2.0, 1.0)]);
+        assert_eq!(store.tiers(1).collect::<Vec<_>>(), vec![(40.0, 3.0), (100.0, 8.0)]);
+        assert_eq!(store.edge(1), tiered);
+        store.inverse_weights(&[1, 2]);
+        assert_eq!(store.tiers(1).collect::<Vec<_>>(), vec![(-40.0, 3.0), (-100.0, 8.0)]);
+        assert_eq!(store.tiers(2).collect::<Vec<_>>(), vec![(-3.0, 1.0), (-5.0, 2.0), (-7.0, 4.0)]);
+    }
+}
@@ -1,3 +1,4 @@
 pub mod edge;
+pub mod edge_store;
 pub mod node;
 pub mod mutable_graph;
\ No newline at end of file
@@ -1,100 +1,199 @@
-use std::cell::RefCell;
-use std::ops::Deref;
-use std::rc::Rc;
-use log::{trace, warn};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use log::trace;
 use crate::algorithms::min_bottleneck_spanning_tree::camerini::MBST;
 use crate::algorithms::min_sum_spanning_tree::kruskal::{CalculationType, ConnectionType, Kruskal};
 use crate::datastructures::graph::edge::Edge;
+use crate::datastructures::graph::edge_store::{EdgeId, EdgeStore};
 use crate::datastructures::graph::node::Node;
-use crate::print_edges;
+use crate::error::CbstuError;
 
-/// Graph with list of immutable nodes and mutable edges. Single threaded.
+/// Graph with list of immutable nodes and a list of edge ids into a struct-of-arrays [`EdgeStore`]. Graphs derived
+/// from a graph (filters, spanning trees) share its nodes and store and only own their edge ids. `Send + Sync`.
+#[derive(Debug, Clone)]
 pub struct MutableGraph {
-    nodes: Rc<Vec<Rc<Node>>>,
-    edges: Vec<Rc<RefCell<Edge>>>,
+    nodes: Arc<Vec<Node>>,
+    store: Arc<EdgeStore>,
+    edges: Vec<EdgeId>,
 }
 
 impl MutableGraph {
-    pub fn new(nodes: Rc<Vec<Rc<Node>>>, edges: Vec<Rc<RefCell<Edge>>>) -> MutableGraph {
+    pub fn new(nodes: Arc<Vec<Node>>, edges: Vec<Edge>) -> MutableGraph {
+        let mut store = EdgeStore::with_capacity(edges.len());
+        let ids = edges.iter().map(|edge| store.push(edge)).collect();
         MutableGraph {
             nodes,
+            store: Arc::new(store),
+            edges: ids,
+        }
+    }
+
+    /// Returns a new graph with the same nodes and store, containing the given edges.
+    pub fn subgraph(&self, edges: Vec<EdgeId>) -> MutableGraph {
+        MutableGraph {
+            nodes: Arc::clone(&self.nodes),
+            store: Arc::clone(&self.store),
             edges,
         }
     }
 
-    pub fn nodes(&self) -> &Vec<Rc<Node>> {
+    pub fn nodes(&self) -> &Vec<Node> {
         &self.nodes
     }
 
-    pub fn edges(&self) -> &Vec<Rc<RefCell<Edge>>> {
+    pub fn edges(&self) -> &Vec<EdgeId> {
         &self.edges
     }
 
-    pub fn edges_mut(&mut self) -> &mut Vec<Rc<RefCell<Edge>>> {
+    pub fn edges_mut(&mut self) -> &mut Vec<EdgeId> {
         &mut self.edges
     }
 
-    pub fn edges_copy(&self) -> Vec<Rc<RefCell<Edge>>> {
+    pub fn edges_copy(&self) -> Vec<EdgeId> {
         self.edges.clone()
     }
 
-    pub fn nodes_copy(&self) -> Rc<Vec<Rc<Node>>> {
-        Rc::clone(&self.nodes)
+    pub fn nodes_copy(&self) -> Arc<Vec<Node>> {
+        Arc::clone(&self.nodes)
+    }
+
+    pub fn store(&self) -> &Arc<EdgeStore> {
+        &self.store
+    }
+
+    #[inline]
+    pub fn endpoints(&self, edge: EdgeId) -> (usize, usize) {
+        self.store.endpoints(edge)
+    }
+
+    #[inline]
+    pub fn weight(&self, edge: EdgeId) -> f64 {
+        self.store.weight(edge)
+    }
+
+    #[inline]
+    pub fn upgraded_weight(&self, edge: EdgeId) -> f64 {
+        self.store.upgraded_weight(edge)
+    }
+
+    #[inline]
+    pub fn cost(&self, edge: EdgeId) -> f64 {
+        self.store.cost(edge)
+    }
+
+    /// Upgrade tiers of the edge as `(upgraded weight, cost)`, starting with its upgraded weight and cost.
+    pub fn tiers(&self, edge: EdgeId) -> impl Iterator<Item = (f64, f64)> + '_ {
+        self.store.tiers(edge)
+    }
+
+    /// Cost vectors of the upgrade tiers of the edge, in the order of [`MutableGraph::tiers`].
+    pub fn tier_cost_vectors(&self, edge: EdgeId) -> impl Iterator<Item = Vec<f64>> + '_ {
+        self.store.tier_cost_vectors(edge)
+    }
+
+    /// Cost vector of the edge over the budget resources, starting with its cost.
+    pub fn costs(&self, edge: EdgeId) -> Vec<f64> {
+        self.store.costs(edge)
+    }
+
+    #[inline]
+    pub fn resource_cost(&self, edge: EdgeId, resource: usize) -> f64 {
+        self.store.resource_cost(edge, resource)
+    }
+
+    #[inline]
+    pub fn or_weight(&self, edge: EdgeId) -> f64 {
+        self.store.or_weight(edge)
+    }
+
+    #[inline]
+    pub fn is_upgraded(&self, edge: EdgeId) -> bool {
+        self.store.is_upgraded(edge)
+    }
+
+    #[inline]
+    pub fn is_candidate(&self, edge: EdgeId) -> bool {
+        self.store.is_candidate(edge)
+    }
+
+    #[inline]
+    pub fn is_required(&self, edge: EdgeId) -> bool {
+        self.store.is_required(edge)
+    }
+
+    #[inline]
+    pub fn is_forbidden(&self, edge: EdgeId) -> bool {
+        self.store.is_forbidden(edge)
+    }
+
+    #[inline]
+    pub fn is_upgrade_required(&self, edge: EdgeId) -> bool {
+        self.store.is_upgrade_required(edge)
+    }
+
+    #[inline]
+    pub fn is_upgrade_forbidden(&self, edge: EdgeId) -> bool {
+        self.store.is_upgrade_forbidden(edge)
+    }
+
+    #[inline]
+    pub fn is_directed(&self, edge: EdgeId) -> bool {
+        self.store.is_directed(edge)
+    }
+
+    #[inline]
+    /// Upgrade bundle of the edge, if any.
+    pub fn bundle(&self, edge: EdgeId) -> Option<usize> {
+        self.store.bundle(edge)
+    }
+
+    /// Materializes the edge with the given id.
+    pub fn edge(&self, edge: EdgeId) -> Edge {
+        self.store.edge(edge)
     }
 
     /// Returns the minimum spanning tree of the graph using Kruskal's algorithm.
-    pub fn mst(&mut self, calculation_type: CalculationType) -> (Option<MutableGraph>, f64, f64) {
+    pub fn mst(&self, calculation_type: CalculationType) -> Result<(MutableGraph, f64, f64), CbstuError> {
         Kruskal::run(self, calculation_type)
     }
 
     /// Returns the minimum spanning tree of the graph using Kruskal's algorithm.
     /// If the graph is not connected, the minimum spanning forest is returned.
-    pub fn mst_disconnected(&mut self, calculation_type: CalculationType) -> (ConnectionType, MutableGraph, f64, f64) {
+    pub fn mst_disconnected(&self, calculation_type: CalculationType) -> Result<(ConnectionType, MutableGraph, f64, f64), CbstuError> {
         Kruskal::run_with_disconnected(self, calculation_type)
     }
 
     /// Returns the minimum bottleneck spanning tree of the graph by using the algorithm of Camerini et al.
-    pub fn mbst(&mut self) -> (Option<MutableGraph>, f64) {
+    pub fn mbst(&self) -> Result<(MutableGraph, f64), CbstuError> {
         MBST::run(self)
     }
 
     pub fn calculate_total_cost(&self) -> f64 {
-        self.edges.iter().fold(0.0, |acc, edge| acc + edge.borrow().get_cost())
+        self.edges.iter().fold(0.0, |acc, &edge| acc + self.cost(edge))
+    }
+
+    /// Returns a new graph with the same nodes and the edges for which `keep` returns true.
+    pub fn filter(&self, mut keep: impl FnMut(EdgeId) -> bool) -> MutableGraph {
+        let edges = self.edges.iter().copied().filter(|&edge| keep(edge)).collect();
+        self.subgraph(edges)
     }
 
     /// Returns a new graph with the same nodes and edges with weight smaller or equal than given threshold.
     pub fn smaller_or_eq_than(&self, threshold: f64) -> MutableGraph {
-        let mut edges = Vec::with_capacity(self.edges.len());
-        self.edges.iter().for_each(|edge| {
-            if edge.borrow().get_weight() <= threshold {
-                edges.push(Rc::clone(edge));
-            }
-        });
-        MutableGraph { nodes: Rc::clone(&self.nodes), edges }
+        self.filter(|edge| self.weight(edge) <= threshold)
     }
 
     /// Returns a new graph with the same nodes and edges with weight bigger than given threshold.
     pub fn bigger_than(&self, threshold: f64) -> MutableGraph {
-        let mut res_edges = Vec::new();
-        self.edges.iter().for_each(|edge| {
-            if edge.borrow().get_weight() > threshold {
-                res_edges.push(Rc::clone(edge));
-            }
-        });
-        // clones only the pointer to the nodes
-        MutableGraph { nodes: Rc::clone(&self.nodes), edges: res_edges }
+        self.filter(|edge| self.weight(edge) > threshold)
     }
 
     /// Returns adjacent edges of given node. Only use for debugging.
-    fn adj_edges(&self, node_id: usize) -> Vec<Rc<RefCell<Edge>>> {
-        let mut edges = Vec::new();
-        self.edges.iter().for_each(|edge| {
-            let (u, v) = edge.borrow().endpoints();
-            if u == node_id || v == node_id {
-                edges.push(Rc::clone(edge));
-            }
-        });
-        edges
+    fn adj_edges(&self, node_id: usize) -> Vec<EdgeId> {
+        self.filter(|edge| {
+            let (u, v) = self.endpoints(edge);
+            u == node_id || v == node_id
+        }).edges
     }
 
     /// Check if graph is connected. Only use for debugging.
@@ -114,8 +213,8 @@ impl MutableGraph {
             if !visited[node_id] {
                 visited[node_id] = true;
                 let adj_edges = self.adj_edges(node_id);
-                adj_edges.iter().for_each(|edge| {
-                    let (u, v) = edge.borrow().endpoints();
+                adj_edges.iter().for_each(|&edge| {
+                    let (u, v) = self.endpoints(edge);
                     if !visited[u] {
                         stack.push(u);
                     }
@@ -150,7 +249,7 @@ impl MutableGraph {
             visited_nodes[node] = true;
             let edges = self.adj_edges(node);
             for edge in edges {
-                let (node1, node2) = edge.borrow().endpoints();
+                let (node1, node2) = self.endpoints(edge);
                 if !visited_nodes[node1] {
                     stack.push(node1);
                 }
@@ -169,28 +268,59 @@ impl MutableGraph {
         true
     }
 
-    /// Returns a new graph with the same nodes and edge inverted (upgrade) weights.
-    pub fn inverse_weights(&mut self) {
-        self.edges.iter().for_each(|edge| {
-            edge.borrow_mut().inverse_weights();
+    /// Returns the number of edges on a shortest path from `root` to every node, `usize::MAX` for nodes it does not
+    /// reach [BFS]. In a tree these are the depths of the nodes below `root`.
+    pub fn hops_from(&self, root: usize) -> Vec<usize> {
+        let mut adjacency = vec![Vec::new(); self.nodes.len()];
+        self.edges.iter().for_each(|&edge| {
+            let (u, v) = self.endpoints(edge);
+            adjacency[u].push(v);
+            adjacency[v].push(u);
         });
+        let mut hops = vec![usize::MAX; self.nodes.len()];
+        let mut queue = VecDeque::from([root]);
+        hops[root] = 0;
+        while let Some(node) = queue.pop_front() {
+            for &next in &adjacency[node] {
+                if hops[next] == usize::MAX {
+                    hops[next] = hops[node] + 1;
+                    queue.push_back(next);
+                }
+            }
+        }
+        hops
+    }
+
+    /// Returns the largest number of edges between `root` and a node of the tree, `None` if some node is not reached.
+    pub fn depth(&self, root: usize) -> Option<usize> {
+        let hops = self.hops_from(root);
+        hops.iter().copied().max().filter(|&depth| depth != usize::MAX)
+    }
+
+    /// Returns the number of edges on the longest path of the tree, `None` if the graph is not connected. The node
+    /// farthest from any node ends a longest path, so two searches suffice.
+    pub fn diameter(&self) -> Option<usize> {
+        let hops = self.hops_from(0);
+        let (end, _) = hops.iter().enumerate().max_by_key(|&(_, &hops)| hops)?;
+        match hops[end] {
+            usize::MAX => None,
+            _ => self.depth(end),
+        }
+    }
+
+    /// Inverts the (upgrade) weights of the edges of the graph. Graphs sharing the store are not affected.
+    pub fn inverse_weights(&mut self) {
+        Arc::make_mut(&mut self.store).inverse_weights(&self.edges);
     }
 
     pub fn number_of_edges_upgraded(&self) -> usize {
-        self.edges.iter().fold(0, |acc, edge| {
-            if edge.borrow().get_cost() > 0.0 {
-                acc + 1
-            } else {
-                acc
-            }
-        })
+        self.edges.iter().filter(|&&edge| self.cost(edge) > 0.0).count()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::cell::RefCell;
-    use std::rc::Rc;
+    use std::sync::Arc;
     use crate::datastructures::graph::edge::Edge;
     use crate::datastructures::graph::mutable_graph::MutableGraph;
     use crate::datastructures::graph::node::Node;
@@ -199,25 +329,52 @@ mod tests {
     fn test_get_edges_weight_lower_or_eq_than() {
         let mut nodes = Vec::new();
         let mut edges = Vec::new();
-        let node1 = Rc::new(Node::new(0, 0.0, 0.0));
-        let node2 = Rc::new(Node::new(1, 0.0, 0.0));
-        let node3 = Rc::new(Node::new(2, 0.0, 0.0));
-        let node4 = Rc::new(Node::new(3, 0.0, 0.0));
-        nodes.push(Rc::clone(&node1));
-        nodes.push(Rc::clone(&node2));
-        nodes.push(Rc::clone(&node3));
-        nodes.push(Rc::clone(&node4));
-
-        edges.push(Rc::new(RefCell::new(Edge::new(0, 1).weight(1.0))));
-        edges.push(Rc::new(RefCell::new(Edge::new(0, 2).weight(2.0))));
-        edges.push(Rc::new(RefCell::new(Edge::new(0, 3).weight(3.0))));
-        edges.push(Rc::new(RefCell::new(Edge::new(1, 2).weight(4.0))));
-        edges.push(Rc::new(RefCell::new(Edge::new(1, 3).weight(5.0))));
-        edges.push(Rc::new(RefCell::new(Edge::new(2, 3).weight(6.0))));
-        let graph = MutableGraph::new(Rc::new(nodes), edges);
+        nodes.push(Node::new(0, 0.0, 0.0));
+        nodes.push(Node::new(1, 0.0, 0.0));
+        nodes.push(Node::new(2, 0.0, 0.0));
+        nodes.push(Node::new(3, 0.0, 0.0));
+
+        edges.push(Edge::new(0, 1).weight(1.0));
+        edges.push(Edge::new(0, 2).weight(2.0));
+        edges.push(Edge::new(0, 3).weight(3.0));
+        edges.push(Edge::new(1, 2).weight(4.0));
+        edges.push(Edge::new(1, 3).weight(5.0));
+        edges.push(Edge::new(2, 3).weight(6.0));
+        let graph = MutableGraph::new(Arc::new(nodes), edges);
         let graph2 = graph.smaller_or_eq_than(3.0);
         assert_eq!(graph2.edges().len(), 3);
-        //check number of pointers in rc
-        assert_eq!(Rc::strong_count(&graph2.edges()[0]), 2);
+        //check that the edges are shared, not copied
+        assert!(Arc::ptr_eq(graph.store(), graph2.store()));
+        assert_eq!(Arc::strong_count(graph.store()), 2);
+    }
+
+    #[test]
+    fn test_inverse_weights_copy_on_write() {
+        let nodes = (0..3).map(Node::default).collect();
+        let edges = vec![Edge::new(0, 1).weight(1.0).upgraded_weight(2.0), Edge::new(1, 2).weight(3.0).upgraded_weight(4.0)];
+        let graph = MutableGraph::new(Arc::new(nodes), edges);
+        let mut inverted = graph.clone();
+        inverted.inverse_weights();
+        assert_eq!(graph.weight(0), 1.0);
+        assert_eq!((inverted.weight(0), inverted.upgraded_weight(1)), (-1.0, -4.0));
+        assert!(!Arc::ptr_eq(graph.store(), inverted.store()));
+    }
+
+    #[test]
+    fn test_depth_and_diameter() {
+        let nodes = Arc::new((0..5).map(Node::default).collect::<Vec<Node>>());
+        // path 0 - 1 - 2 - 3 with 4 hanging off 1
+        let edges = vec![Edge::new(0, 1), Edge::new(1, 2), Edge::new(2, 3), Edge::new(4, 1)];
+        let tree = MutableGraph::new(Arc::clone(&nodes), edges);
+        assert_eq!(tree.hops_from(1), vec![1, 0, 1, 2, 1]);
+        assert_eq!((tree.depth(0), tree.depth(1), tree.diameter()), (Some(3), Some(2), Some(3)));
+        let forest = tree.subgraph(vec![0, 1, 2]);
+        assert_eq!((forest.depth(0), forest.diameter()), (None, None));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<MutableGraph>();
+    }
+}
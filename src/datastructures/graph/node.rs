@@ -1,16 +1,57 @@
+/// Site of the network. Upgrading a node costs `upgrade_cost`; it multiplies the capacity of its incident links by
+/// `multiplier` and raises the capacity of the node itself, which caps its links, from `capacity` to
+/// `upgraded_capacity`. Without upgrade data a node never limits its links. The optional `region` decides which
+/// regional budget pays for upgrades of its links, the optional `max_degree` caps the number of tree links at the node.
 #[derive(Debug, Clone)]
 pub struct Node {
     id: usize,
     x: f64,
     y: f64,
+    capacity: f64,
+    upgraded_capacity: f64,
+    multiplier: f64,
+    upgrade_cost: f64,
+    region: Option<String>,
+    max_degree: Option<usize>,
 }
 
 impl Node {
     pub fn new(id: usize, x: f64, y: f64) -> Node {
-        Node { id, x, y }
+        Node {
+            id,
+            x,
+            y,
+            capacity: f64::INFINITY,
+            upgraded_capacity: f64::INFINITY,
+            multiplier: 1.0,
+            upgrade_cost: 0.0,
+            region: None,
+            max_degree: None,
+        }
     }
     pub fn default(id: usize) -> Node {
-        Node { id, x: 0.0, y: 0.0 }
+        Node::new(id, 0.0, 0.0)
+    }
+    pub fn capacity(mut self, capacity: f64, upgraded_capacity: f64) -> Node {
+        self.capacity = capacity;
+        self.upgraded_capacity = upgraded_capacity;
+        self
+    }
+    pub fn multiplier(mut self, multiplier: f64) -> Node {
+        self.multiplier = multiplier;
+        self
+    }
+    pub fn upgrade_cost(mut self, upgrade_cost: f64) -> Node {
+        self.upgrade_cost = upgrade_cost;
+        self
+    }
+    pub fn region(mut self, region: &str) -> Node {
+        self.region = Some(String::from(region));
+        self
+    }
+    pub fn max_degree(mut self, max_degree: usize) -> Node {
+        self.max_degree = Some(max_degree);
+        self
     }
     pub fn id(&self) -> usize {
         self.id
@@ -21,6 +62,28 @@ impl Node {
     pub fn y(&self) -> f64 {
         self.y
     }
+    pub fn get_capacity(&self) -> f64 {
+        self.capacity
+    }
+    pub fn get_upgraded_capacity(&self) -> f64 {
+        self.upgraded_capacity
+    }
+    pub fn get_multiplier(&self) -> f64 {
+        self.multiplier
+    }
+    pub fn get_upgrade_cost(&self) -> f64 {
+        self.upgrade_cost
+    }
+    pub fn get_region(&self) -> Option<&str> {
+        self.region.as_deref()
+    }
+    pub fn get_max_degree(&self) -> Option<usize> {
+        self.max_degree
+    }
+    /// True if upgrading the node changes any of its links.
+    pub fn is_upgradable(&self) -> bool {
+        self.multiplier != 1.0 || self.upgraded_capacity != self.capacity
+    }
 }
 
 impl PartialEq for Node {
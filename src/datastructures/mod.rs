@@ -1,3 +1,2 @@
 pub mod graph;
-pub mod uf;
-pub mod garbage;
\ No newline at end of file
+pub mod uf;
\ No newline at end of file
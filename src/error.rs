@@ -0,0 +1,63 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// Errors returned by the public entry points of the crate.
+#[derive(Debug)]
+pub enum CbstuError {
+    /// Reading or writing a file failed.
+    Io(io::Error),
+    /// The input is not valid JSON or misses/mistypes a required field.
+    MalformedInput(String),
+    /// No spanning tree exists because the graph is not connected.
+    DisconnectedGraph,
+    /// The graph has no edges to build a tree from.
+    EmptyEdgeSet,
+    /// Every spanning tree exceeds the upgrade budget.
+    InfeasibleBudget,
+    /// No spanning tree respects the maximum degrees of the nodes, whatever the budget.
+    InfeasibleDegrees,
+    /// No spanning tree respects the hop limit, whatever the budget.
+    InfeasibleHops,
+    /// No spanning tree reaches the requested bottleneck, not even with every link upgraded.
+    UnreachableBottleneck,
+    /// The link constraints cannot all hold, `links` are the links involved.
+    ConstraintConflict { reason: String, links: Vec<(usize, usize)> },
+}
+
+impl fmt::Display for CbstuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CbstuError::Io(err) => write!(f, "I/O error: {}", err),
+            CbstuError::MalformedInput(msg) => write!(f, "malformed input: {}", msg),
+            CbstuError::DisconnectedGraph => write!(f, "graph is disconnected"),
+            CbstuError::EmptyEdgeSet => write!(f, "graph has no edges"),
+            CbstuError::InfeasibleBudget => write!(f, "no spanning tree fits within the budget"),
+            CbstuError::InfeasibleDegrees => write!(f, "no spanning tree respects the degree bounds"),
+            CbstuError::InfeasibleHops => write!(f, "no spanning tree respects the hop limit"),
+            CbstuError::UnreachableBottleneck => write!(f, "no spanning tree reaches the target bottleneck"),
+            CbstuError::ConstraintConflict { reason, links } => write!(f, "link constraints conflict, {}: {:?}", reason, links),
+        }
+    }
+}
+
+impl Error for CbstuError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CbstuError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for CbstuError {
+    fn from(err: io::Error) -> CbstuError {
+        CbstuError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for CbstuError {
+    fn from(err: serde_json::Error) -> CbstuError {
+        CbstuError::MalformedInput(err.to_string())
+    }
+}
@@ -0,0 +1,73 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use log::info;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use crate::datastructures::graph::edge::Edge;
+use crate::datastructures::graph::mutable_graph::MutableGraph;
+use crate::datastructures::graph::node::Node;
+use crate::error::CbstuError;
+
+pub struct Generator();
+
+impl Generator {
+    /// Random connected instance with `nodes` nodes and `edges` links. A random spanning tree is laid down first so the
+    /// instance is always connected, the remaining links are drawn between random node pairs. Every link gets an
+    /// integer capacity `k`, an upgraded capacity `kBar > k` and an upgrade cost `c`.
+    pub fn random_instance(nodes: usize, edges: usize, seed: u64) -> Result<MutableGraph, CbstuError> {
+        if nodes < 2 || edges < nodes - 1 || edges > nodes * (nodes - 1) / 2 {
+            return Err(CbstuError::MalformedInput(format!("cannot build a simple connected graph with {} nodes and {} edges", nodes, edges)));
+        }
+        info!("Generating instance with {} nodes and {} edges [seed {}]", nodes, edges, seed);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let node_list: Vec<Node> = (0..nodes)
+            .map(|id| Node::new(id, rng.gen_range(0.0..100.0), rng.gen_range(0.0..100.0)))
+            .collect();
+        let mut pairs = HashSet::new();
+        for v in 1..nodes {
+            let w = rng.gen_range(0..v);
+            pairs.insert((w, v));
+        }
+        while pairs.len() < edges {
+            let v = rng.gen_range(0..nodes);
+            let w = rng.gen_range(0..nodes);
+            if v != w {
+                pairs.insert((v.min(w), v.max(w)));
+            }
+        }
+        let mut pairs: Vec<(usize, usize)> = pairs.into_iter().collect();
+        pairs.sort();
+        let edge_list = pairs.into_iter().map(|(v, w)| {
+            let weight = rng.gen_range(1..50) as f64;
+            let upgraded_weight = weight + rng.gen_range(1..50) as f64;
+            let cost = rng.gen_range(1..100) as f64;
+            Edge::new(v, w).weight(weight).upgraded_weight(upgraded_weight).cost(cost)
+        }).collect();
+        Ok(MutableGraph::new(Arc::new(node_list), edge_list))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::CbstuError;
+    use crate::io::generator::Generator;
+    use crate::io::input_handler::InputHandler;
+    use crate::io::output_handler::OutputHandler;
+
+    #[test]
+    fn test_random_instance() {
+        let graph = Generator::random_instance(20, 40, 7).unwrap();
+        assert_eq!(graph.nodes().len(), 20);
+        assert_eq!(graph.edges().len(), 40);
+        assert!(graph.is_connected_graph());
+        for &edge in graph.edges() {
+            assert!(graph.upgraded_weight(edge) > graph.weight(edge));
+        }
+        let same = Generator::random_instance(20, 40, 7).unwrap();
+        assert_eq!(OutputHandler::instance_json(&graph), OutputHandler::instance_json(&same));
+        let read = InputHandler::parse_mut(&OutputHandler::instance_json(&graph).to_string()).unwrap();
+        assert_eq!(read.edges().len(), 40);
+        assert!(matches!(Generator::random_instance(5, 3, 0), Err(CbstuError::MalformedInput(_))));
+        assert!(matches!(Generator::random_instance(4, 7, 0), Err(CbstuError::MalformedInput(_))));
+    }
+}
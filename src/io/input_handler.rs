@@ -1,43 +1,533 @@
-use std::cell::RefCell;
 use std::fs;
-use std::rc::Rc;
+use std::sync::Arc;
 use log::info;
+use serde_json::Value;
+use crate::algorithms::constrained_bottleneck_spanning_tree::discount::{DiscountBasis, DiscountCurve};
+use crate::algorithms::constrained_bottleneck_spanning_tree::solver::Solution;
+use crate::algorithms::continuous_bottleneck_spanning_tree::cost_curve::CostCurve;
+use crate::algorithms::continuous_bottleneck_spanning_tree::parametric::ContinuousInstance;
 use crate::datastructures::graph::edge::Edge;
 use crate::datastructures::graph::mutable_graph::MutableGraph;
 use crate::datastructures::graph::node::Node;
+use crate::error::CbstuError;
 
 pub struct InputHandler();
 
 impl InputHandler {
-    pub fn read_mut(file_name: &str) -> MutableGraph {
+    pub fn read_mut(file_name: &str) -> Result<MutableGraph, CbstuError> {
         info!("Reading file {}", file_name);
-        let data = fs::read_to_string(file_name).expect("Unable to read file");
-        let json: serde_json::Value = serde_json::from_str(&data).expect("JSON was not well-formatted");
-        // Read nodes
-        let mut nodes = Vec::new();
-        let json_nodes = &json["nodes"];
-        for node in json_nodes.as_array().unwrap() {
-            let id = node["id"].as_i64().unwrap() as usize;
-            let x = node["x"].as_f64().unwrap();
-            let y = node["y"].as_f64().unwrap();
-            let node = Node::new(id, x, y);
-            nodes.push(Rc::new(node));
-        }
+        let data = fs::read_to_string(file_name)?;
+        Self::parse_mut(&data)
+    }
+
+    pub fn parse_mut(data: &str) -> Result<MutableGraph, CbstuError> {
+        Ok(Self::parse_bundled(data)?.0)
+    }
+
+    /// Reads an instance together with the fixed charges of its upgrade bundles. A bundle lists the positions of its
+    /// members in the `links` array and its charge `c`, as in `"bundles": [{"links": [0, 3], "c": 5.0}]`.
+    pub fn read_bundled(file_name: &str) -> Result<(MutableGraph, Vec<f64>), CbstuError> {
+        info!("Reading file {}", file_name);
+        let data = fs::read_to_string(file_name)?;
+        Self::parse_bundled(&data)
+    }
+
+    pub fn parse_bundled(data: &str) -> Result<(MutableGraph, Vec<f64>), CbstuError> {
+        let json: Value = serde_json::from_str(data)?;
+        let nodes = Self::nodes(&json)?;
+        // Links are undirected unless the instance or the link itself says otherwise
+        let directed = Self::optional_bool_field(&json, "directed")?;
         // Read edges
         let mut edges = Vec::new();
-        let json_edges = &json["links"];
-        for edge in json_edges.as_array().unwrap() {
-            let either = edge["sourceId"].as_i64().unwrap() as usize;
-            let other = edge["targetId"].as_i64().unwrap() as usize;
+        for edge in Self::array(&json, "links")? {
+            let either = Self::node_id_field(edge, "sourceId", nodes.len())?;
+            let other = Self::node_id_field(edge, "targetId", nodes.len())?;
+            let link = Self::tiers(edge, "kBar", Edge::new(either, other).weight(Self::f64_field(edge, "k")?))?;
+            edges.push(Self::direction(edge, Self::constraints(edge, link)?, directed)?);
+        }
+        // Read upgrade bundles of the links
+        let mut bundle_costs = Vec::new();
+        if let Some(bundles) = json.get("bundles") {
+            for bundle in bundles.as_array().ok_or_else(|| CbstuError::MalformedInput(String::from("field 'bundles' must be an array")))? {
+                let cost = Self::f64_field(bundle, "c")?;
+                if cost < 0.0 {
+                    return Err(CbstuError::MalformedInput(format!("negative cost of bundle {}", bundle)));
+                }
+                for member in Self::array(bundle, "links")? {
+                    let link = member.as_u64().map(|link| link as usize).filter(|&link| link < edges.len())
+                        .ok_or_else(|| CbstuError::MalformedInput(format!("bundle {} refers to unknown link {}", bundle_costs.len(), member)))?;
+                    if edges[link].get_bundle().is_some() {
+                        return Err(CbstuError::MalformedInput(format!("link {} is in more than one bundle", link)));
+                    }
+                    edges[link] = edges[link].clone().bundle(Some(bundle_costs.len()));
+                }
+                bundle_costs.push(cost);
+            }
+        }
+        // Read candidate links, they only exist once built
+        if let Some(candidates) = json.get("candidates") {
+            for candidate in candidates.as_array().ok_or_else(|| CbstuError::MalformedInput(String::from("field 'candidates' must be an array")))? {
+                let either = Self::node_id_field(candidate, "sourceId", nodes.len())?;
+                let other = Self::node_id_field(candidate, "targetId", nodes.len())?;
+                let link = Self::tiers(candidate, "k", Edge::new(either, other).candidate(true))?;
+                edges.push(Self::direction(candidate, Self::constraints(candidate, link)?, directed)?);
+            }
+        }
+        if edges.is_empty() {
+            return Err(CbstuError::EmptyEdgeSet);
+        }
+        info!("Read {} nodes, {} edges and {} bundles", nodes.len(), edges.len(), bundle_costs.len());
+        Ok((MutableGraph::new(Arc::new(nodes), edges), bundle_costs))
+    }
+
+    /// Reads an instance together with its optional volume discount, given as
+    /// `"discount": {"basis": "sum", "points": [{"total": 10, "c": 8}, ...]}` with basis `sum` or `count`.
+    pub fn read_discounted(file_name: &str) -> Result<(MutableGraph, Option<DiscountCurve>), CbstuError> {
+        info!("Reading file {}", file_name);
+        let data = fs::read_to_string(file_name)?;
+        Self::parse_discounted(&data)
+    }
+
+    pub fn parse_discounted(data: &str) -> Result<(MutableGraph, Option<DiscountCurve>), CbstuError> {
+        let graph = Self::parse_mut(data)?;
+        let json: Value = serde_json::from_str(data)?;
+        let discount = match json.get("discount") {
+            Some(discount) => {
+                let basis = match discount["basis"].as_str() {
+                    Some("sum") => DiscountBasis::Sum,
+                    Some("count") => DiscountBasis::Count,
+                    _ => return Err(CbstuError::MalformedInput(format!("field 'basis' must be 'sum' or 'count' in {}", discount))),
+                };
+                let points = Self::array(discount, "points")?.iter()
+                    .map(|point| Ok((Self::f64_field(point, "total")?, Self::f64_field(point, "c")?)))
+                    .collect::<Result<Vec<_>, CbstuError>>()?;
+                Some(DiscountCurve::piecewise(basis, points)?)
+            }
+            None => None,
+        };
+        Ok((graph, discount))
+    }
+
+    /// Reads an instance together with its Steiner terminals, the node ids listed as `"terminals": [0, 4, 7]`. Without
+    /// that field every node is a terminal.
+    pub fn read_terminals(file_name: &str) -> Result<(MutableGraph, Vec<usize>), CbstuError> {
+        info!("Reading file {}", file_name);
+        let data = fs::read_to_string(file_name)?;
+        Self::parse_terminals(&data)
+    }
+
+    pub fn parse_terminals(data: &str) -> Result<(MutableGraph, Vec<usize>), CbstuError> {
+        let graph = Self::parse_mut(data)?;
+        let json: Value = serde_json::from_str(data)?;
+        let terminals = match json.get("terminals") {
+            Some(_) => Self::array(&json, "terminals")?.iter()
+                .map(|terminal| match terminal.as_u64().map(|id| id as usize) {
+                    Some(id) if id < graph.nodes().len() => Ok(id),
+                    _ => Err(CbstuError::MalformedInput(format!("terminal {} is not a node id", terminal))),
+                })
+                .collect::<Result<Vec<usize>, CbstuError>>()?,
+            None => (0..graph.nodes().len()).collect(),
+        };
+        Ok((graph, terminals))
+    }
+
+    /// Reads an instance with continuous upgrades. A link is upgraded up to `kBar` along a `curve` of `kBar`/`c`
+    /// points, at a per-unit `rate`, or linearly to the full cost `c` of the standard format.
+    pub fn read_continuous(file_name: &str) -> Result<ContinuousInstance, CbstuError> {
+        info!("Reading file {}", file_name);
+        let data = fs::read_to_string(file_name)?;
+        Self::parse_continuous(&data)
+    }
+
+    pub fn parse_continuous(data: &str) -> Result<ContinuousInstance, CbstuError> {
+        let json: Value = serde_json::from_str(data)?;
+        let nodes = Self::nodes(&json)?;
+        let mut links = Vec::new();
+        for link in Self::array(&json, "links")? {
+            let either = Self::node_id_field(link, "sourceId", nodes.len())?;
+            let other = Self::node_id_field(link, "targetId", nodes.len())?;
+            let k = Self::f64_field(link, "k")?;
+            let curve = match (link.get("curve"), link.get("rate")) {
+                (Some(_), _) => CostCurve::piecewise(k, Self::array(link, "curve")?.iter()
+                    .map(|point| Ok((Self::f64_field(point, "kBar")?, Self::f64_field(point, "c")?)))
+                    .collect::<Result<Vec<_>, CbstuError>>()?)?,
+                (None, Some(_)) => CostCurve::linear(k, Self::f64_field(link, "kBar")?, Self::f64_field(link, "rate")?)?,
+                (None, None) => CostCurve::piecewise(k, vec![(Self::f64_field(link, "kBar")?, Self::f64_field(link, "c")?)])?,
+            };
+            links.push((either, other, curve));
+        }
+        if links.is_empty() {
+            return Err(CbstuError::EmptyEdgeSet);
+        }
+        info!("Read {} nodes and {} edges", nodes.len(), links.len());
+        Ok(ContinuousInstance::new(Arc::new(nodes), links))
+    }
+
+    /// Reads a solution written by [`OutputHandler`](crate::io::output_handler::OutputHandler). The tree is built on the
+    /// nodes of the given instance with inverted weights, like the trees returned by the solvers.
+    pub fn read_solution(file_name: &str, graph: &MutableGraph) -> Result<Solution, CbstuError> {
+        info!("Reading solution {}", file_name);
+        let data = fs::read_to_string(file_name)?;
+        Self::parse_solution(&data, graph)
+    }
+
+    pub fn parse_solution(data: &str, graph: &MutableGraph) -> Result<Solution, CbstuError> {
+        let json: Value = serde_json::from_str(data)?;
+        match json["status"].as_str() {
+            Some("feasible") => {}
+            Some("infeasible") => return Ok(Solution::infeasible()),
+            _ => return Err(CbstuError::MalformedInput(String::from("field 'status' must be 'feasible' or 'infeasible'"))),
+        }
+        let mut edges = Vec::new();
+        for link in Self::array(&json, "links")? {
+            let either = Self::node_id_field(link, "sourceId", graph.nodes().len())?;
+            let other = Self::node_id_field(link, "targetId", graph.nodes().len())?;
+            let upgraded = link["upgraded"].as_bool()
+                .ok_or_else(|| CbstuError::MalformedInput(format!("missing or non-boolean field 'upgraded' in {}", link)))?;
             let edge = Edge::new(either, other)
-                .weight(edge["k"].as_f64().unwrap())
-                .upgraded_weight(edge["kBar"].as_f64().unwrap())
-                .cost(edge["c"].as_f64().unwrap());
-            edges.push(Rc::new(RefCell::new(edge)));
+                .weight(-Self::f64_field(link, "k")?)
+                .cost(Self::f64_field(link, "c")?)
+                .upgraded(upgraded)
+                .candidate(link["built"].as_bool().unwrap_or(false));
+            edges.push(edge);
+        }
+        let cost = Self::f64_field(&json, "cost")?;
+        let bottleneck = -Self::f64_field(&json, "bottleneck")?;
+        Ok(Solution::feasible(MutableGraph::new(graph.nodes_copy(), edges), cost, bottleneck))
+    }
+
+    /// Adds the upgrade tiers of a link: either a single capacity/`c` pair or a `tiers` array of them. The capacity is
+    /// `kBar` for the upgrades of links and `k` for the capacity of candidate links, `c` is a cost or a cost vector.
+    fn tiers(link: &Value, capacity_key: &str, edge: Edge) -> Result<Edge, CbstuError> {
+        let tiers = match link.get("tiers") {
+            Some(tiers) => tiers.as_array().filter(|tiers| !tiers.is_empty())
+                .ok_or_else(|| CbstuError::MalformedInput(format!("field 'tiers' must be a non-empty array in {}", link)))?
+                .iter()
+                .map(|tier| Ok((Self::f64_field(tier, capacity_key)?, Self::costs_field(tier, "c")?)))
+                .collect::<Result<Vec<_>, CbstuError>>()?,
+            None => vec![(Self::f64_field(link, capacity_key)?, Self::costs_field(link, "c")?)],
+        };
+        let edge = edge.upgraded_weight(tiers[0].0).costs(tiers[0].1.clone());
+        Ok(tiers[1..].iter().fold(edge, |edge, (upgraded_weight, costs)| edge.tier_costs(*upgraded_weight, costs.clone())))
+    }
+
+    /// Adds the optional `required`, `forbidden`, `upgradeRequired` and `upgradeForbidden` flags of a link.
+    fn constraints(link: &Value, edge: Edge) -> Result<Edge, CbstuError> {
+        Ok(edge
+            .required(Self::optional_bool_field(link, "required")?)
+            .forbidden(Self::optional_bool_field(link, "forbidden")?)
+            .upgrade_required(Self::optional_bool_field(link, "upgradeRequired")?)
+            .upgrade_forbidden(Self::optional_bool_field(link, "upgradeForbidden")?))
+    }
+
+    /// Sets the optional `directed` flag of a link, `default` when the link has none. A directed link runs from its
+    /// `sourceId` to its `targetId`.
+    fn direction(link: &Value, edge: Edge, default: bool) -> Result<Edge, CbstuError> {
+        match link.get("directed") {
+            Some(_) => Ok(edge.directed(Self::optional_bool_field(link, "directed")?)),
+            None => Ok(edge.directed(default)),
+        }
+    }
+
+    /// Reads the nodes with their optional upgrade data: `capacity`, `upgradedCapacity`, `multiplier` and
+    /// `upgradeCost`, their optional `region` and their optional `maxDegree` in the tree.
+    fn nodes(json: &Value) -> Result<Vec<Node>, CbstuError> {
+        let mut nodes = Vec::new();
+        for node_json in Self::array(json, "nodes")? {
+            let id = Self::usize_field(node_json, "id")?;
+            let x = Self::f64_field(node_json, "x")?;
+            let y = Self::f64_field(node_json, "y")?;
+            let capacity = Self::optional_f64_field(node_json, "capacity", f64::INFINITY)?;
+            let upgraded_capacity = Self::optional_f64_field(node_json, "upgradedCapacity", capacity)?;
+            let multiplier = Self::optional_f64_field(node_json, "multiplier", 1.0)?;
+            let upgrade_cost = Self::optional_f64_field(node_json, "upgradeCost", 0.0)?;
+            if upgraded_capacity < capacity || multiplier < 1.0 || upgrade_cost < 0.0 {
+                return Err(CbstuError::MalformedInput(format!("upgrade of node {} lowers its capacity or has a negative cost", id)));
+            }
+            let node = Node::new(id, x, y).capacity(capacity, upgraded_capacity).multiplier(multiplier).upgrade_cost(upgrade_cost);
+            let node = match node_json.get("region") {
+                Some(region) => node.region(region.as_str()
+                    .ok_or_else(|| CbstuError::MalformedInput(format!("non-string field 'region' in {}", node_json)))?),
+                None => node,
+            };
+            nodes.push(match node_json.get("maxDegree") {
+                Some(_) => node.max_degree(Self::usize_field(node_json, "maxDegree")?),
+                None => node,
+            });
+        }
+        Ok(nodes)
+    }
+
+    fn array<'a>(json: &'a Value, key: &str) -> Result<&'a Vec<Value>, CbstuError> {
+        json[key].as_array().ok_or_else(|| CbstuError::MalformedInput(format!("missing array '{}'", key)))
+    }
+
+    fn f64_field(value: &Value, key: &str) -> Result<f64, CbstuError> {
+        value[key].as_f64().ok_or_else(|| CbstuError::MalformedInput(format!("missing or non-numeric field '{}' in {}", key, value)))
+    }
+
+    /// Reads a cost that is either a number or an array with one number per budget resource.
+    fn costs_field(value: &Value, key: &str) -> Result<Vec<f64>, CbstuError> {
+        match value[key].as_array() {
+            Some(costs) if !costs.is_empty() => costs.iter()
+                .map(|cost| cost.as_f64().ok_or_else(|| CbstuError::MalformedInput(format!("non-numeric entry in field '{}' in {}", key, value))))
+                .collect(),
+            Some(_) => Err(CbstuError::MalformedInput(format!("empty array '{}' in {}", key, value))),
+            None => Ok(vec![Self::f64_field(value, key)?]),
+        }
+    }
+
+    fn optional_f64_field(value: &Value, key: &str, default: f64) -> Result<f64, CbstuError> {
+        match value.get(key) {
+            Some(_) => Self::f64_field(value, key),
+            None => Ok(default),
         }
-        info!("Read {} nodes and {} edges", nodes.len(), edges.len());
-        let graph = MutableGraph::new(Rc::new(nodes), edges);
-        debug_assert!(graph.is_connected_graph());
-        graph
     }
-}
\ No newline at end of file
+
+    fn optional_bool_field(value: &Value, key: &str) -> Result<bool, CbstuError> {
+        match value.get(key) {
+            Some(flag) => flag.as_bool().ok_or_else(|| CbstuError::MalformedInput(format!("non-boolean field '{}' in {}", key, value))),
+            None => Ok(false),
+        }
+    }
+
+    fn usize_field(value: &Value, key: &str) -> Result<usize, CbstuError> {
+        value[key].as_u64().map(|v| v as usize).ok_or_else(|| CbstuError::MalformedInput(format!("missing or non-integer field '{}' in {}", key, value)))
+    }
+
+    /// Reads an endpoint id and checks that it refers to one of the `n` nodes.
+    fn node_id_field(value: &Value, key: &str, n: usize) -> Result<usize, CbstuError> {
+        let id = Self::usize_field(value, key)?;
+        if id >= n {
+            return Err(CbstuError::MalformedInput(format!("field '{}' refers to unknown node {}", key, id)));
+        }
+        Ok(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithms::constrained_bottleneck_spanning_tree::discount::DiscountBasis;
+    use crate::algorithms::constrained_bottleneck_spanning_tree::solver::solver_by_name;
+    use crate::algorithms::util::Util;
+    use crate::error::CbstuError;
+    use crate::io::input_handler::InputHandler;
+    use crate::io::output_handler::OutputHandler;
+
+    #[test]
+    fn test_parse() {
+        let graph = InputHandler::parse_mut(r#"{
+            "nodes": [{"id": 0, "x": 0.0, "y": 0.0}, {"id": 1, "x": 1.0, "y": 1.0}],
+            "links": [{"sourceId": 0, "targetId": 1, "k": 2.0, "kBar": 4.0, "c": 3.0}]
+        }"#).unwrap();
+        assert_eq!(graph.nodes().len(), 2);
+        assert_eq!(graph.upgraded_weight(graph.edges()[0]), 4.0);
+    }
+
+    #[test]
+    fn test_parse_tiers() {
+        let data = r#"{
+            "nodes": [{"id": 0, "x": 0.0, "y": 0.0}, {"id": 1, "x": 1.0, "y": 1.0}],
+            "links": [{"sourceId": 0, "targetId": 1, "k": 10.0, "tiers": [{"kBar": 40.0, "c": 3.0}, {"kBar": 100.0, "c": 8.0}]}]
+        }"#;
+        let graph = InputHandler::parse_mut(data).unwrap();
+        let edge = graph.edges()[0];
+        assert_eq!(graph.tiers(edge).collect::<Vec<_>>(), vec![(40.0, 3.0), (100.0, 8.0)]);
+        let read = InputHandler::parse_mut(&OutputHandler::instance_json(&graph).to_string()).unwrap();
+        assert_eq!(read.edge(read.edges()[0]), graph.edge(edge));
+        assert!(matches!(InputHandler::parse_mut(&data.replace(r#"{"kBar": 100.0, "c": 8.0}"#, r#"{"kBar": 100.0}"#)), Err(CbstuError::MalformedInput(_))));
+        assert!(matches!(InputHandler::parse_mut(&data.replace(r#"[{"kBar": 40.0, "c": 3.0}, {"kBar": 100.0, "c": 8.0}]"#, "[]")), Err(CbstuError::MalformedInput(_))));
+    }
+
+    #[test]
+    fn test_parse_continuous() {
+        let instance = InputHandler::parse_continuous(r#"{
+            "nodes": [{"id": 0, "x": 0.0, "y": 0.0}, {"id": 1, "x": 1.0, "y": 1.0}, {"id": 2, "x": 2.0, "y": 2.0}],
+            "links": [{"sourceId": 0, "targetId": 1, "k": 2.0, "kBar": 10.0, "rate": 1.5},
+                      {"sourceId": 1, "targetId": 2, "k": 1.0, "curve": [{"kBar": 5.0, "c": 1.0}, {"kBar": 20.0, "c": 31.0}]},
+                      {"sourceId": 0, "targetId": 2, "k": 4.0, "kBar": 6.0, "c": 3.0}]
+        }"#).unwrap();
+        assert_eq!(instance.curve(0).full_cost(), 12.0);
+        assert_eq!(instance.curve(1).points(), &vec![(1.0, 0.0), (5.0, 1.0), (20.0, 31.0)]);
+        assert_eq!(instance.curve(2).cost_at(5.0), Some(1.5));
+        assert!(matches!(InputHandler::parse_continuous(r#"{"nodes": [{"id": 0, "x": 0.0, "y": 0.0}, {"id": 1, "x": 1.0, "y": 1.0}],
+            "links": [{"sourceId": 0, "targetId": 1, "k": 2.0, "curve": [{"kBar": 1.0, "c": 1.0}]}]}"#), Err(CbstuError::MalformedInput(_))));
+    }
+
+    #[test]
+    fn test_parse_node_upgrades() {
+        let data = r#"{
+            "nodes": [{"id": 0, "x": 0.0, "y": 0.0, "capacity": 4.0, "upgradedCapacity": 10.0, "upgradeCost": 5.0, "region": "north", "maxDegree": 3},
+                      {"id": 1, "x": 1.0, "y": 1.0, "multiplier": 2.0, "upgradeCost": 1.0}],
+            "links": [{"sourceId": 0, "targetId": 1, "k": 2.0, "kBar": 4.0, "c": 3.0}]
+        }"#;
+        let graph = InputHandler::parse_mut(data).unwrap();
+        let nodes = graph.nodes();
+        assert_eq!((nodes[0].get_capacity(), nodes[0].get_upgraded_capacity(), nodes[0].get_upgrade_cost()), (4.0, 10.0, 5.0));
+        assert_eq!((nodes[1].get_capacity(), nodes[1].get_multiplier()), (f64::INFINITY, 2.0));
+        let read = InputHandler::parse_mut(&OutputHandler::instance_json(&graph).to_string()).unwrap();
+        assert_eq!(read.nodes()[0].get_upgraded_capacity(), 10.0);
+        assert_eq!(read.nodes()[1].get_multiplier(), 2.0);
+        assert_eq!((read.nodes()[0].get_region(), read.nodes()[1].get_region()), (Some("north"), None));
+        assert_eq!((read.nodes()[0].get_max_degree(), read.nodes()[1].get_max_degree()), (Some(3), None));
+        assert!(matches!(InputHandler::parse_mut(&data.replace(r#""maxDegree": 3"#, r#""maxDegree": -3"#)), Err(CbstuError::MalformedInput(_))));
+        assert!(matches!(InputHandler::parse_mut(&data.replace(r#""multiplier": 2.0"#, r#""multiplier": 0.5"#)), Err(CbstuError::MalformedInput(_))));
+        assert!(matches!(InputHandler::parse_mut(&data.replace(r#""north""#, "1")), Err(CbstuError::MalformedInput(_))));
+    }
+
+    #[test]
+    fn test_parse_candidates() {
+        let mut graph = InputHandler::parse_mut(r#"{
+            "nodes": [{"id": 0, "x": 0.0, "y": 0.0}, {"id": 1, "x": 1.0, "y": 1.0}, {"id": 2, "x": 2.0, "y": 2.0}],
+            "links": [{"sourceId": 0, "targetId": 1, "k": 5.0, "kBar": 9.0, "c": 6.0},
+                      {"sourceId": 1, "targetId": 2, "k": 1.0, "kBar": 3.0, "c": 10.0}],
+            "candidates": [{"sourceId": 0, "targetId": 2, "k": 8.0, "c": 4.0}]
+        }"#).unwrap();
+        assert!(graph.is_candidate(2));
+        assert_eq!(graph.tiers(2).collect::<Vec<_>>(), vec![(8.0, 4.0)]);
+        let read = InputHandler::parse_mut(&OutputHandler::instance_json(&graph).to_string()).unwrap();
+        assert_eq!(read.edge(2), graph.edge(2));
+        graph.inverse_weights();
+        let graph = Util::duplicate_edges(&graph);
+        let solution = solver_by_name("berman").unwrap().solve(&graph, 4.0).unwrap();
+        let json = OutputHandler::solution_json(&solution, "berman", 4.0);
+        assert_eq!(json["builds"].as_array().unwrap().len(), 1);
+        let read = InputHandler::parse_solution(&json.to_string(), &graph).unwrap();
+        assert_eq!(read.built_edges(), solution.built_edges());
+    }
+
+    #[test]
+    fn test_parse_constraints() {
+        let data = r#"{
+            "nodes": [{"id": 0, "x": 0.0, "y": 0.0}, {"id": 1, "x": 1.0, "y": 1.0}, {"id": 2, "x": 2.0, "y": 2.0}],
+            "links": [{"sourceId": 0, "targetId": 1, "k": 5.0, "kBar": 9.0, "c": 6.0, "required": true, "upgradeForbidden": true},
+                      {"sourceId": 1, "targetId": 2, "k": 1.0, "kBar": 3.0, "c": 10.0, "upgradeRequired": true}],
+            "candidates": [{"sourceId": 0, "targetId": 2, "k": 8.0, "c": 4.0, "forbidden": true}]
+        }"#;
+        let graph = InputHandler::parse_mut(data).unwrap();
+        assert!(graph.is_required(0) && graph.is_upgrade_forbidden(0) && !graph.is_forbidden(0));
+        assert!(graph.is_upgrade_required(1) && !graph.is_required(1));
+        assert!(graph.is_forbidden(2));
+        let read = InputHandler::parse_mut(&OutputHandler::instance_json(&graph).to_string()).unwrap();
+        (0..3).for_each(|edge| assert_eq!(read.edge(edge), graph.edge(edge)));
+        assert!(matches!(InputHandler::parse_mut(&data.replace(r#""forbidden": true"#, r#""forbidden": 1"#)), Err(CbstuError::MalformedInput(_))));
+    }
+
+    #[test]
+    fn test_parse_directed() {
+        let data = r#"{
+            "directed": true,
+            "nodes": [{"id": 0, "x": 0.0, "y": 0.0}, {"id": 1, "x": 1.0, "y": 1.0}, {"id": 2, "x": 2.0, "y": 2.0}],
+            "links": [{"sourceId": 0, "targetId": 1, "k": 5.0, "kBar": 9.0, "c": 6.0},
+                      {"sourceId": 1, "targetId": 2, "k": 1.0, "kBar": 3.0, "c": 10.0, "directed": false}],
+            "candidates": [{"sourceId": 0, "targetId": 2, "k": 8.0, "c": 4.0}]
+        }"#;
+        let graph = InputHandler::parse_mut(data).unwrap();
+        assert_eq!((graph.is_directed(0), graph.is_directed(1), graph.is_directed(2)), (true, false, true));
+        let duplicated = Util::duplicate_edges(&graph);
+        assert_eq!(duplicated.edges().iter().filter(|&&edge| duplicated.is_directed(edge)).count(), 3);
+        let read = InputHandler::parse_mut(&OutputHandler::instance_json(&graph).to_string()).unwrap();
+        (0..3).for_each(|edge| assert_eq!(read.edge(edge), graph.edge(edge)));
+        assert!(!InputHandler::parse_mut(&data.replace(r#""directed": true,"#, "")).unwrap().is_directed(0));
+        assert!(matches!(InputHandler::parse_mut(&data.replace(r#""directed": false"#, r#""directed": 0"#)), Err(CbstuError::MalformedInput(_))));
+    }
+
+    #[test]
+    fn test_parse_cost_vectors() {
+        let data = r#"{
+            "nodes": [{"id": 0, "x": 0.0, "y": 0.0}, {"id": 1, "x": 1.0, "y": 1.0}, {"id": 2, "x": 2.0, "y": 2.0}],
+            "links": [{"sourceId": 0, "targetId": 1, "k": 5.0, "kBar": 9.0, "c": [6.0, 2.0, 1.0]},
+                      {"sourceId": 1, "targetId": 2, "k": 1.0, "tiers": [{"kBar": 3.0, "c": [2.0, 1.0]}, {"kBar": 7.0, "c": 5.0}]}],
+            "candidates": [{"sourceId": 0, "targetId": 2, "k": 8.0, "c": 4.0}]
+        }"#;
+        let graph = InputHandler::parse_mut(data).unwrap();
+        assert_eq!(graph.costs(0), vec![6.0, 2.0, 1.0]);
+        assert_eq!(graph.edge(1).tier_cost_vectors(), vec![vec![2.0, 1.0], vec![5.0]]);
+        assert_eq!(graph.costs(2), vec![4.0]);
+        let duplicated = Util::duplicate_edges(&graph);
+        assert_eq!(duplicated.edges().iter().map(|&edge| duplicated.resource_cost(edge, 1)).sum::<f64>(), 3.0);
+        let read = InputHandler::parse_mut(&OutputHandler::instance_json(&graph).to_string()).unwrap();
+        (0..3).for_each(|edge| assert_eq!(read.edge(edge), graph.edge(edge)));
+        assert!(matches!(InputHandler::parse_mut(&data.replace("[6.0, 2.0, 1.0]", "[]")), Err(CbstuError::MalformedInput(_))));
+        assert!(matches!(InputHandler::parse_mut(&data.replace("[6.0, 2.0, 1.0]", r#"[6.0, "2"]"#)), Err(CbstuError::MalformedInput(_))));
+    }
+
+    #[test]
+    fn test_parse_bundles() {
+        let data = r#"{
+            "nodes": [{"id": 0, "x": 0.0, "y": 0.0}, {"id": 1, "x": 1.0, "y": 1.0}, {"id": 2, "x": 2.0, "y": 2.0}],
+            "links": [{"sourceId": 0, "targetId": 1, "k": 5.0, "kBar": 9.0, "c": 0.0},
+                      {"sourceId": 1, "targetId": 2, "k": 1.0, "kBar": 3.0, "c": 2.0},
+                      {"sourceId": 0, "targetId": 2, "k": 1.0, "kBar": 3.0, "c": 1.0}],
+            "bundles": [{"links": [0, 2], "c": 7.0}]
+        }"#;
+        let (graph, costs) = InputHandler::parse_bundled(data).unwrap();
+        assert_eq!(costs, vec![7.0]);
+        assert_eq!((graph.bundle(0), graph.bundle(1), graph.bundle(2)), (Some(0), None, Some(0)));
+        let duplicated = Util::duplicate_edges(&graph);
+        assert_eq!(duplicated.edges().iter().filter(|&&edge| duplicated.bundle(edge).is_some()).count(), 2);
+        assert!(matches!(InputHandler::parse_bundled(&data.replace("[0, 2]", "[0, 3]")), Err(CbstuError::MalformedInput(_))));
+        assert!(matches!(InputHandler::parse_bundled(&data.replace(r#"[{"links": [0, 2], "c": 7.0}]"#, r#"[{"links": [0], "c": 7.0}, {"links": [0], "c": 1.0}]"#)), Err(CbstuError::MalformedInput(_))));
+        assert!(matches!(InputHandler::parse_bundled(&data.replace(r#""c": 7.0"#, r#""c": -7.0"#)), Err(CbstuError::MalformedInput(_))));
+    }
+
+    #[test]
+    fn test_parse_discount() {
+        let data = r#"{
+            "nodes": [{"id": 0, "x": 0.0, "y": 0.0}, {"id": 1, "x": 1.0, "y": 1.0}],
+            "links": [{"sourceId": 0, "targetId": 1, "k": 2.0, "kBar": 4.0, "c": 3.0}],
+            "discount": {"basis": "sum", "points": [{"total": 10.0, "c": 10.0}, {"total": 30.0, "c": 20.0}]}
+        }"#;
+        let (_, discount) = InputHandler::parse_discounted(data).unwrap();
+        let discount = discount.unwrap();
+        assert_eq!(discount.basis(), DiscountBasis::Sum);
+        assert_eq!(discount.points(), &[(10.0, 10.0), (30.0, 20.0)]);
+        assert!(InputHandler::parse_discounted(&data.replace(r#",
+            "discount": {"basis": "sum", "points": [{"total": 10.0, "c": 10.0}, {"total": 30.0, "c": 20.0}]}"#, "")).unwrap().1.is_none());
+        assert!(matches!(InputHandler::parse_discounted(&data.replace(r#""sum""#, r#""max""#)), Err(CbstuError::MalformedInput(_))));
+        assert!(matches!(InputHandler::parse_discounted(&data.replace(r#""c": 20.0"#, r#""c": 40.0"#)), Err(CbstuError::MalformedInput(_))));
+    }
+
+    #[test]
+    fn test_parse_terminals() {
+        let data = r#"{
+            "nodes": [{"id": 0, "x": 0.0, "y": 0.0}, {"id": 1, "x": 1.0, "y": 1.0}, {"id": 2, "x": 2.0, "y": 2.0}],
+            "links": [{"sourceId": 0, "targetId": 1, "k": 2.0, "kBar": 4.0, "c": 3.0},
+                      {"sourceId": 1, "targetId": 2, "k": 1.0, "kBar": 3.0, "c": 10.0}],
+            "terminals": [0, 2]
+        }"#;
+        assert_eq!(InputHandler::parse_terminals(data).unwrap().1, vec![0, 2]);
+        assert_eq!(InputHandler::parse_terminals(&data.replace(r#",
+            "terminals": [0, 2]"#, "")).unwrap().1, vec![0, 1, 2]);
+        assert!(matches!(InputHandler::parse_terminals(&data.replace("[0, 2]", "[0, 3]")), Err(CbstuError::MalformedInput(_))));
+        assert!(matches!(InputHandler::parse_terminals(&data.replace("[0, 2]", "[0, -1]")), Err(CbstuError::MalformedInput(_))));
+    }
+
+    #[test]
+    fn test_malformed_input() {
+        assert!(matches!(InputHandler::parse_mut("{"), Err(CbstuError::MalformedInput(_))));
+        assert!(matches!(InputHandler::parse_mut(r#"{"nodes": [{"id": 0, "x": 0.0, "y": 0.0}], "links": [{"sourceId": 0, "targetId": 3, "k": 1, "kBar": 2, "c": 1}]}"#), Err(CbstuError::MalformedInput(_))));
+        assert!(matches!(InputHandler::parse_mut(r#"{"nodes": [{"id": 0, "x": 0.0, "y": 0.0}], "links": [{"sourceId": 0, "targetId": 0, "k": 1, "c": 1}]}"#), Err(CbstuError::MalformedInput(_))));
+        assert!(matches!(InputHandler::parse_mut(r#"{"nodes": [{"id": 0, "x": 0.0, "y": 0.0}], "links": []}"#), Err(CbstuError::EmptyEdgeSet)));
+        assert!(matches!(InputHandler::read_mut("does/not/exist.json"), Err(CbstuError::Io(_))));
+    }
+
+    #[test]
+    fn test_solution_round_trip() {
+        let mut graph = InputHandler::parse_mut(r#"{
+            "nodes": [{"id": 0, "x": 0.0, "y": 0.0}, {"id": 1, "x": 1.0, "y": 1.0}, {"id": 2, "x": 2.0, "y": 2.0}],
+            "links": [{"sourceId": 0, "targetId": 1, "k": 2.0, "kBar": 4.0, "c": 3.0},
+                      {"sourceId": 1, "targetId": 2, "k": 5.0, "kBar": 6.0, "c": 1.0}]
+        }"#).unwrap();
+        graph.inverse_weights();
+        let graph = Util::duplicate_edges(&graph);
+        let solution = solver_by_name("berman").unwrap().solve(&graph, 3.0).unwrap();
+        let json = OutputHandler::solution_json(&solution, "berman", 3.0);
+        assert_eq!(json["bottleneck"], 4.0);
+        let read = InputHandler::parse_solution(&json.to_string(), &graph).unwrap();
+        assert!(read.is_feasible());
+        assert_eq!(read.cost(), solution.cost());
+        assert_eq!(read.bottleneck(), solution.bottleneck());
+        assert_eq!(read.upgraded_edges(), solution.upgraded_edges());
+    }
+}
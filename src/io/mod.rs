@@ -1 +1,3 @@
-pub mod input_handler;
\ No newline at end of file
+pub mod input_handler;
+pub mod output_handler;
+pub mod generator;
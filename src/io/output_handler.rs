@@ -0,0 +1,370 @@
+use std::fs;
+use serde_json::{json, Value};
+use crate::algorithms::constrained_bottleneck_spanning_tree::bundles::Bundles;
+use crate::algorithms::constrained_bottleneck_spanning_tree::balanced::BalancedSolution;
+use crate::algorithms::constrained_bottleneck_spanning_tree::hop_limit::HopBound;
+use crate::algorithms::constrained_bottleneck_spanning_tree::lexicographic::LexicographicSolution;
+use crate::algorithms::min_sum_spanning_tree::upgrades::MinSumSolution;
+use crate::algorithms::constrained_bottleneck_spanning_tree::min_budget::BudgetSolution;
+use crate::algorithms::constrained_bottleneck_spanning_tree::node_upgrade::NodeUpgradeSolution;
+use crate::algorithms::constrained_bottleneck_spanning_tree::pareto::ParetoPoint;
+use crate::algorithms::constrained_bottleneck_spanning_tree::solver::{Solution, SolutionStatus};
+use crate::algorithms::continuous_bottleneck_spanning_tree::parametric::ContinuousSolution;
+use crate::algorithms::util::BoundedSolution;
+use crate::datastructures::graph::edge::Edge;
+use crate::datastructures::graph::mutable_graph::MutableGraph;
+use crate::error::CbstuError;
+
+pub struct OutputHandler();
+
+impl OutputHandler {
+    /// JSON representation of a solution of the inverted, duplicated graph. Weights are written as capacities again,
+    /// so the output uses the same `k`/`c` conventions as the input files.
+    pub fn solution_json(solution: &Solution, algorithm: &str, budget: f64) -> Value {
+        let links: Vec<Value> = match solution.tree() {
+            Some(tree) => tree.edges().iter().map(|&edge| Self::tree_link_json(&tree.edge(edge))).collect(),
+            None => Vec::new(),
+        };
+        let upgrades: Vec<Value> = solution.upgraded_edges().iter().map(Self::tree_link_json).collect();
+        let builds: Vec<Value> = solution.built_edges().iter().map(Self::tree_link_json).collect();
+        let status = match solution.status() {
+            SolutionStatus::Feasible => "feasible",
+            SolutionStatus::Infeasible => "infeasible",
+        };
+        json!({
+            "algorithm": algorithm,
+            "budget": budget,
+            "status": status,
+            "bottleneck": -solution.bottleneck(),
+            "cost": solution.cost(),
+            "links": links,
+            "upgrades": upgrades,
+            "builds": builds,
+        })
+    }
+
+    /// JSON representation of the cheapest upgrades reaching the target capacity `target`.
+    pub fn budget_solution_json(solution: &BudgetSolution, target: f64) -> Value {
+        let tree = solution.tree();
+        let links: Vec<Value> = tree.edges().iter().map(|&edge| Self::tree_link_json(&tree.edge(edge))).collect();
+        let upgrades: Vec<Value> = solution.upgraded_edges().iter().map(Self::tree_link_json).collect();
+        let builds: Vec<Value> = solution.built_edges().iter().map(Self::tree_link_json).collect();
+        json!({
+            "target": target,
+            "bottleneck": -solution.bottleneck(),
+            "cost": solution.cost(),
+            "links": links,
+            "upgrades": upgrades,
+            "builds": builds,
+        })
+    }
+
+    /// JSON representation of a budget–bottleneck frontier, one breakpoint per entry.
+    pub fn frontier_json(frontier: &[ParetoPoint]) -> Value {
+        let points: Vec<Value> = frontier.iter().map(|point| json!({
+            "bottleneck": -point.bottleneck(),
+            "cost": point.cost(),
+            "upgrades": point.tree().number_of_edges_upgraded(),
+        })).collect();
+        Value::Array(points)
+    }
+
+    /// JSON representation of a continuous upgrade solution, with how far each tree edge is upgraded.
+    pub fn continuous_solution_json(solution: &ContinuousSolution, budget: f64) -> Value {
+        let tree = solution.tree();
+        let links: Vec<Value> = tree.edges().iter().map(|&edge| {
+            let (either, other) = tree.endpoints(edge);
+            json!({"sourceId": either, "targetId": other, "k": tree.weight(edge)})
+        }).collect();
+        let upgrades: Vec<Value> = solution.upgrades().iter().map(|upgrade| {
+            let (either, other) = tree.endpoints(upgrade.edge());
+            json!({
+                "sourceId": either,
+                "targetId": other,
+                "from": upgrade.from(),
+                "to": upgrade.to(),
+                "c": upgrade.cost(),
+            })
+        }).collect();
+        json!({
+            "budget": budget,
+            "bottleneck": solution.capacity(),
+            "cost": solution.cost(),
+            "links": links,
+            "upgrades": upgrades,
+        })
+    }
+
+    /// JSON representation of a node upgrade solution.
+    pub fn node_upgrade_json(solution: &NodeUpgradeSolution, budget: f64) -> Value {
+        let tree = solution.tree();
+        let links: Vec<Value> = tree.edges().iter().map(|&edge| {
+            let (either, other) = tree.endpoints(edge);
+            json!({"sourceId": either, "targetId": other, "k": tree.weight(edge)})
+        }).collect();
+        json!({
+            "budget": budget,
+            "bottleneck": solution.capacity(),
+            "cost": solution.cost(),
+            "optimal": solution.is_optimal(),
+            "upgradedNodes": solution.upgraded_nodes(),
+            "links": links,
+        })
+    }
+
+    /// JSON representation of a multi-resource solution with its spending per resource and the proven bound.
+    pub fn multi_resource_json(solution: &BoundedSolution, budgets: &[f64]) -> Value {
+        let tree = solution.tree();
+        let links: Vec<Value> = tree.edges().iter().map(|&edge| {
+            let mut link = Self::tree_link_json(&tree.edge(edge));
+            link["c"] = Self::costs_json(&tree.costs(edge));
+            link
+        }).collect();
+        json!({
+            "budgets": budgets,
+            "bottleneck": -solution.bottleneck(),
+            "bound": -solution.bound(),
+            "optimal": solution.is_optimal(),
+            "costs": solution.costs(),
+            "links": links,
+        })
+    }
+
+    /// JSON representation of a solution with upgrade bundles, listing the bundles whose charge is paid.
+    pub fn bundle_solution_json(solution: &BoundedSolution, budget: f64) -> Value {
+        let tree = solution.tree();
+        let links: Vec<Value> = tree.edges().iter().map(|&edge| Self::tree_link_json(&tree.edge(edge))).collect();
+        json!({
+            "budget": budget,
+            "bottleneck": -solution.bottleneck(),
+            "bound": -solution.bound(),
+            "optimal": solution.is_optimal(),
+            "cost": solution.cost(),
+            "bundles": Bundles::paid(tree),
+            "links": links,
+        })
+    }
+
+    /// JSON representation of a solution within the degree bounds, with the degree of every node in the tree.
+    pub fn degree_solution_json(solution: &BoundedSolution, budget: f64) -> Value {
+        let tree = solution.tree();
+        let links: Vec<Value> = tree.edges().iter().map(|&edge| Self::tree_link_json(&tree.edge(edge))).collect();
+        let mut degrees = vec![0; tree.nodes().len()];
+        tree.edges().iter().map(|&edge| tree.endpoints(edge)).for_each(|(u, v)| {
+            degrees[u] += 1;
+            degrees[v] += 1;
+        });
+        json!({
+            "budget": budget,
+            "bottleneck": -solution.bottleneck(),
+            "bound": -solution.bound(),
+            "optimal": solution.is_optimal(),
+            "cost": solution.cost(),
+            "degrees": degrees,
+            "links": links,
+        })
+    }
+
+    /// JSON representation of a solution within the hop limit, with the depth below the root or the diameter reached.
+    pub fn hop_solution_json(solution: &BoundedSolution, bound: HopBound, budget: f64) -> Value {
+        let tree = solution.tree();
+        let links: Vec<Value> = tree.edges().iter().map(|&edge| Self::tree_link_json(&tree.edge(edge))).collect();
+        let hops = bound.measure(tree);
+        let mut value = json!({
+            "budget": budget,
+            "bottleneck": -solution.bottleneck(),
+            "bound": -solution.bound(),
+            "optimal": solution.is_optimal(),
+            "cost": solution.cost(),
+        });
+        match bound {
+            HopBound::Depth { root, hops } => {
+                value["root"] = json!(root);
+                value["maxDepth"] = json!(hops);
+                value["depth"] = json!(hops);
+            }
+            HopBound::Diameter(hops) => {
+                value["maxDiameter"] = json!(hops);
+                value["diameter"] = json!(hops);
+            }
+        }
+        value["links"] = json!(links);
+        value
+    }
+
+    /// JSON representation of a min-sum solution with its optimality gap. Its weights are latencies, not inverted.
+    pub fn min_sum_solution_json(solution: &MinSumSolution, budget: f64) -> Value {
+        let tree = solution.tree();
+        let links: Vec<Value> = tree.edges().iter().map(|&edge| {
+            let mut link = Self::tree_link_json(&tree.edge(edge));
+            link["k"] = json!(tree.weight(edge));
+            link
+        }).collect();
+        json!({
+            "budget": budget,
+            "weight": solution.weight(),
+            "bound": solution.bound(),
+            "gap": solution.gap(),
+            "optimal": solution.is_optimal(),
+            "cost": solution.cost(),
+            "links": links,
+        })
+    }
+
+    /// JSON representation of a balanced solution with the range of its capacities.
+    pub fn balanced_solution_json(solution: &BalancedSolution, algorithm: &str, budget: f64) -> Value {
+        let tree = solution.tree();
+        let links: Vec<Value> = tree.edges().iter().map(|&edge| Self::tree_link_json(&tree.edge(edge))).collect();
+        json!({
+            "algorithm": algorithm,
+            "budget": budget,
+            "bottleneck": -solution.bottleneck(),
+            "peak": -solution.peak(),
+            "spread": solution.spread(),
+            "cost": solution.cost(),
+            "links": links,
+        })
+    }
+
+    /// JSON representation of a lexicographic solution with the capacities of the tree from the worst up.
+    pub fn lexicographic_solution_json(solution: &LexicographicSolution, budget: f64) -> Value {
+        let tree = solution.tree();
+        let links: Vec<Value> = tree.edges().iter().map(|&edge| Self::tree_link_json(&tree.edge(edge))).collect();
+        let ranks: Vec<f64> = solution.ranks().iter().map(|&rank| -rank).collect();
+        json!({
+            "budget": budget,
+            "bottleneck": -solution.bottleneck(),
+            "ranks": ranks,
+            "proven": solution.proven(),
+            "optimal": solution.is_optimal(),
+            "cost": solution.cost(),
+            "links": links,
+        })
+    }
+
+    /// JSON representation of a Steiner tree connecting `terminals`.
+    pub fn steiner_solution_json(solution: &BoundedSolution, terminals: &[usize], budget: f64) -> Value {
+        let tree = solution.tree();
+        let links: Vec<Value> = tree.edges().iter().map(|&edge| Self::tree_link_json(&tree.edge(edge))).collect();
+        json!({
+            "budget": budget,
+            "terminals": terminals,
+            "bottleneck": -solution.bottleneck(),
+            "bound": -solution.bound(),
+            "optimal": solution.is_optimal(),
+            "cost": solution.cost(),
+            "links": links,
+        })
+    }
+
+    /// JSON representation of an instance in the input format read by [`InputHandler`](crate::io::input_handler::InputHandler).
+    pub fn instance_json(graph: &MutableGraph) -> Value {
+        let nodes: Vec<Value> = graph.nodes().iter().map(|node| {
+            let mut value = json!({
+                "id": node.id(),
+                "x": node.x(),
+                "y": node.y(),
+            });
+            if node.get_capacity().is_finite() {
+                value["capacity"] = json!(node.get_capacity());
+            }
+            if node.get_upgraded_capacity().is_finite() {
+                value["upgradedCapacity"] = json!(node.get_upgraded_capacity());
+            }
+            if node.get_multiplier() != 1.0 {
+                value["multiplier"] = json!(node.get_multiplier());
+            }
+            if node.get_upgrade_cost() != 0.0 {
+                value["upgradeCost"] = json!(node.get_upgrade_cost());
+            }
+            if let Some(region) = node.get_region() {
+                value["region"] = json!(region);
+            }
+            if let Some(max_degree) = node.get_max_degree() {
+                value["maxDegree"] = json!(max_degree);
+            }
+            value
+        }).collect();
+        let (candidates, links): (Vec<Edge>, Vec<Edge>) = graph.edges().iter()
+            .map(|&edge| graph.edge(edge))
+            .partition(|edge| edge.is_candidate());
+        let links: Vec<Value> = links.iter().map(|edge| {
+            let (either, other) = edge.endpoints();
+            let mut link = json!({
+                "sourceId": either,
+                "targetId": other,
+                "k": edge.get_weight(),
+                "kBar": edge.get_upgraded_weight(),
+                "c": Self::costs_json(&edge.get_costs()),
+            });
+            if edge.tiers().len() > 1 {
+                link["tiers"] = edge.tiers().iter().zip(edge.tier_cost_vectors())
+                    .map(|(&(k_bar, _), costs)| json!({"kBar": k_bar, "c": Self::costs_json(&costs)})).collect();
+            }
+            Self::constraints_json(edge, &mut link);
+            link
+        }).collect();
+        let mut instance = json!({
+            "nodes": nodes,
+            "links": links,
+        });
+        if !candidates.is_empty() {
+            instance["candidates"] = candidates.iter().map(|edge| {
+                let (either, other) = edge.endpoints();
+                let mut candidate = json!({
+                    "sourceId": either,
+                    "targetId": other,
+                    "k": edge.get_upgraded_weight(),
+                    "c": Self::costs_json(&edge.get_costs()),
+                });
+                if edge.tiers().len() > 1 {
+                    candidate["tiers"] = edge.tiers().iter().zip(edge.tier_cost_vectors())
+                        .map(|(&(k, _), costs)| json!({"k": k, "c": Self::costs_json(&costs)})).collect();
+                }
+                Self::constraints_json(edge, &mut candidate);
+                candidate
+            }).collect();
+        }
+        instance
+    }
+
+    /// A cost as a number, or as an array when it spans several budget resources.
+    fn costs_json(costs: &[f64]) -> Value {
+        match costs {
+            [cost] => json!(cost),
+            costs => json!(costs),
+        }
+    }
+
+    /// Adds the constraint flags of a link that are set, and whether it is directed.
+    fn constraints_json(edge: &Edge, link: &mut Value) {
+        for (key, flag) in [
+            ("required", edge.is_required()),
+            ("forbidden", edge.is_forbidden()),
+            ("upgradeRequired", edge.is_upgrade_required()),
+            ("upgradeForbidden", edge.is_upgrade_forbidden()),
+            ("directed", edge.is_directed()),
+        ] {
+            if flag {
+                link[key] = json!(true);
+            }
+        }
+    }
+
+    pub fn write_json(file_name: &str, value: &Value) -> Result<(), CbstuError> {
+        fs::write(file_name, serde_json::to_string_pretty(value)?)?;
+        Ok(())
+    }
+
+    fn tree_link_json(edge: &Edge) -> Value {
+        let (either, other) = edge.endpoints();
+        json!({
+            "sourceId": either,
+            "targetId": other,
+            "k": -edge.get_weight(),
+            "c": edge.get_cost(),
+            "upgraded": edge.is_upgraded(),
+            "built": edge.is_candidate(),
+        })
+    }
+}
@@ -4,3 +4,4 @@ extern crate core;
 pub mod datastructures;
 pub mod algorithms;
 pub mod io;
+pub mod error;
@@ -12,23 +12,17 @@ mod tests {
 
     #[test]
     fn test_easy_instance() {
-        let mut graph_mut = InputHandler::read_mut("test_data/abilene--D-B-M-N-C-A-N-N_network12_15.json");
+        let mut graph_mut = InputHandler::read_mut("test_data/abilene--D-B-M-N-C-A-N-N_network12_15.json").unwrap();
         graph_mut.inverse_weights();
-        let mut duplicated_graph = Util::duplicate_edges(&graph_mut);
-        let (_, _, bottleneck_big_budget_berman) = Berman::run(&mut duplicated_graph, 10000.0);
-        let (_, _, bottleneck_small_budget_berman) = Berman::run(&mut duplicated_graph, 100.0);
-        let (_, _, bottleneck_big_budget_punnen) = Punnen::run(&mut duplicated_graph, 10000.0);
-        graph_mut = InputHandler::read_mut("test_data/abilene--D-B-M-N-C-A-N-N_network12_15.json");
-        graph_mut.inverse_weights();
-        duplicated_graph = Util::duplicate_edges(&graph_mut);
-        let (_, _, bottleneck_small_budget_punnen) = Punnen::run(&mut duplicated_graph, 100.0);
-        let (_, _, bottleneck_big_budget_edg, _) = EdgeEliminationOld::run(&mut duplicated_graph, 10000.0);
-        let (_, _, bottleneck_small_budget_edg, _) = EdgeEliminationOld::run(&mut duplicated_graph, 100.0);
-        let (_, _, bottleneck_big_budget_ee, _) = FastEdgeElimination::run(duplicated_graph, 10000.0);
-        graph_mut = InputHandler::read_mut("test_data/abilene--D-B-M-N-C-A-N-N_network12_15.json");
-        graph_mut.inverse_weights();
-        duplicated_graph = Util::duplicate_edges(&graph_mut);
-        let (st, _, bottleneck_small_budget_ee, _) = FastEdgeElimination::run(duplicated_graph, 100.0);
+        let duplicated_graph = Util::duplicate_edges(&graph_mut);
+        let (_, _, bottleneck_big_budget_berman) = Berman::run(&duplicated_graph, 10000.0).unwrap();
+        let (_, _, bottleneck_small_budget_berman) = Berman::run(&duplicated_graph, 100.0).unwrap();
+        let (_, _, bottleneck_big_budget_punnen) = Punnen::run(&duplicated_graph, 10000.0).unwrap();
+        let (_, _, bottleneck_small_budget_punnen) = Punnen::run(&duplicated_graph, 100.0).unwrap();
+        let (_, _, bottleneck_big_budget_edg) = EdgeEliminationOld::run(&duplicated_graph, 10000.0).unwrap();
+        let (_, _, bottleneck_small_budget_edg) = EdgeEliminationOld::run(&duplicated_graph, 100.0).unwrap();
+        let (_, _, bottleneck_big_budget_ee) = FastEdgeElimination::run(&duplicated_graph, 10000.0).unwrap();
+        let (st, _, bottleneck_small_budget_ee) = FastEdgeElimination::run(&duplicated_graph, 100.0).unwrap();
         assert_eq!(bottleneck_big_budget_berman, -26.0);
         assert_eq!(bottleneck_small_budget_berman, -14.0);
         assert_eq!(bottleneck_big_budget_edg, -26.0);
@@ -41,13 +35,13 @@ mod tests {
 
     #[test]
     fn test_difficult_instance1() {
-        let mut graph_mut = InputHandler::read_mut("test_data/ta2--D-B-E-N-C-A-N-N_network65_108.json");
+        let mut graph_mut = InputHandler::read_mut("test_data/ta2--D-B-E-N-C-A-N-N_network65_108.json").unwrap();
         graph_mut.inverse_weights();
-        let mut duplicated_graph = Util::duplicate_edges(&graph_mut);
-        let (_, _, bottleneck) = Berman::run(&mut duplicated_graph, 300.0);
-        let (_, _, bottleneck2) = Punnen::run(&mut duplicated_graph, 300.0);
-        let (_, _, bottleneck3, _) = EdgeEliminationOld::run(&mut duplicated_graph, 300.0);
-        let (_, _, bottleneck4, _) = FastEdgeElimination::run(duplicated_graph, 300.0);
+        let duplicated_graph = Util::duplicate_edges(&graph_mut);
+        let (_, _, bottleneck) = Berman::run(&duplicated_graph, 300.0).unwrap();
+        let (_, _, bottleneck2) = Punnen::run(&duplicated_graph, 300.0).unwrap();
+        let (_, _, bottleneck3) = EdgeEliminationOld::run(&duplicated_graph, 300.0).unwrap();
+        let (_, _, bottleneck4) = FastEdgeElimination::run(&duplicated_graph, 300.0).unwrap();
         assert_eq!(bottleneck, -13.0);
         assert_eq!(bottleneck2, -13.0);
         assert_eq!(bottleneck3, -13.0);
@@ -56,38 +50,25 @@ mod tests {
 
     #[test]
     fn test_difficult_instance2() {
-        let mut graph_mut = InputHandler::read_mut("data/wrp4-11_network123_233.json");
-        graph_mut.inverse_weights();
-        let mut duplicated_graph = Util::duplicate_edges(&graph_mut);
-        let (_, _, bottleneck) = Berman::run(&mut duplicated_graph, 1000.0);
-        let (_, _, bottleneck_2) = Punnen::run(&mut duplicated_graph, 1000.0);
-        let (_, _, bottleneck_3, _) = EdgeEliminationOld::run(&mut duplicated_graph, 1000.0);
-        duplicated_graph = Util::duplicate_edges(&graph_mut);
-        let (_, _, bottleneck_small) = Berman::run(&mut duplicated_graph, 100.0);
-        let (_, _, bottleneck_small2) = Punnen::run(&mut duplicated_graph, 100.0);
-        let (_, _, bottleneck_small3, _) = EdgeEliminationOld::run(&mut duplicated_graph, 100.0);
-        duplicated_graph = Util::duplicate_edges(&graph_mut);
-        let (_, _, bottleneck_mid) = Berman::run(&mut duplicated_graph, 700.0);
-        let (_, _, bottleneck_mid2) = Punnen::run(&mut duplicated_graph, 700.0);
-        let (_, _, bottleneck_mid3, _) = EdgeEliminationOld::run(&mut duplicated_graph, 700.0);
-        duplicated_graph = Util::duplicate_edges(&graph_mut);
-        let (_, _, bottleneck_big) = Berman::run(&mut duplicated_graph, 10000.0);
-        let (_, _, bottleneck_big2) = Punnen::run(&mut duplicated_graph, 10000.0);
-        let (_, _, bottleneck_big3, _) = EdgeEliminationOld::run(&mut duplicated_graph, 10000.0);
-        duplicated_graph = Util::duplicate_edges(&graph_mut);
-        let (_, _, bottleneck_4, _) = FastEdgeElimination::run(duplicated_graph, 1000.0);
-        graph_mut = InputHandler::read_mut("data/wrp4-11_network123_233.json");
-        graph_mut.inverse_weights();
-        duplicated_graph = Util::duplicate_edges(&graph_mut);
-        let (_, _, bottleneck_small_4, _) = FastEdgeElimination::run(duplicated_graph, 100.0);
-        graph_mut = InputHandler::read_mut("data/wrp4-11_network123_233.json");
-        graph_mut.inverse_weights();
-        duplicated_graph = Util::duplicate_edges(&graph_mut);
-        let (_, _, bottleneck_mid_4, _) = FastEdgeElimination::run(duplicated_graph, 700.0);
-        graph_mut = InputHandler::read_mut("data/wrp4-11_network123_233.json");
+        let mut graph_mut = InputHandler::read_mut("data/wrp4-11_network123_233.json").unwrap();
         graph_mut.inverse_weights();
-        duplicated_graph = Util::duplicate_edges(&graph_mut);
-        let (_, _, bottleneck_big_4, _) = FastEdgeElimination::run(duplicated_graph, 10000.0);
+        let duplicated_graph = Util::duplicate_edges(&graph_mut);
+        let (_, _, bottleneck) = Berman::run(&duplicated_graph, 1000.0).unwrap();
+        let (_, _, bottleneck_2) = Punnen::run(&duplicated_graph, 1000.0).unwrap();
+        let (_, _, bottleneck_3) = EdgeEliminationOld::run(&duplicated_graph, 1000.0).unwrap();
+        let (_, _, bottleneck_small) = Berman::run(&duplicated_graph, 100.0).unwrap();
+        let (_, _, bottleneck_small2) = Punnen::run(&duplicated_graph, 100.0).unwrap();
+        let (_, _, bottleneck_small3) = EdgeEliminationOld::run(&duplicated_graph, 100.0).unwrap();
+        let (_, _, bottleneck_mid) = Berman::run(&duplicated_graph, 700.0).unwrap();
+        let (_, _, bottleneck_mid2) = Punnen::run(&duplicated_graph, 700.0).unwrap();
+        let (_, _, bottleneck_mid3) = EdgeEliminationOld::run(&duplicated_graph, 700.0).unwrap();
+        let (_, _, bottleneck_big) = Berman::run(&duplicated_graph, 10000.0).unwrap();
+        let (_, _, bottleneck_big2) = Punnen::run(&duplicated_graph, 10000.0).unwrap();
+        let (_, _, bottleneck_big3) = EdgeEliminationOld::run(&duplicated_graph, 10000.0).unwrap();
+        let (_, _, bottleneck_4) = FastEdgeElimination::run(&duplicated_graph, 1000.0).unwrap();
+        let (_, _, bottleneck_small_4) = FastEdgeElimination::run(&duplicated_graph, 100.0).unwrap();
+        let (_, _, bottleneck_mid_4) = FastEdgeElimination::run(&duplicated_graph, 700.0).unwrap();
+        let (_, _, bottleneck_big_4) = FastEdgeElimination::run(&duplicated_graph, 10000.0).unwrap();
         assert_eq!(bottleneck_small, -2.0);
         assert_eq!(bottleneck_small2, -2.0);
         assert_eq!(bottleneck_small3, -2.0);
@@ -108,13 +89,13 @@ mod tests {
 
     #[test]
     fn random_test_equal_results() {
-        let mut graph_mut = InputHandler::read_mut("test_data/germany50--D-B-L-N-C-A-N-N_network50_88.json");
+        let mut graph_mut = InputHandler::read_mut("test_data/germany50--D-B-L-N-C-A-N-N_network50_88.json").unwrap();
         graph_mut.inverse_weights();
-        let mut duplicated_graph = Util::duplicate_edges(&graph_mut);
-        let (_, _, bottleneck) = Berman::run(&mut duplicated_graph, 150.0);
-        let (_, _, bottleneck2) = Punnen::run(&mut duplicated_graph, 150.0);
-        let (_, _, bottleneck3, _) = EdgeEliminationOld::run(&mut duplicated_graph, 150.0);
-        let (_, _, bottleneck4, _) = FastEdgeElimination::run(duplicated_graph, 150.0);
+        let duplicated_graph = Util::duplicate_edges(&graph_mut);
+        let (_, _, bottleneck) = Berman::run(&duplicated_graph, 150.0).unwrap();
+        let (_, _, bottleneck2) = Punnen::run(&duplicated_graph, 150.0).unwrap();
+        let (_, _, bottleneck3) = EdgeEliminationOld::run(&duplicated_graph, 150.0).unwrap();
+        let (_, _, bottleneck4) = FastEdgeElimination::run(&duplicated_graph, 150.0).unwrap();
         assert_eq!(bottleneck, bottleneck2);
         assert_eq!(bottleneck, bottleneck3);
         assert_eq!(bottleneck, bottleneck4);
@@ -122,13 +103,13 @@ mod tests {
 
     #[test]
     fn random_test_equal_results2() {
-        let mut graph_mut = InputHandler::read_mut("test_data/pioro40--D-B-M-N-C-A-N-N_network40_89.json");
+        let mut graph_mut = InputHandler::read_mut("test_data/pioro40--D-B-M-N-C-A-N-N_network40_89.json").unwrap();
         graph_mut.inverse_weights();
-        let mut duplicated_graph = Util::duplicate_edges(&graph_mut);
-        let (_, _, bottleneck) = Berman::run(&mut duplicated_graph, 200.0);
-        let (_, _, bottleneck2) = Punnen::run(&mut duplicated_graph, 200.0);
-        let (_, _, bottleneck3, _) = EdgeEliminationOld::run(&mut duplicated_graph, 200.0);
-        let (_, _, bottleneck4, _) = FastEdgeElimination::run(duplicated_graph, 200.0);
+        let duplicated_graph = Util::duplicate_edges(&graph_mut);
+        let (_, _, bottleneck) = Berman::run(&duplicated_graph, 200.0).unwrap();
+        let (_, _, bottleneck2) = Punnen::run(&duplicated_graph, 200.0).unwrap();
+        let (_, _, bottleneck3) = EdgeEliminationOld::run(&duplicated_graph, 200.0).unwrap();
+        let (_, _, bottleneck4) = FastEdgeElimination::run(&duplicated_graph, 200.0).unwrap();
         assert_eq!(bottleneck, bottleneck2);
         assert_eq!(bottleneck, bottleneck3);
         assert_eq!(bottleneck, bottleneck4);
@@ -142,13 +123,13 @@ mod tests {
             let path = path.to_str().unwrap();
             if path.ends_with(".json") {
                 let budget = rand::thread_rng().gen_range(100.0..1000.0);
-                let mut graph_mut = InputHandler::read_mut(path);
+                let mut graph_mut = InputHandler::read_mut(path).unwrap();
                 graph_mut.inverse_weights();
-                let mut duplicated_graph = Util::duplicate_edges(&graph_mut);
-                let (_, _, bottleneck) = Berman::run(&mut duplicated_graph, budget);
-                let (_, _, bottleneck2) = Punnen::run(&mut duplicated_graph, budget);
-                let (_, _, bottleneck3, _) = EdgeEliminationOld::run(&mut duplicated_graph, budget);
-                let (_, _, bottleneck4, _) = FastEdgeElimination::run(duplicated_graph, budget);
+                let duplicated_graph = Util::duplicate_edges(&graph_mut);
+                let (_, _, bottleneck) = Berman::run(&duplicated_graph, budget).unwrap();
+                let (_, _, bottleneck2) = Punnen::run(&duplicated_graph, budget).unwrap();
+                let (_, _, bottleneck3) = EdgeEliminationOld::run(&duplicated_graph, budget).unwrap();
+                let (_, _, bottleneck4) = FastEdgeElimination::run(&duplicated_graph, budget).unwrap();
                 if bottleneck != bottleneck2 || bottleneck != bottleneck3 || bottleneck != bottleneck4 {
                     panic!("Bottlenecks are not equal for {}, bottleneck Berman {}, bottleneck Punnen {}, bottleneck edge_elm {}, bottleneck EE {}", path, bottleneck, bottleneck2, bottleneck3, bottleneck4);
                 }